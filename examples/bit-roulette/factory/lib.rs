@@ -0,0 +1,364 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+pub use self::bit_roulette_factory::{
+    BitRouletteFactory,
+    BitRouletteFactoryRef,
+    Error,
+    TableClosed,
+    TableCreated,
+    TableDetails,
+};
+pub use registry::{RegistryKey, MAX_PAGE_SIZE};
+
+use idl_contract_extension::ext::DrandEnvironment;
+
+/// paginated lists of tables
+mod registry;
+
+#[ink::contract(env = DrandEnvironment)]
+mod bit_roulette_factory {
+    use crate::{
+        registry::{RegistryKey, TableRegistry},
+        DrandEnvironment,
+    };
+    use bit_roulette::{BitRouletteRef, HouseRules};
+    use idl_contract_extension::{
+        access::{AccessError, Ownable},
+        types::{unique_id, EventConfig},
+    };
+    use ink::{prelude::vec::Vec, storage::Mapping, ToAccountId};
+
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// this function is callable only by the factory owner
+        NotOwner,
+        /// this function is callable only by the factory owner or the table's game master
+        NotGameMaster,
+        /// the rounds of a table must be at least one slot apart
+        InvalidInterval,
+        /// there is no table deployed by the factory at the address
+        UnknownTable,
+        /// the table has already been closed
+        TableAlreadyClosed,
+    }
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    impl From<AccessError> for Error {
+        fn from(_: AccessError) -> Self {
+            Error::NotOwner
+        }
+    }
+
+    /// A table deployed by the factory, and the settings it was deployed with
+    #[derive(Clone, Debug, PartialEq, scale::Decode, scale::Encode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct TableDetails {
+        /// the address of the table
+        pub table: AccountId,
+        /// the account which created the table, its first game master
+        pub creator: AccountId,
+        /// the schedule of the table's rounds
+        pub config: EventConfig,
+        /// the block the table's rounds are counted from
+        pub start_at: BlockNumber,
+        /// the edge and limits of the table
+        pub rules: HouseRules,
+        /// whether the table is listed as active
+        pub active: bool,
+    }
+
+    /// A new table was deployed
+    #[ink(event)]
+    pub struct TableCreated {
+        #[ink(topic)]
+        pub table: AccountId,
+        #[ink(topic)]
+        pub creator: AccountId,
+    }
+
+    /// A table was removed from the active tables
+    #[ink(event)]
+    pub struct TableClosed {
+        #[ink(topic)]
+        pub table: AccountId,
+    }
+
+    /// the factory storage
+    #[ink(storage)]
+    pub struct BitRouletteFactory {
+        /// the admin of the factory, who can close any table and update the table code
+        owner: Ownable,
+        /// the code hash of the uploaded bit roulette contract
+        table_code_hash: Hash,
+        /// the details of each table
+        tables: Mapping<AccountId, TableDetails>,
+        /// the lists of tables
+        registry: TableRegistry,
+    }
+
+    impl BitRouletteFactory {
+        /// Constructor that initializes a factory owned by the caller
+        ///
+        /// * `table_code_hash`: the code hash of the uploaded bit roulette contract
+        ///
+        #[ink(constructor)]
+        pub fn new(table_code_hash: Hash) -> Self {
+            Self {
+                owner: Ownable::new(Self::env().caller()),
+                table_code_hash,
+                tables: Mapping::default(),
+                registry: TableRegistry::default(),
+            }
+        }
+
+        /// deploy a table whose game master is the caller
+        /// the caller funds the table's bankroll by calling `fund` on it
+        /// returns the address of the table
+        ///
+        /// * `config`: the schedule of the table's rounds, whose interval must be non-zero
+        /// * `start_at`: the block the table's rounds are counted from
+        /// * `rules`: the edge and limits of the table
+        ///
+        #[ink(message)]
+        pub fn create_table(
+            &mut self,
+            config: EventConfig,
+            start_at: BlockNumber,
+            rules: HouseRules,
+        ) -> Result<AccountId> {
+            if config.interval == 0 {
+                return Err(Error::InvalidInterval);
+            }
+            let creator = self.env().caller();
+            let factory = self.env().account_id();
+            let salt = unique_id(
+                &self.env().extension().random(),
+                factory.as_ref(),
+                self.registry.len(RegistryKey::All).into(),
+            )
+            .to_le_bytes();
+            let table = BitRouletteRef::new(creator, config.clone(), start_at, rules)
+                .endowment(0)
+                .code_hash(self.table_code_hash)
+                .salt_bytes(salt)
+                .instantiate()
+                .to_account_id();
+            self.register(TableDetails {
+                table,
+                creator,
+                config,
+                start_at,
+                rules: rules.capped(),
+                active: true,
+            });
+            Ok(table)
+        }
+
+        /// remove the table from the active tables
+        /// callable by the factory owner or the table's current game master
+        /// The table itself keeps running, so that pending rounds can be settled
+        /// and payouts withdrawn
+        ///
+        /// * `table`: the address of the table
+        ///
+        #[ink(message)]
+        pub fn close_table(&mut self, table: AccountId) -> Result<()> {
+            let mut details = self.tables.get(table).ok_or(Error::UnknownTable)?;
+            if !details.active {
+                return Err(Error::TableAlreadyClosed);
+            }
+            let caller = self.env().caller();
+            if self.owner.ensure_owner(caller).is_err() {
+                let table_ref: BitRouletteRef =
+                    ink::env::call::FromAccountId::from_account_id(table);
+                if table_ref.get_game_master() != caller {
+                    return Err(Error::NotGameMaster);
+                }
+            }
+            details.active = false;
+            self.tables.insert(table, &details);
+            self.registry.remove(RegistryKey::Active, table);
+            self.env().emit_event(TableClosed { table });
+            Ok(())
+        }
+
+        /// replace the code new tables are deployed with, callable only by the owner
+        /// tables already deployed are not affected
+        ///
+        /// * `code_hash`: the code hash of the uploaded bit roulette contract
+        ///
+        #[ink(message)]
+        pub fn set_table_code_hash(&mut self, code_hash: Hash) -> Result<()> {
+            self.owner.ensure_owner(self.env().caller())?;
+            self.table_code_hash = code_hash;
+            Ok(())
+        }
+
+        /// hand the factory to a new owner, callable only by the owner
+        ///
+        /// * `new_owner`: the account which becomes the owner
+        ///
+        #[ink(message)]
+        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<()> {
+            self.owner.transfer_ownership(self.env().caller(), new_owner)?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_owner(&self) -> AccountId {
+            self.owner.owner()
+        }
+
+        #[ink(message)]
+        pub fn get_table_code_hash(&self) -> Hash {
+            self.table_code_hash
+        }
+
+        /// get the details of a table deployed by the factory
+        #[ink(message)]
+        pub fn get_table(&self, table: AccountId) -> Option<TableDetails> {
+            self.tables.get(table)
+        }
+
+        /// get a page of every table, in the order they were created
+        /// at most `MAX_PAGE_SIZE` tables are returned
+        #[ink(message)]
+        pub fn get_tables(&self, offset: u32, limit: u32) -> Vec<TableDetails> {
+            self.page(RegistryKey::All, offset, limit)
+        }
+
+        /// get a page of the tables which have not been closed
+        /// at most `MAX_PAGE_SIZE` tables are returned
+        #[ink(message)]
+        pub fn get_active_tables(&self, offset: u32, limit: u32) -> Vec<TableDetails> {
+            self.page(RegistryKey::Active, offset, limit)
+        }
+
+        /// get a page of the tables created by the account
+        /// at most `MAX_PAGE_SIZE` tables are returned
+        #[ink(message)]
+        pub fn get_tables_by_creator(
+            &self,
+            creator: AccountId,
+            offset: u32,
+            limit: u32,
+        ) -> Vec<TableDetails> {
+            self.page(RegistryKey::Creator(creator), offset, limit)
+        }
+
+        /// get the number of tables listed under the key
+        #[ink(message)]
+        pub fn get_table_count(&self, key: RegistryKey) -> u32 {
+            self.registry.len(key)
+        }
+
+        /// record a table and list it as active
+        fn register(&mut self, details: TableDetails) {
+            let table = details.table;
+            self.registry.insert(RegistryKey::All, table);
+            self.registry.insert(RegistryKey::Active, table);
+            self.registry.insert(RegistryKey::Creator(details.creator), table);
+            self.env().emit_event(TableCreated {
+                table,
+                creator: details.creator,
+            });
+            self.tables.insert(table, &details);
+        }
+
+        /// the details of a page of the tables listed under the key
+        fn page(&self, key: RegistryKey, offset: u32, limit: u32) -> Vec<TableDetails> {
+            self.registry
+                .page(key, offset, limit)
+                .into_iter()
+                .filter_map(|table| self.tables.get(table))
+                .collect()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink::env::{test, DefaultEnvironment};
+
+        fn setup() -> BitRouletteFactory {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            BitRouletteFactory::new(Hash::from([1; 32]))
+        }
+
+        fn details(table: AccountId, creator: AccountId, interval: u64) -> TableDetails {
+            TableDetails {
+                table,
+                creator,
+                config: EventConfig { initial_slot: 0, interval },
+                start_at: 0,
+                rules: HouseRules { house_edge: 200, max_exposure: 1_000 },
+                active: true,
+            }
+        }
+
+        #[ink::test]
+        fn tables_need_an_interval() {
+            let mut factory = setup();
+            let rules = HouseRules { house_edge: 200, max_exposure: 1_000 };
+            assert_eq!(
+                factory.create_table(EventConfig { initial_slot: 0, interval: 0 }, 0, rules),
+                Err(Error::InvalidInterval)
+            );
+            assert_eq!(factory.get_table_count(RegistryKey::All), 0);
+        }
+
+        #[ink::test]
+        fn tables_are_listed_by_creator_and_activity() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut factory = setup();
+            factory.register(details(accounts.django, accounts.bob, 1));
+            factory.register(details(accounts.eve, accounts.charlie, 5));
+            factory.register(details(accounts.frank, accounts.bob, 10));
+
+            let tables = |page: Vec<TableDetails>| {
+                page.iter().map(|details| details.table).collect::<Vec<_>>()
+            };
+            assert_eq!(
+                tables(factory.get_tables(0, 10)),
+                vec![accounts.django, accounts.eve, accounts.frank]
+            );
+            assert_eq!(
+                tables(factory.get_tables_by_creator(accounts.bob, 0, 10)),
+                vec![accounts.django, accounts.frank]
+            );
+            assert_eq!(factory.get_table(accounts.eve).unwrap().config.interval, 5);
+
+            // the owner can close any table
+            assert_eq!(factory.close_table(accounts.django), Ok(()));
+            assert_eq!(factory.close_table(accounts.django), Err(Error::TableAlreadyClosed));
+            assert_eq!(factory.close_table(accounts.alice), Err(Error::UnknownTable));
+            assert!(!factory.get_table(accounts.django).unwrap().active);
+            assert_eq!(
+                tables(factory.get_active_tables(0, 10)),
+                vec![accounts.frank, accounts.eve]
+            );
+            assert_eq!(factory.get_table_count(RegistryKey::All), 3);
+            assert_eq!(factory.get_table_count(RegistryKey::Active), 2);
+        }
+
+        #[ink::test]
+        fn only_the_owner_manages_the_factory() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut factory = setup();
+            test::set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(factory.set_table_code_hash(Hash::from([2; 32])), Err(Error::NotOwner));
+            assert_eq!(factory.transfer_ownership(accounts.bob), Err(Error::NotOwner));
+
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(factory.set_table_code_hash(Hash::from([2; 32])), Ok(()));
+            assert_eq!(factory.get_table_code_hash(), Hash::from([2; 32]));
+            assert_eq!(factory.transfer_ownership(accounts.bob), Ok(()));
+            assert_eq!(factory.get_owner(), accounts.bob);
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use idl_contract_extension::index::Index;
+use ink::primitives::AccountId;
+
+pub use idl_contract_extension::index::MAX_PAGE_SIZE;
+
+/// The lists tables are registered under
+#[derive(Clone, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+// the scale derive casts the index of variants with fields to u8
+#[allow(clippy::cast_possible_truncation)]
+pub enum RegistryKey {
+    /// every table, in the order they were created
+    All,
+    /// the tables which have not been closed
+    Active,
+    /// the tables created by an account
+    Creator(AccountId),
+}
+
+/// Lists of tables, one per `RegistryKey`
+pub type TableRegistry = Index<RegistryKey>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[ink::test]
+    fn lists_keep_their_order_until_a_table_is_removed() {
+        let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+        let mut registry = TableRegistry::default();
+        for table in [accounts.alice, accounts.bob, accounts.charlie] {
+            assert!(registry.insert(RegistryKey::Active, table));
+        }
+        assert!(!registry.insert(RegistryKey::Active, accounts.bob));
+        assert_eq!(registry.len(RegistryKey::All), 0);
+        assert!(registry.remove(RegistryKey::Active, accounts.alice));
+        assert!(!registry.remove(RegistryKey::Active, accounts.alice));
+        assert_eq!(
+            registry.page(RegistryKey::Active, 0, 10),
+            vec![accounts.charlie, accounts.bob]
+        );
+        assert_eq!(registry.page(RegistryKey::Active, 1, 10), vec![accounts.bob]);
+    }
+}
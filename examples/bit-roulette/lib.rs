@@ -1,269 +1,784 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 use ink::prelude::vec::Vec;
-use etf_contract_utils::ext::EtfEnvironment;
+use idl_contract_extension::ext::DrandEnvironment;
 pub use self::bit_roulette::{
+    Bet,
     BitRoulette,
     BitRouletteRef,
+    Draw,
+    Error,
+    HouseRules,
+    RoundResult,
 };
 
-#[ink::contract(env = EtfEnvironment)]
+#[ink::contract(env = DrandEnvironment)]
 mod bit_roulette {
     use ink::storage::Mapping;
-    // use sha3::Digest;
-    use etf_contract_utils::types::{
+    use idl_contract_extension::access::{AccessError, Ownable};
+    use idl_contract_extension::stats::{GameStats, PlayerStats, RoundStats};
+    use idl_contract_extension::types::{
+        share_of,
+        BasisPoints,
         RoundNumber, 
         SlotNumber,
         EventConfig,
+        MAX_BASIS_POINTS,
     };
-    use crate::{EtfEnvironment, Vec};
+    use crate::{DrandEnvironment, Vec};
+
+    /// a player, their bet and their stake
+    pub type PlayerInput = (AccountId, Bet, Balance);
+
+    /// a player and a round they played
+    pub type PlayerRound = (AccountId, RoundNumber);
+
+    /// the number of pockets of a european wheel, 0 to 36
+    pub const POCKETS: u8 = 37;
+
+    /// the most bets a round accepts, so that it can be settled in a single call
+    pub const MAX_BETS_PER_ROUND: usize = 128;
+
+    /// the furthest a bet can be placed past `get_current_round`, so that payouts are
+    /// not reserved from the bankroll for rounds which are far off
+    pub const MAX_ROUNDS_AHEAD: RoundNumber = 64;
+
+    /// the red pockets, the other numbered pockets are black
+    const RED: [u8; 18] = [1, 3, 5, 7, 9, 12, 14, 16, 18, 19, 21, 23, 25, 27, 30, 32, 34, 36];
 
     #[derive(PartialEq, Debug, scale::Decode, scale::Encode)]
     #[cfg_attr(
         feature = "std",
         derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
     )]
+    // the scale derive casts the index of variants with fields to u8
+    #[allow(clippy::cast_possible_truncation)]
     pub enum Error {
         InvalidCommitment,
         InvalidPlayer,
-        Test(bool),
+        /// the round is closed to guesses, or has nothing left to settle
         InvalidRoundNumber,
+        /// the player already has a guess in the round
         InputExists(RoundNumber),
         InvalidResourceAmount,
         NotGameMaster,
-        InvalidBlockNumber
+        /// the round's block has not been reached, or its pulse is not available
+        InvalidBlockNumber,
+        /// the round's block has passed, so it can only be refunded (see `refund_round`)
+        RoundExpired,
+        /// the bet could win more than the house is willing to risk (see `get_max_bet`)
+        BetTooLarge,
+        /// the bankroll does not hold the amount requested
+        InsufficientBankroll,
+        /// the caller has no payout to withdraw
+        NothingToWithdraw,
+        /// the balance transfer failed
+        TransferFailed,
+        /// the bet is not on the layout of the table
+        InvalidBet,
+        /// the round has as many bets as it accepts (see `MAX_BETS_PER_ROUND`)
+        RoundFull,
+    }
+
+    impl From<AccessError> for Error {
+        fn from(_: AccessError) -> Self {
+            Error::NotGameMaster
+        }
+    }
+
+    /// the bets of a european table
+    /// 0 only wins straight bets and splits on it, every other bet loses
+    #[derive(Clone, Copy, PartialEq, Eq, Debug, scale::Decode, scale::Encode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    // the scale derive casts the index of variants with fields to u8
+    #[allow(clippy::cast_possible_truncation)]
+    pub enum Bet {
+        /// a single pocket, pays 35 to 1
+        Straight(u8),
+        /// two adjacent pockets of the layout (or 0 and one of 1, 2, 3), pays 17 to 1
+        Split(u8, u8),
+        /// a row of three pockets, given by its first pocket (1, 4, ..., 34), pays 11 to 1
+        Street(u8),
+        /// the four pockets meeting at a corner, given by the lowest of them, pays 8 to 1
+        Corner(u8),
+        /// 1-12, 13-24 or 25-36 (1, 2 or 3), pays 2 to 1
+        Dozen(u8),
+        /// a column of the layout, the first holding 1, 4, ..., 34 (1, 2 or 3), pays 2 to 1
+        Column(u8),
+        /// pays 1 to 1
+        Red,
+        /// pays 1 to 1
+        Black,
+        /// pays 1 to 1
+        Odd,
+        /// pays 1 to 1
+        Even,
+        /// 1-18, pays 1 to 1
+        Low,
+        /// 19-36, pays 1 to 1
+        High,
+    }
+
+    impl Bet {
+        /// whether the bet is on the layout of the table
+        pub fn is_valid(&self) -> bool {
+            match *self {
+                Bet::Straight(n) => n < POCKETS,
+                Bet::Split(a, b) => {
+                    let (low, high) = (a.min(b), a.max(b));
+                    if low == 0 {
+                        return (1..=3).contains(&high);
+                    }
+                    let gap = high.saturating_sub(low);
+                    high < POCKETS && (gap == 3 || (gap == 1 && low % 3 != 0))
+                },
+                Bet::Street(n) => n < POCKETS && n % 3 == 1,
+                Bet::Corner(n) => (1..=32).contains(&n) && n % 3 != 0,
+                Bet::Dozen(n) | Bet::Column(n) => (1..=3).contains(&n),
+                _ => true,
+            }
+        }
+
+        /// whether the bet wins when the ball lands in the pocket
+        pub fn covers(&self, pocket: u8) -> bool {
+            if !self.is_valid() || pocket >= POCKETS {
+                return false;
+            }
+            if pocket == 0 {
+                return matches!(*self, Bet::Straight(0) | Bet::Split(0, _) | Bet::Split(_, 0));
+            }
+            match *self {
+                Bet::Straight(n) => pocket == n,
+                Bet::Split(a, b) => pocket == a || pocket == b,
+                Bet::Street(n) => (n..n.saturating_add(3)).contains(&pocket),
+                Bet::Corner(n) => [0, 1, 3, 4].contains(&pocket.wrapping_sub(n)),
+                Bet::Dozen(n) => pocket.div_ceil(12) == n,
+                Bet::Column(n) => pocket % 3 == n % 3,
+                Bet::Red => RED.contains(&pocket),
+                Bet::Black => !RED.contains(&pocket),
+                Bet::Odd => pocket % 2 == 1,
+                Bet::Even => pocket.is_multiple_of(2),
+                Bet::Low => pocket <= 18,
+                Bet::High => pocket > 18,
+            }
+        }
+
+        /// the winnings of a unit stake, before the house edge
+        pub fn odds(&self) -> u32 {
+            match self {
+                Bet::Straight(_) => 35,
+                Bet::Split(_, _) => 17,
+                Bet::Street(_) => 11,
+                Bet::Corner(_) => 8,
+                Bet::Dozen(_) | Bet::Column(_) => 2,
+                _ => 1,
+            }
+        }
     }
 
-    /// the auction storage
+    /// the pulse a round was settled with and the pocket drawn from it,
+    /// which anyone can check with `draw_pocket`
+    #[derive(Clone, Copy, PartialEq, Debug, scale::Decode, scale::Encode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Draw {
+        pub pulse: [u8; 32],
+        pub pocket: u8,
+    }
+
+    /// the outcome of a bet
+    #[derive(Clone, Copy, PartialEq, Debug, scale::Decode, scale::Encode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct RoundResult {
+        pub round: RoundNumber,
+        pub bet: Bet,
+        pub stake: Balance,
+        /// the pocket drawn for the round
+        pub pocket: u8,
+        /// the amount won, zero if the bet lost
+        pub payout: Balance,
+    }
+
+    /// the cut the house takes and the risk it takes
+    #[derive(Clone, Copy, PartialEq, Debug, scale::Decode, scale::Encode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct HouseRules {
+        /// the share of the winnings the house keeps
+        pub house_edge: BasisPoints,
+        /// the share of the bankroll a single bet can win
+        pub max_exposure: BasisPoints,
+    }
+
+    impl HouseRules {
+        /// the rules with shares above 100% capped, as a table is deployed with them
+        pub fn capped(self) -> Self {
+            HouseRules {
+                house_edge: self.house_edge.min(MAX_BASIS_POINTS),
+                max_exposure: self.max_exposure.min(MAX_BASIS_POINTS),
+            }
+        }
+    }
+
+    /// a roulette table
     #[ink(storage)]
     pub struct BitRoulette {
-        /// the controller of the mine clock, calls to the clock must be proxied through it
-        game_master: AccountId,
+        /// the admin of the table, who funds the bankroll
+        game_master: Ownable,
         /// the block number when the contract was created
         created_at: BlockNumber,
         /// the interval (in slots) that this clock ticks
         interval: SlotNumber,
         /// the initial slot number, when the first event should happen 
         initial_slot: SlotNumber,
-        /// the current round number
-        current_round: RoundNumber,
-        /// a map between rounds (slot ids) and player moves for the upcoming (next) event
-        /// this can be cleared after each successive clock advance
-        round_input: Mapping<RoundNumber, Vec<(AccountId, u8)>>,
-        /// the amount of IRON each player has
-        results: Mapping<AccountId, Vec<(RoundNumber, u8)>>,
+        /// a map between rounds and the guesses waiting for the round's pulse,
+        /// cleared when the round is settled
+        round_input: Mapping<RoundNumber, Vec<PlayerInput>>,
+        /// the outcome of the bets of each player in each round
+        results: Mapping<PlayerRound, Vec<RoundResult>>,
+        /// the totals of each player and round, and the leaderboard
+        stats: GameStats,
+        /// the draw of each settled round
+        draws: Mapping<RoundNumber, Draw>,
+        /// the odds and limits of the table
+        rules: HouseRules,
+        /// the funds the house can pay winners from,
+        /// excluding payouts already owed or reserved for unsettled guesses
+        bankroll: Balance,
+        /// the payouts reserved for the guesses waiting to be settled, should they all win
+        reserved: Balance,
+        /// the payouts won but not yet withdrawn, by player
+        payouts: Mapping<AccountId, Balance>,
+        /// the sum of the payouts owed
+        owed: Balance,
     }
 
     impl BitRoulette {
     
-        /// TODO: interval must be non-zero
         /// Constructor that initializes a new game of roulette
+        /// the bankroll starts empty, the game master funds it with `fund`
+        ///
+        /// * `config`: the clock of the table, an interval of 0 ticks every slot
+        /// * `rules`: the edge and limits of the table, shares above 100% are capped
+        ///
         #[ink(constructor)]
         pub fn new(
             game_master: AccountId,
             config: EventConfig,
             start_at: BlockNumber,
+            rules: HouseRules,
         ) -> Self {
             Self {
-                game_master,
+                game_master: Ownable::new(game_master),
                 created_at: start_at,
-                interval: config.interval,
+                interval: config.interval.max(1),
                 initial_slot: config.initial_slot,
-                current_round: 0, 
                 round_input: Mapping::default(),
                 results: Mapping::default(),
+                stats: GameStats::default(),
+                draws: Mapping::default(),
+                rules: rules.capped(),
+                bankroll: 0,
+                reserved: 0,
+                payouts: Mapping::default(),
+                owed: 0,
             }
         }
 
+        /// add the transferred value to the bankroll, callable only by the game master
+        #[ink(message, payable)]
+        pub fn fund(&mut self) -> Result<(), Error> {
+            self.game_master.ensure_owner(self.env().caller())?;
+            self.bankroll = self.bankroll.saturating_add(self.env().transferred_value());
+            Ok(())
+        }
+
+        /// take funds out of the bankroll, callable only by the game master
+        /// payouts owed to winners or reserved for unsettled guesses are not part of
+        /// the bankroll, so they cannot be taken
+        ///
+        /// * `amount`: the amount sent to the game master
+        ///
+        #[ink(message)]
+        pub fn withdraw_bankroll(&mut self, amount: Balance) -> Result<(), Error> {
+            self.game_master.ensure_owner(self.env().caller())?;
+            self.bankroll = self
+                .bankroll
+                .checked_sub(amount)
+                .ok_or(Error::InsufficientBankroll)?;
+            self.env()
+                .transfer(self.game_master.owner(), amount)
+                .map_err(|_| Error::TransferFailed)
+        }
+
+        /// send the caller everything they have won
+        /// returns the amount sent
+        #[ink(message)]
+        pub fn withdraw(&mut self) -> Result<Balance, Error> {
+            let caller = self.env().caller();
+            let amount = self.payouts.take(caller).ok_or(Error::NothingToWithdraw)?;
+            self.owed = self.owed.saturating_sub(amount);
+            self.env()
+                .transfer(caller, amount)
+                .map_err(|_| Error::TransferFailed)?;
+            Ok(amount)
+        }
+
+        /// hand the table to a new game master, callable only by the game master
+        /// the bankroll goes with it
+        ///
+        /// * `new_game_master`: the account which becomes the game master
+        ///
+        #[ink(message)]
+        pub fn transfer_game_master(&mut self, new_game_master: AccountId) -> Result<(), Error> {
+            self.game_master.transfer_ownership(self.env().caller(), new_game_master)?;
+            Ok(())
+        }
+
         #[ink(message)]
-        pub fn get_current_round_input(&self) -> Option<Vec<(AccountId, u8)>> {
-            self.round_input.get(&self.current_round)
+        pub fn get_game_master(&self) -> AccountId {
+            self.game_master.owner()
+        }
+
+        #[ink(message)]
+        pub fn get_rules(&self) -> HouseRules {
+            self.rules
+        }
+
+        #[ink(message)]
+        pub fn get_bankroll(&self) -> Balance {
+            self.bankroll
+        }
+
+        /// get the payouts won by the player and not yet withdrawn
+        #[ink(message)]
+        pub fn get_payout(&self, who: AccountId) -> Balance {
+            self.payouts.get(who).unwrap_or(0)
+        }
+
+        /// get the sum of the payouts owed to every player
+        #[ink(message)]
+        pub fn get_owed(&self) -> Balance {
+            self.owed
+        }
+
+        /// get the sum of the payouts reserved for unsettled guesses
+        #[ink(message)]
+        pub fn get_reserved(&self) -> Balance {
+            self.reserved
+        }
+
+        /// get the largest stake accepted on the bet, whose winnings are at most
+        /// `max_exposure` of the bankroll
+        #[ink(message)]
+        pub fn get_max_bet(&self, bet: Bet) -> Balance {
+            share_of(self.bankroll, self.rules.max_exposure)
+                .checked_div(Balance::from(bet.odds()))
+                .unwrap_or(0)
+        }
+
+        /// get the amount paid for a winning stake on the bet:
+        /// the stake and its winnings, less the edge
+        #[ink(message)]
+        pub fn get_payout_for(&self, bet: Bet, stake: Balance) -> Balance {
+            let winnings = stake.saturating_mul(Balance::from(bet.odds()));
+            let paid_share = MAX_BASIS_POINTS.saturating_sub(self.rules.house_edge);
+            stake.saturating_add(share_of(winnings, paid_share))
+        }
+
+        /// get the draw the round was settled with
+        #[ink(message)]
+        pub fn get_draw(&self, round: RoundNumber) -> Option<Draw> {
+            self.draws.get(round)
+        }
+
+        #[ink(message)]
+        pub fn get_current_round_input(&self) -> Option<Vec<PlayerInput>> {
+            self.round_input.get(self.get_current_round())
+        }
+
+        /// get the guesses waiting for the round to be settled
+        #[ink(message)]
+        pub fn get_round_input(&self, round: RoundNumber) -> Option<Vec<PlayerInput>> {
+            self.round_input.get(round)
         }
 
         /// get the next slot number
         #[ink(message)]
         pub fn get_next_slot(&self) -> SlotNumber {
-            self.initial_slot + self.current_round as u64 * self.interval
+            self.initial_slot
+                .saturating_add(self.get_current_round().saturating_mul(self.interval))
         }
 
+        /// get the earliest round still open to guesses
         #[ink(message)]
         pub fn get_current_round(&self) -> RoundNumber {
-            self.current_round
+            let now = self.env().block_number();
+            if now < self.created_at {
+                return 0;
+            }
+            let elapsed = u64::from(now.saturating_sub(self.created_at));
+            elapsed.checked_div(self.interval).unwrap_or(elapsed).saturating_add(1)
         }
 
+        /// get the block of the round, whose pulse settles it
+        #[ink(message)]
+        pub fn get_round_block(&self, round: RoundNumber) -> BlockNumber {
+            let offset = round.saturating_mul(self.interval);
+            self.created_at.saturating_add(offset.try_into().unwrap_or(BlockNumber::MAX))
+        }
+
+        /// get the outcome of the player's bets in the round,
+        /// or in the last round they played when no round is given
         #[ink(message)]
         pub fn get_results(
             &self, 
             who: AccountId, 
             round: Option<RoundNumber>,
-        ) -> Option<Vec<(RoundNumber, u8)>> {
-            let optional_results: Option<Vec<(RoundNumber, u8)>> = self.results.get(who);
+        ) -> Option<Vec<RoundResult>> {
+            let round = match round {
+                Some(round) => round,
+                None => self.results_round(who)?,
+            };
+            self.results.get((who, round))
+        }
 
-            if round.is_none() {
-                return optional_results;
-            }
+        /// get the player's totals, streaks and winnings over every round they played
+        #[ink(message)]
+        pub fn get_player_stats(&self, who: AccountId) -> PlayerStats {
+            self.stats.player(who)
+        }
 
-            if let Some(results) = optional_results {
-                if let Some(r) = round {
-                    return Some(results.iter()
-                        .filter(|res| res.0.eq(&r))
-                        .map(|res| *res)
-                        .collect::<Vec<_>>()
-                    );
-                }
-            }
-            None
+        /// get the totals of every player of the round,
+        /// kept for the last `ROUND_HISTORY` rounds settled
+        #[ink(message)]
+        pub fn get_round_stats(&self, round: RoundNumber) -> Option<RoundStats> {
+            self.stats.round(round)
         }
 
-        /// place a guess for a future round of roulette
+        /// get the players who won the most, most winnings first
         #[ink(message)]
+        pub fn get_leaderboard(&self) -> Vec<(AccountId, Balance)> {
+            self.stats.leaderboard()
+        }
+
+        /// place a bet of the caller on a future round of roulette,
+        /// a player can place many bets a round
+        /// the transferred value is the stake, which the house keeps if the bet loses.
+        /// If it wins, `get_payout_for(bet, stake)` is owed to the player, so that payout is
+        /// reserved from the bankroll until the round is settled
+        ///
+        /// * `round`: the round to play, from `get_current_round` to `MAX_ROUNDS_AHEAD`
+        ///   rounds after it
+        /// * `bet`: the pockets bet on
+        ///
+        #[ink(message, payable)]
         pub fn play(
             &mut self,
-            player: AccountId,
-            input: u8
+            round: RoundNumber,
+            bet: Bet,
         ) -> Result<(), Error> {
-            verify_game_master(self.env().caller(), self.game_master)?;
-            // we need to make sure it's the right time to call this function
-            let current_block = self.env().block_number();
-            // fast forward to the closest valid round number
-            let diff = current_block.saturating_sub(self.created_at);
-            let actual_slot_number = self.initial_slot + diff as u64;
-            while self.current_round * self.interval < diff as u64 {
-                self.current_round += 1;
-            }
-            let expected_next_slot_number = 
-                self.initial_slot + self.interval * self.current_round;
-            
-            if !expected_next_slot_number.eq(&actual_slot_number) {
+            let player = self.env().caller();
+            // once the round's block is reached its pulse could be known
+            let current_round = self.get_current_round();
+            if round < current_round || round > current_round.saturating_add(MAX_ROUNDS_AHEAD) {
+                return Err(Error::InvalidRoundNumber);
+            }
+            if !bet.is_valid() {
+                return Err(Error::InvalidBet);
+            }
+            let stake = self.env().transferred_value();
+            if stake == 0 {
+                return Err(Error::InvalidResourceAmount);
+            }
+            if stake > self.get_max_bet(bet) {
+                return Err(Error::BetTooLarge);
+            }
+            let mut round_input = self.round_input.get(round).unwrap_or_default();
+            if round_input.len() >= MAX_BETS_PER_ROUND {
+                return Err(Error::RoundFull);
+            }
+            // the house takes the stake, and sets the payout aside in case the bet wins
+            let payout = self.get_payout_for(bet, stake);
+            self.bankroll = self
+                .bankroll
+                .saturating_add(stake)
+                .checked_sub(payout)
+                .ok_or(Error::InsufficientBankroll)?;
+            self.reserved = self.reserved.saturating_add(payout);
+            round_input.push((player, bet, stake));
+            self.round_input.insert(round, &round_input);
+            Ok(())
+        }
+
+        /// draw the round's pocket from its pulse and resolve every bet of the round,
+        /// callable by anyone in the round's block. Winners are owed their payouts,
+        /// and the payouts reserved for the losers return to the bankroll
+        ///
+        /// The round is settled with the pulse ingested in the round's block, which is
+        /// unknown when guesses close. The extension only serves the latest pulse, so a
+        /// round which is not settled in its block is refunded instead (see `refund_round`)
+        ///
+        /// * `round`: the round to settle
+        ///
+        #[ink(message)]
+        pub fn settle(&mut self, round: RoundNumber) -> Result<(), Error> {
+            let round_block = self.get_round_block(round);
+            let now = self.env().block_number();
+            if now > round_block {
+                return Err(Error::RoundExpired);
+            }
+            if now < round_block {
                 return Err(Error::InvalidBlockNumber);
             }
-            // calculates the parity from the expected next slot number
-            // TODO: should check that it is not all 0's (invalid slot)
-            let mut parity: u8 = self.env()
-                .extension()
-                .secret()
-                .to_vec()
-                .iter()
-                .sum();
-            parity = parity % 2;
-            let mut player_results = Vec::new();
-            
-            if let Some(mut player_data) = self.results.get(player) {
-                player_results.append(&mut player_data);
+            let pulse = self.env().extension().random();
+            if pulse.iter().all(|b| *b == 0) {
+                return Err(Error::InvalidBlockNumber);
             }
-            if parity.eq(&(input % 2)) {
-                player_results.push((self.current_round, 1));
-            } else {
-                player_results.push((self.current_round, 0));
+            let round_input = self.round_input.take(round).ok_or(Error::InvalidRoundNumber)?;
+            let pocket = draw_pocket(&pulse);
+            self.draws.insert(round, &Draw { pulse, pocket });
+
+            // the outcome of the bets of each player of the round
+            let mut players: Vec<(AccountId, Vec<RoundResult>)> = Vec::new();
+            for (player, bet, stake) in round_input {
+                let payout = self.get_payout_for(bet, stake);
+                self.reserved = self.reserved.saturating_sub(payout);
+                let won = if bet.covers(pocket) {
+                    self.owed = self.owed.saturating_add(payout);
+                    self.payouts.insert(player, &self.get_payout(player).saturating_add(payout));
+                    payout
+                } else {
+                    self.bankroll = self.bankroll.saturating_add(payout);
+                    0
+                };
+                let result = RoundResult { round, bet, stake, pocket, payout: won };
+                match players.iter_mut().find(|(who, _)| who.eq(&player)) {
+                    Some((_, results)) => results.push(result),
+                    None => players.push((player, Vec::from([result]))),
+                }
             }
 
-            self.results.insert(player, &player_results);
+            for (player, results) in players {
+                let staked = results.iter().map(|result| result.stake).sum();
+                let paid = results.iter().map(|result| result.payout).sum();
+                self.stats.record(player, round, staked, paid);
+                self.results.insert((player, round), &results);
+            }
+            Ok(())
+        }
 
+        /// give back the stakes of a round which was not settled in its block (e.g. its
+        /// block had no pulse), callable by anyone once the block has passed.
+        /// Each stake is owed to its player, and the payouts reserved for the round
+        /// return to the bankroll
+        ///
+        /// * `round`: the round to refund
+        ///
+        #[ink(message)]
+        pub fn refund_round(&mut self, round: RoundNumber) -> Result<(), Error> {
+            if self.env().block_number() <= self.get_round_block(round) {
+                return Err(Error::InvalidBlockNumber);
+            }
+            let round_input = self.round_input.take(round).ok_or(Error::InvalidRoundNumber)?;
+            for (player, bet, stake) in round_input {
+                let payout = self.get_payout_for(bet, stake);
+                self.reserved = self.reserved.saturating_sub(payout);
+                // the stake went to the bankroll when the bet was placed
+                self.bankroll = self.bankroll.saturating_add(payout).saturating_sub(stake);
+                self.owed = self.owed.saturating_add(stake);
+                self.payouts.insert(player, &self.get_payout(player).saturating_add(stake));
+            }
             Ok(())
         }
+
+        /// the last round the player played, if they have played
+        fn results_round(&self, who: AccountId) -> Option<RoundNumber> {
+            let stats = self.stats.player(who);
+            (stats.rounds > 0).then_some(stats.last_round)
+        }
     }
 
-    /// check if the account is the clock's game master
-    pub fn verify_game_master(
-        who: AccountId, 
-        game_master: AccountId
-    ) -> Result<(), Error> {
-        if !who.eq(&game_master) {
-            return Err(Error::NotGameMaster);
+    /// draw a pocket from the pulse, uniformly: the first byte below the largest
+    /// multiple of `POCKETS` picks it, and the pulse is rehashed if no byte is
+    pub fn draw_pocket(pulse: &[u8; 32]) -> u8 {
+        let limit = u8::MAX - u8::MAX % POCKETS;
+        let mut seed = *pulse;
+        loop {
+            if let Some(b) = seed.iter().find(|b| **b < limit) {
+                return b % POCKETS;
+            }
+            let input = seed;
+            ink::env::hash_bytes::<ink::env::hash::Blake2x256>(&input, &mut seed);
         }
-        Ok(())
     }
 
     #[cfg(test)]
     mod tests {
         use super::*;
+        use ink::env::{test, DefaultEnvironment};
+
+        const BANKROLL: Balance = 1_000_000;
+
+        /// a 2% edge, risking at most 10% of the bankroll on a bet
+        fn rules() -> HouseRules {
+            HouseRules { house_edge: 200, max_exposure: 1_000 }
+        }
+
+        /// the existential deposit of the off-chain environment, which the contract keeps
+        const ENDOWMENT: Balance = 1_000_000;
+
+        fn contract_id() -> AccountId {
+            AccountId::from([0x42; 32])
+        }
+
+        /// the balance of the contract, besides its endowment
+        fn contract_balance() -> Balance {
+            test::get_account_balance::<DefaultEnvironment>(contract_id()).unwrap() - ENDOWMENT
+        }
+
+        /// deploy a table run by alice, with a funded bankroll
+        fn setup(config: EventConfig, start_at: BlockNumber) -> BitRoulette {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            test::set_callee::<DefaultEnvironment>(contract_id());
+            test::set_account_balance::<DefaultEnvironment>(contract_id(), ENDOWMENT);
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            let mut contract = BitRoulette::new(accounts.alice, config, start_at, rules());
+            pay(BANKROLL, || contract.fund()).unwrap();
+            contract
+        }
+
+        /// call a payable message with `value`, which reaches the contract's balance
+        /// only if the call succeeds (a failed call is reverted on chain)
+        fn pay<T>(value: Balance, call: impl FnOnce() -> Result<T, Error>) -> Result<T, Error> {
+            test::set_value_transferred::<DefaultEnvironment>(value);
+            let result = call();
+            if result.is_ok() {
+                let balance = ENDOWMENT + contract_balance() + value;
+                test::set_account_balance::<DefaultEnvironment>(contract_id(), balance);
+            }
+            test::set_value_transferred::<DefaultEnvironment>(0);
+            result
+        }
+
+        /// place a bet of the player, staking `value`
+        fn play(
+            contract: &mut BitRoulette,
+            player: AccountId,
+            round: RoundNumber,
+            bet: Bet,
+            value: Balance,
+        ) -> Result<(), Error> {
+            test::set_caller::<DefaultEnvironment>(player);
+            pay(value, || contract.play(round, bet))
+        }
+
+        /// the bankroll, and every payout owed or reserved, are backed by the contract's balance
+        fn assert_solvent(contract: &BitRoulette) {
+            let held = contract.get_bankroll() + contract.get_reserved() + contract.get_owed();
+            assert_eq!(contract_balance(), held);
+        }
+
+        /// every bet on the layout of the table
+        fn all_bets() -> Vec<Bet> {
+            let mut bets = Vec::new();
+            for a in 0..POCKETS {
+                bets.push(Bet::Straight(a));
+                bets.push(Bet::Street(a));
+                bets.push(Bet::Corner(a));
+                bets.push(Bet::Dozen(a));
+                bets.push(Bet::Column(a));
+                for b in a + 1..POCKETS {
+                    bets.push(Bet::Split(a, b));
+                }
+            }
+            bets.extend([Bet::Red, Bet::Black, Bet::Odd, Bet::Even, Bet::Low, Bet::High]);
+            bets.retain(|bet| bet.is_valid());
+            bets
+        }
 
         #[ink::test]
         fn clock_can_play_with_single_player() {
-            let accounts = 
-                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            let mut contract = 
-                BitRoulette::new(
-                    accounts.alice, 
-                    EventConfig { 
-                        initial_slot: 0u64, 
-                        interval: 1u64,
-                    },
-                    0,
-                );
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 1u64 }, 0);
     
-            setup_ext_even_parity();
-            assert_eq!(None, contract.results.get(accounts.alice));
-            ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
-            let _ = contract.play(accounts.alice, 0)
-                .map_err(|_| panic!("{:?}", "the call should work"));
+            setup_ext_pulse([17; 32]);
+            assert_eq!(None, contract.results.get((accounts.alice, 1)));
+            assert_eq!(play(&mut contract, accounts.alice, 1, Bet::Straight(17), 100), Ok(()));
+            assert_eq!(
+                contract.get_round_input(1),
+                Some(vec![(accounts.alice, Bet::Straight(17), 100)])
+            );
+            // nothing is resolved before the round is settled
+            assert_eq!(None, contract.results.get((accounts.alice, 1)));
+
+            test::advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.settle(1), Ok(()));
             
-            let mut expected_result = Vec::new();
-            expected_result.push((1u64, 1u8));
+            // 35 to 1, less the edge
+            let expected_result = vec![RoundResult {
+                round: 1,
+                bet: Bet::Straight(17),
+                stake: 100,
+                pocket: 17,
+                payout: 100 + 3_430,
+            }];
             assert_eq!(expected_result, contract.results
-                            .get(accounts.alice)
+                            .get((accounts.alice, 1))
                             .unwrap());
+            assert_eq!(contract.get_round_input(1), None);
         }
 
         #[ink::test]
         fn clock_can_play_with_many_players() {
-            let accounts = 
-                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            let mut contract = 
-                BitRoulette::new(
-                    accounts.alice, 
-                    EventConfig { 
-                        initial_slot: 0u64, 
-                        interval: 1u64,
-                    },
-                    0,
-                );
-
-            setup_ext_odd_parity();
-            ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
-            // odd parity => only bob wins 
-            let _ = contract.play(accounts.alice, 0).map_err(|_| panic!("{:?}", "the call should work"));
-            let _ = contract.play(accounts.bob, 1).map_err(|_| panic!("{:?}", "the call should work"));
-            let _ = contract.play(accounts.charlie, 0).map_err(|_| panic!("{:?}", "the call should work"));
-
-            let mut expected_fail = Vec::new();
-            expected_fail.push((1u64, 0u8));
-
-            let mut expected_result = Vec::new();
-            expected_result.push((1u64, 1u8));
-
-            assert_eq!(expected_fail, contract.results
-                            .get(accounts.alice)
-                            .unwrap());
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 1u64 }, 0);
 
-            assert_eq!(expected_result, contract.results
-                .get(accounts.bob)
-                .unwrap());
+            setup_ext_pulse([17; 32]);
+            // 17 is black and odd => only bob wins
+            assert_eq!(play(&mut contract, accounts.alice, 1, Bet::Red, 100), Ok(()));
+            assert_eq!(play(&mut contract, accounts.bob, 1, Bet::Odd, 100), Ok(()));
+            assert_eq!(play(&mut contract, accounts.charlie, 1, Bet::Straight(0), 100), Ok(()));
+            test::advance_block::<DefaultEnvironment>();
+            // anyone can settle the round
+            test::set_caller::<DefaultEnvironment>(accounts.django);
+            assert_eq!(contract.settle(1), Ok(()));
 
-            assert_eq!(expected_fail, contract.results
-                .get(accounts.charlie)
-                .unwrap());
+            let payouts = |who| {
+                contract.results
+                    .get((who, 1))
+                    .unwrap()
+                    .iter()
+                    .map(|result: &RoundResult| (result.pocket, result.payout))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(payouts(accounts.alice), vec![(17, 0)]);
+            assert_eq!(payouts(accounts.bob), vec![(17, 198)]);
+            assert_eq!(payouts(accounts.charlie), vec![(17, 0)]);
         }
 
 
         #[ink::test]
         fn clock_fails_when_executed_at_invalid_block() {
-            let accounts = 
-                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            let mut contract = 
-                BitRoulette::new(
-                    accounts.alice, 
-                    EventConfig { 
-                        initial_slot: 1u64, 
-                        interval: 2u64
-                    },
-                    1,
-                );
-            setup_ext_even_parity();
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 1u64, interval: 2u64 }, 1);
+            setup_ext_pulse([17; 32]);
             // the slot/block schedule is 1, 3, 5, 7, ... and so on. all odd numbers
             // jump ahead to block number 2
-            ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
-            ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
-            match contract.play(accounts.alice, 0) {
+            test::advance_block::<DefaultEnvironment>();
+            test::advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.get_round_block(1), 3);
+            assert_eq!(play(&mut contract, accounts.alice, 1, Bet::Red, 100), Ok(()));
+            match contract.settle(1) {
                 Ok(_) => {
                     panic!("{:?}", "we should have encountered an error");
                 },
@@ -271,41 +786,499 @@ mod bit_roulette {
                     assert_eq!(e, Error::InvalidBlockNumber);
                 }
             }
+            test::advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.settle(1), Ok(()));
+        }
+
+        #[ink::test]
+        fn clock_with_zero_interval_ticks_every_block() {
+            let contract = setup(EventConfig { initial_slot: 0u64, interval: 0u64 }, 0);
+            assert_eq!(contract.get_current_round(), 1);
+            assert_eq!(contract.get_round_block(2), 2);
+            test::advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.get_current_round(), 2);
+        }
+
+        #[ink::test]
+        fn guesses_close_when_the_round_begins() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 2u64 }, 0);
+            assert_eq!(contract.get_current_round(), 1);
+            assert_eq!(play(&mut contract, accounts.bob, 3, Bet::Red, 100), Ok(()));
+            test::advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.get_current_round(), 1);
+            test::advance_block::<DefaultEnvironment>();
+            // the pulse of round 1 could now be known
+            assert_eq!(contract.get_current_round(), 2);
+            assert_eq!(
+                play(&mut contract, accounts.bob, 1, Bet::Red, 100),
+                Err(Error::InvalidRoundNumber)
+            );
+            assert_eq!(play(&mut contract, accounts.bob, 2, Bet::Red, 100), Ok(()));
+            assert_solvent(&contract);
+        }
+
+        #[ink::test]
+        fn bets_are_placed_a_bounded_number_of_rounds_ahead() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 1u64 }, 0);
+            let last_round = contract.get_current_round() + MAX_ROUNDS_AHEAD;
+            assert_eq!(play(&mut contract, accounts.bob, last_round, Bet::Red, 100), Ok(()));
+            assert_eq!(
+                play(&mut contract, accounts.bob, last_round + 1, Bet::Red, 100),
+                Err(Error::InvalidRoundNumber)
+            );
+            assert_eq!(
+                play(&mut contract, accounts.bob, RoundNumber::MAX, Bet::Red, 100),
+                Err(Error::InvalidRoundNumber)
+            );
+            // the window moves with the current round
+            test::advance_block::<DefaultEnvironment>();
+            assert_eq!(play(&mut contract, accounts.bob, last_round + 1, Bet::Red, 100), Ok(()));
+            assert_solvent(&contract);
+        }
+
+        #[ink::test]
+        fn rounds_are_settled_once_with_a_pulse() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 1u64 }, 0);
+            assert_eq!(play(&mut contract, accounts.bob, 1, Bet::Black, 100), Ok(()));
+            test::advance_block::<DefaultEnvironment>();
+            setup_ext_pulse([0; 32]);
+            assert_eq!(contract.settle(1), Err(Error::InvalidBlockNumber));
+            assert_eq!(contract.get_draw(1), None);
+            assert_eq!(
+                contract.get_round_input(1),
+                Some(vec![(accounts.bob, Bet::Black, 100)])
+            );
+
+            setup_ext_pulse([17; 32]);
+            assert_eq!(contract.settle(1), Ok(()));
+            assert_eq!(contract.settle(1), Err(Error::InvalidRoundNumber));
+            assert_eq!(contract.get_draw(1), Some(Draw { pulse: [17; 32], pocket: 17 }));
+            assert_eq!(contract.get_results(accounts.bob, Some(1)).unwrap()[0].payout, 198);
+        }
+
+        #[ink::test]
+        fn rounds_are_settled_in_their_block() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 2u64 }, 0);
+            setup_ext_pulse([17; 32]);
+            assert_eq!(play(&mut contract, accounts.bob, 1, Bet::Black, 100), Ok(()));
+            assert_eq!(play(&mut contract, accounts.bob, 2, Bet::Black, 100), Ok(()));
+            assert_eq!(contract.get_round_block(1), 2);
+            test::advance_block::<DefaultEnvironment>();
+            test::advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.settle(1), Ok(()));
+            assert_eq!(contract.get_draw(1), Some(Draw { pulse: [17; 32], pocket: 17 }));
+            // a later pulse would let whoever settles pick the pocket
+            for _ in 0..3 {
+                test::advance_block::<DefaultEnvironment>();
+            }
+            assert_eq!(contract.settle(2), Err(Error::RoundExpired));
+            assert_eq!(contract.get_draw(2), None);
+        }
+
+        #[ink::test]
+        fn rounds_without_a_pulse_are_refunded() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 1u64 }, 0);
+            assert_eq!(play(&mut contract, accounts.bob, 1, Bet::Black, 100), Ok(()));
+            assert_eq!(play(&mut contract, accounts.charlie, 1, Bet::Straight(17), 10), Ok(()));
+            // no pulse is ingested in the block of round 1
+            test::advance_block::<DefaultEnvironment>();
+            setup_ext_pulse([0; 32]);
+            assert_eq!(contract.settle(1), Err(Error::InvalidBlockNumber));
+            // the round can still be settled in its block
+            assert_eq!(contract.refund_round(1), Err(Error::InvalidBlockNumber));
+
+            test::advance_block::<DefaultEnvironment>();
+            setup_ext_pulse([17; 32]);
+            assert_eq!(contract.settle(1), Err(Error::RoundExpired));
+            test::set_caller::<DefaultEnvironment>(accounts.django);
+            assert_eq!(contract.refund_round(1), Ok(()));
+            assert_eq!(contract.refund_round(1), Err(Error::InvalidRoundNumber));
+            assert_eq!(contract.get_draw(1), None);
+            assert_eq!(contract.get_payout(accounts.bob), 100);
+            assert_eq!(contract.get_payout(accounts.charlie), 10);
+            assert_eq!(contract.get_reserved(), 0);
+            assert_eq!(contract.get_bankroll(), BANKROLL);
+            assert_solvent(&contract);
+
+            test::set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw(), Ok(100));
+            assert_solvent(&contract);
+        }
+
+        #[ink::test]
+        fn the_pocket_can_be_reproduced_from_the_pulse() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 1u64 }, 0);
+            let mut pulse = [255; 32];
+            pulse[7] = 100;
+            setup_ext_pulse(pulse);
+            assert_eq!(play(&mut contract, accounts.bob, 1, Bet::Even, 100), Ok(()));
+            test::advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.settle(1), Ok(()));
+
+            let draw = contract.get_draw(1).unwrap();
+            assert_eq!(draw.pulse, pulse);
+            assert_eq!(draw.pocket, draw_pocket(&draw.pulse));
+            assert_eq!(draw.pocket, 100 % 37);
+            assert_eq!(contract.get_results(accounts.bob, None).unwrap()[0].pocket, draw.pocket);
+        }
+
+        #[test]
+        fn pockets_are_drawn_uniformly() {
+            // each accepted byte value maps to a pocket, every pocket the same number of times
+            let mut counts = [0u8; POCKETS as usize];
+            for b in 0..=u8::MAX {
+                let mut pulse = [b; 32];
+                pulse[31] = 0;
+                let pocket = draw_pocket(&pulse);
+                if b < 222 {
+                    counts[pocket as usize] += 1;
+                } else {
+                    // biased bytes are skipped
+                    assert_eq!(pocket, 0);
+                }
+            }
+            assert!(counts.iter().all(|count| *count == 6));
+            // a pulse without an unbiased byte is rehashed
+            let pocket = draw_pocket(&[250; 32]);
+            assert!(pocket < POCKETS);
+            assert_eq!(pocket, draw_pocket(&[250; 32]));
         }
 
-        fn setup_ext_even_parity() {
-            struct MockETFExtension;
-            impl ink_env::test::ChainExtension for MockETFExtension {
-                fn func_id(&self) -> u32 {
-                    1101
+        #[test]
+        fn bets_cover_the_standard_pockets() {
+            let bets = all_bets();
+            let count = |kind: fn(&Bet) -> bool| bets.iter().filter(|bet| kind(bet)).count();
+            assert_eq!(count(|bet| matches!(bet, Bet::Straight(_))), 37);
+            assert_eq!(count(|bet| matches!(bet, Bet::Split(_, _))), 60);
+            assert_eq!(count(|bet| matches!(bet, Bet::Street(_))), 12);
+            assert_eq!(count(|bet| matches!(bet, Bet::Corner(_))), 22);
+            assert_eq!(count(|bet| matches!(bet, Bet::Dozen(_) | Bet::Column(_))), 6);
+
+            for bet in bets {
+                let covered = (0..POCKETS).filter(|pocket| bet.covers(*pocket)).count() as u32;
+                // the standard odds pay back the whole table when every pocket but 0 is covered
+                assert_eq!((bet.odds() + 1) * covered, 36, "{:?}", bet);
+                if !matches!(bet, Bet::Straight(0) | Bet::Split(0, _)) {
+                    assert!(!bet.covers(0), "{:?}", bet);
                 }
+            }
+            assert!(Bet::Corner(14).covers(18));
+            assert!(Bet::Street(34).covers(36));
+            assert!(Bet::Column(3).covers(36));
+            assert!(Bet::Dozen(2).covers(24));
+            assert!(Bet::Black.covers(20));
+            assert!(!Bet::Red.covers(20));
+        }
 
-                fn call(&mut self, _input: &[u8], output: &mut Vec<u8>) -> u32 {
-                    let ret = [0;48];
-                    scale::Encode::encode_to(&ret, output);
-                    0
+        #[ink::test]
+        fn settled_rounds_update_the_stats() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 1u64 }, 0);
+            setup_ext_pulse([17; 32]);
+            // bob wins 98 on black and loses 100 on red: staking 200 for 198 is a loss
+            assert_eq!(play(&mut contract, accounts.bob, 1, Bet::Black, 100), Ok(()));
+            assert_eq!(play(&mut contract, accounts.bob, 1, Bet::Red, 100), Ok(()));
+            assert_eq!(play(&mut contract, accounts.charlie, 1, Bet::Straight(17), 10), Ok(()));
+            assert_eq!(play(&mut contract, accounts.bob, 2, Bet::Odd, 100), Ok(()));
+            test::advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.settle(1), Ok(()));
+            test::advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.settle(2), Ok(()));
+
+            let bob = contract.get_player_stats(accounts.bob);
+            assert_eq!((bob.rounds, bob.wins, bob.losses), (2, 1, 1));
+            assert_eq!((bob.staked, bob.paid, bob.winnings), (300, 396, 98));
+            assert_eq!((bob.win_streak, bob.longest_loss_streak), (1, 1));
+            assert_eq!(
+                contract.get_round_stats(1),
+                Some(RoundStats { round: 1, players: 2, winners: 1, staked: 210, paid: 198 + 353 })
+            );
+            assert_eq!(contract.get_round_stats(3), None);
+            assert_eq!(
+                contract.get_leaderboard(),
+                vec![(accounts.charlie, 343), (accounts.bob, 98)]
+            );
+            // without a round, the results of the last round played
+            assert_eq!(contract.get_results(accounts.bob, None).unwrap()[0].bet, Bet::Odd);
+            assert_eq!(contract.get_results(accounts.bob, Some(1)).unwrap().len(), 2);
+            assert_eq!(contract.get_results(accounts.django, None), None);
+        }
+
+        #[ink::test]
+        fn bets_must_be_on_the_layout() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 1u64 }, 0);
+            let invalid = [
+                Bet::Straight(37),
+                Bet::Split(3, 4),
+                Bet::Split(1, 5),
+                Bet::Split(0, 4),
+                Bet::Street(2),
+                Bet::Corner(3),
+                Bet::Corner(34),
+                Bet::Dozen(0),
+                Bet::Column(4),
+            ];
+            for bet in invalid {
+                assert_eq!(
+                    play(&mut contract, accounts.bob, 1, bet, 100),
+                    Err(Error::InvalidBet)
+                );
+            }
+            assert_eq!(contract.get_round_input(1), None);
+            assert_solvent(&contract);
+        }
+
+        #[ink::test]
+        fn players_can_place_many_bets_a_round() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 1u64 }, 0);
+            setup_ext_pulse([17; 32]);
+            for bet in [Bet::Red, Bet::Black, Bet::Split(17, 20), Bet::Corner(13)] {
+                assert_eq!(play(&mut contract, accounts.bob, 1, bet, 100), Ok(()));
+            }
+            assert_eq!(contract.get_round_input(1).unwrap().len(), 4);
+            test::advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.settle(1), Ok(()));
+
+            let payouts = contract
+                .get_results(accounts.bob, Some(1))
+                .unwrap()
+                .iter()
+                .map(|result| result.payout)
+                .collect::<Vec<_>>();
+            assert_eq!(payouts, vec![0, 198, 100 + 1_666, 100 + 784]);
+            assert_eq!(contract.get_payout(accounts.bob), 198 + 1_766 + 884);
+            assert_solvent(&contract);
+        }
+
+        #[ink::test]
+        fn rounds_accept_a_bounded_number_of_bets() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 1u64 }, 0);
+            for _ in 0..MAX_BETS_PER_ROUND {
+                assert_eq!(play(&mut contract, accounts.bob, 1, Bet::Red, 1), Ok(()));
+            }
+            assert_eq!(play(&mut contract, accounts.bob, 1, Bet::Red, 1), Err(Error::RoundFull));
+            assert_eq!(play(&mut contract, accounts.bob, 2, Bet::Red, 1), Ok(()));
+        }
+
+        #[ink::test]
+        fn only_the_game_master_can_fund_and_withdraw_the_bankroll() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 1u64 }, 0);
+            test::set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(pay(100, || contract.fund()), Err(Error::NotGameMaster));
+            assert_eq!(contract.withdraw_bankroll(100), Err(Error::NotGameMaster));
+
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(
+                contract.withdraw_bankroll(BANKROLL + 1),
+                Err(Error::InsufficientBankroll)
+            );
+            assert_eq!(contract.withdraw_bankroll(400_000), Ok(()));
+            assert_eq!(contract.get_bankroll(), BANKROLL - 400_000);
+            assert_solvent(&contract);
+        }
+
+        #[ink::test]
+        fn the_game_master_can_hand_over_the_table() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 1u64 }, 0);
+            test::set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.transfer_game_master(accounts.bob), Err(Error::NotGameMaster));
+
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.transfer_game_master(accounts.bob), Ok(()));
+            assert_eq!(contract.get_game_master(), accounts.bob);
+            assert_eq!(contract.withdraw_bankroll(100), Err(Error::NotGameMaster));
+            test::set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_bankroll(100), Ok(()));
+            assert_solvent(&contract);
+        }
+
+        #[ink::test]
+        fn players_bet_for_themselves() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 1u64 }, 0);
+            setup_ext_pulse([17; 32]);
+            // the game master is not needed to place bets
+            assert_eq!(play(&mut contract, accounts.bob, 1, Bet::Black, 100), Ok(()));
+            assert_eq!(play(&mut contract, accounts.charlie, 1, Bet::Red, 100), Ok(()));
+            assert_eq!(
+                contract.get_round_input(1),
+                Some(vec![
+                    (accounts.bob, Bet::Black, 100),
+                    (accounts.charlie, Bet::Red, 100),
+                ])
+            );
+            test::advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.settle(1), Ok(()));
+            assert_eq!(contract.get_payout(accounts.bob), 198);
+            assert_eq!(contract.get_payout(accounts.charlie), 0);
+        }
+
+        #[ink::test]
+        fn bets_must_be_staked_and_within_the_exposure_limit() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 1u64 }, 0);
+
+            // 10% of the bankroll, over the odds of the bet
+            assert_eq!(contract.get_max_bet(Bet::Red), 100_000);
+            assert_eq!(contract.get_max_bet(Bet::Dozen(1)), 50_000);
+            assert_eq!(contract.get_max_bet(Bet::Straight(7)), 2_857);
+            assert_eq!(
+                play(&mut contract, accounts.bob, 1, Bet::Red, 0),
+                Err(Error::InvalidResourceAmount)
+            );
+            assert_eq!(
+                play(&mut contract, accounts.bob, 1, Bet::Straight(7), 2_858),
+                Err(Error::BetTooLarge)
+            );
+            assert_eq!(contract.get_bankroll(), BANKROLL);
+            assert_eq!(play(&mut contract, accounts.bob, 1, Bet::Straight(7), 2_857), Ok(()));
+            // the stake is taken and the payout is set aside until the round is settled
+            let payout = 2_857 + 97_995;
+            assert_eq!(contract.get_reserved(), payout);
+            assert_eq!(contract.get_bankroll(), BANKROLL + 2_857 - payout);
+            assert_solvent(&contract);
+        }
+
+        #[ink::test]
+        fn winners_are_paid_the_odds_less_the_edge() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 1u64 }, 0);
+            setup_ext_pulse([17; 32]);
+
+            assert_eq!(play(&mut contract, accounts.bob, 1, Bet::Dozen(2), 500), Ok(()));
+            assert_eq!(play(&mut contract, accounts.charlie, 1, Bet::Column(1), 500), Ok(()));
+            assert_eq!(play(&mut contract, accounts.django, 1, Bet::Corner(13), 500), Ok(()));
+            assert_solvent(&contract);
+            test::advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.settle(1), Ok(()));
+            // bob wins his 500 back and 98% of 2 x 500 more, django 98% of 8 x 500
+            assert_eq!(contract.get_payout(accounts.bob), 1_480);
+            assert_eq!(contract.get_payout(accounts.charlie), 0);
+            assert_eq!(contract.get_payout(accounts.django), 4_420);
+            assert_eq!(contract.get_owed(), 5_900);
+            assert_eq!(contract.get_reserved(), 0);
+            assert_eq!(contract.get_bankroll(), BANKROLL + 1_500 - 5_900);
+            assert_solvent(&contract);
+        }
+
+        #[ink::test]
+        fn winners_withdraw_their_payouts() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 1u64 }, 0);
+            setup_ext_pulse([17; 32]);
+            assert_eq!(play(&mut contract, accounts.bob, 1, Bet::Low, 200), Ok(()));
+            assert_eq!(play(&mut contract, accounts.bob, 2, Bet::Black, 300), Ok(()));
+            test::advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.settle(1), Ok(()));
+            test::advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.settle(2), Ok(()));
+            assert_eq!(contract.get_payout(accounts.bob), 396 + 594);
+
+            let before = test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+            test::set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw(), Ok(990));
+            let after = test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+            assert_eq!(after, before + 990);
+            assert_eq!(contract.get_payout(accounts.bob), 0);
+            assert_eq!(contract.withdraw(), Err(Error::NothingToWithdraw));
+            assert_solvent(&contract);
+        }
+
+        #[ink::test]
+        fn owed_payouts_cannot_be_withdrawn_by_the_game_master() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 1u64 }, 0);
+            setup_ext_pulse([18; 32]);
+            assert_eq!(play(&mut contract, accounts.bob, 1, Bet::Red, 1_000), Ok(()));
+
+            // reserved payouts are out of reach before the round is settled
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            let bankroll = contract.get_bankroll();
+            assert_eq!(
+                contract.withdraw_bankroll(bankroll + 1),
+                Err(Error::InsufficientBankroll)
+            );
+            assert_eq!(contract.withdraw_bankroll(bankroll), Ok(()));
+            assert_eq!(contract_balance(), contract.get_reserved());
+            test::advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.settle(1), Ok(()));
+            assert_eq!(contract_balance(), contract.get_owed());
+            assert_solvent(&contract);
+
+            test::set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw(), Ok(1_980));
+            assert_eq!(contract_balance(), 0);
+        }
+
+        #[ink::test]
+        fn the_bankroll_covers_every_winning_streak() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut contract = setup(EventConfig { initial_slot: 0u64, interval: 1u64 }, 0);
+            setup_ext_pulse([17; 32]);
+            let players = [accounts.bob, accounts.charlie, accounts.django, accounts.eve];
+            let bets = [Bet::Straight(17), Bet::Split(16, 17), Bet::Black, Bet::Column(2)];
+            // every player bets the maximum in every round, and every bet wins
+            for round in 1..20 {
+                for (player, bet) in players.into_iter().zip(bets) {
+                    let max_bet = contract.get_max_bet(bet);
+                    if max_bet == 0 {
+                        continue;
+                    }
+                    assert_eq!(
+                        play(&mut contract, player, round, bet, max_bet + 1),
+                        Err(Error::BetTooLarge)
+                    );
+                    assert_eq!(play(&mut contract, player, round, bet, max_bet), Ok(()));
+                    assert_solvent(&contract);
                 }
+                test::advance_block::<DefaultEnvironment>();
+                let _ = contract.settle(round);
+                assert_eq!(contract.get_reserved(), 0);
+                assert_solvent(&contract);
             }
+            assert!(contract.get_bankroll() < BANKROLL);
 
-            ink_env::test::register_chain_extension(MockETFExtension);
+            for player in players {
+                test::set_caller::<DefaultEnvironment>(player);
+                let _ = contract.withdraw();
+            }
+            assert_eq!(contract.get_owed(), 0);
+            assert_eq!(contract_balance(), contract.get_bankroll());
         }
 
-        fn setup_ext_odd_parity() {
-            struct MockETFExtension;
-            impl ink_env::test::ChainExtension for MockETFExtension {
-                fn func_id(&self) -> u32 {
-                    1101
+        /// mock the drand extension, returning the pulse until it is mocked again
+        /// (zeroed when no pulse was ingested)
+        fn setup_ext_pulse(pulse: [u8; 32]) {
+            struct MockDrandExtension([u8; 32]);
+            impl ink::env::test::ChainExtension for MockDrandExtension {
+                fn ext_id(&self) -> u16 {
+                    12
                 }
 
-                fn call(&mut self, _input: &[u8], output: &mut Vec<u8>) -> u32 {
-                    let mut ret = [1;48];
-                    ret[0] = 0;
-                    scale::Encode::encode_to(&ret, output);
+                fn call(
+                    &mut self,
+                    func_id: u16,
+                    _input: &[u8],
+                    output: &mut Vec<u8>,
+                ) -> u32 {
+                    assert_eq!(func_id, 1101);
+                    scale::Encode::encode_to(&self.0, output);
                     0
                 }
             }
 
-            ink_env::test::register_chain_extension(MockETFExtension);
+            ink::env::test::register_chain_extension(MockDrandExtension(pulse));
         }
     }
 
@@ -0,0 +1,318 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+//! The interface shared by every auction format the `TlockProxy` can deploy
+//!
+//! Each format is its own contract implementing the [`Auction`] trait. The proxy
+//! keeps a registry of code hashes per [`AuctionKind`], instantiates the right
+//! contract for a new auction and dispatches bids and settlement through the trait.
+
+pub use idl_contract_extension::types::TlockMessage;
+use idl_contract_extension::access::AccessError;
+use ink::{prelude::vec::Vec, primitives::AccountId};
+use sha3::{Digest, Sha3_256};
+
+/// sealed bid bookkeeping and ranking, shared by the sealed-bid formats
+pub mod sealed;
+
+/// the balance type of the `DrandEnvironment`
+pub type Balance = u128;
+
+/// The auction formats known to the proxy, used as the key of its registry
+#[derive(Clone, Copy, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum AuctionKind {
+    /// sealed bids, the highest bidder pays their bid
+    FirstPrice,
+    /// sealed bids, the highest bidder pays the second highest bid
+    Vickrey,
+    /// open ascending bids, the highest bidder pays their bid
+    English,
+    /// a descending price, the first bidder to accept it pays it
+    Dutch,
+    /// sealed bids for several units, every winner pays the highest losing bid
+    UniformPrice,
+}
+
+impl AuctionKind {
+    /// whether bids are sealed (committed to, then revealed after the deadline)
+    pub fn is_sealed(&self) -> bool {
+        matches!(
+            self,
+            AuctionKind::FirstPrice | AuctionKind::Vickrey | AuctionKind::UniformPrice
+        )
+    }
+}
+
+/// The format of a new auction, with its format specific parameters
+#[derive(Clone, Debug, scale::Decode, scale::Encode, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+// the scale derive casts the index of variants with fields to u8
+#[allow(clippy::cast_possible_truncation)]
+pub enum AuctionConfig {
+    FirstPrice,
+    Vickrey,
+    English {
+        /// the minimum amount by which a bid must beat the highest bid
+        min_increment: Balance,
+    },
+    Dutch {
+        /// the price when the auction opens
+        start_price: Balance,
+        /// the amount the price drops each block, down to the reserve price
+        decrement: Balance,
+    },
+    UniformPrice {
+        /// the number of units (assets) on sale
+        units: u32,
+    },
+}
+
+impl AuctionConfig {
+    /// the kind of auction this config deploys
+    pub fn kind(&self) -> AuctionKind {
+        match self {
+            AuctionConfig::FirstPrice => AuctionKind::FirstPrice,
+            AuctionConfig::Vickrey => AuctionKind::Vickrey,
+            AuctionConfig::English { .. } => AuctionKind::English,
+            AuctionConfig::Dutch { .. } => AuctionKind::Dutch,
+            AuctionConfig::UniformPrice { .. } => AuctionKind::UniformPrice,
+        }
+    }
+
+    /// the number of assets on sale
+    pub fn units(&self) -> u32 {
+        match self {
+            AuctionConfig::UniformPrice { units } => *units,
+            _ => 1,
+        }
+    }
+}
+
+/// The stage of an auction's lifecycle
+#[derive(Clone, Copy, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum AuctionStatus {
+    /// bids are accepted
+    Open,
+    /// bidding has closed and sealed bids are being revealed
+    RevealPhase,
+    /// the outcome is determined, prizes and deposits can be claimed
+    Settled,
+    /// the seller withdrew the asset
+    Cancelled,
+    /// every prize and deposit has been claimed
+    Claimed,
+}
+
+impl AuctionStatus {
+    /// whether the lifecycle allows moving from this status to `next`
+    ///
+    /// Open -> RevealPhase -> Settled -> Claimed, where auctions without sealed
+    /// bids settle straight from Open, and only an open auction can be cancelled.
+    pub fn can_become(&self, next: AuctionStatus) -> bool {
+        matches!(
+            (self, next),
+            (AuctionStatus::Open, AuctionStatus::RevealPhase)
+                | (AuctionStatus::Open, AuctionStatus::Settled)
+                | (AuctionStatus::Open, AuctionStatus::Cancelled)
+                | (AuctionStatus::RevealPhase, AuctionStatus::Settled)
+                | (AuctionStatus::Settled, AuctionStatus::Claimed)
+        )
+    }
+}
+
+/// A bid, in the shape the auction's format expects
+#[derive(Clone, Debug, scale::Decode, scale::Encode, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+#[allow(clippy::cast_possible_truncation)]
+pub enum BidPayload {
+    /// a bid sealed by its commitment (see `bid_commitment`)
+    Sealed(TlockMessage),
+    /// a public bid of the given amount
+    Open(Balance),
+    /// accept the current price
+    Accept,
+}
+
+/// The result of an auction for a single winner
+#[derive(Clone, Debug, scale::Decode, scale::Encode, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct AuctionResult<AccountId, Balance> {
+    pub winner: AccountId,
+    pub debt: Balance,
+}
+
+/// The outcome of a completed auction
+#[derive(Clone, Debug, scale::Decode, scale::Encode, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+#[allow(clippy::cast_possible_truncation)]
+pub enum AuctionOutcome<AccountId, Balance> {
+    /// the assets are sold, the i-th result wins the i-th asset
+    Sale(Vec<AuctionResult<AccountId, Balance>>),
+    /// no valid bid met the reserve price, so the assets return to the seller
+    NoSale,
+}
+
+/// A custom type for storing revealed bids
+#[derive(Clone, Debug, scale::Decode, scale::Encode, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct RevealedBid<AccountId> {
+    /// the bidder
+    pub bidder: AccountId,
+    /// the (supposedly) revealed amount they bid
+    pub bid: u128,
+    /// the salt the bid was committed to
+    pub salt: [u8; 32],
+}
+
+/// The commitment to a sealed bid:
+/// `sha3_256(SCALE(bidder, bid, salt, ciphertext, nonce, capsule))`
+///
+/// A sealed bid can only be revealed by opening this commitment. Nothing checks
+/// that the ciphertext encrypts the bid, so sealing is a plain commit-reveal scheme.
+/// Binding the message means an opening only reveals the ciphertext it was sealed with.
+///
+/// * `sealed_bid`: the sealed message, whose own `commitment` is ignored
+pub fn bid_commitment<AccountId: scale::Encode>(
+    bidder: &AccountId,
+    bid: u128,
+    salt: &[u8; 32],
+    sealed_bid: &TlockMessage,
+) -> [u8; 32] {
+    let TlockMessage { ciphertext, nonce, capsule, .. } = sealed_bid;
+    let preimage = scale::Encode::encode(&(bidder, bid, salt, ciphertext, nonce, capsule));
+    let mut commitment = [0u8; 32];
+    commitment.copy_from_slice(&Sha3_256::digest(preimage));
+    commitment
+}
+
+#[derive(Clone, PartialEq, Debug, scale::Decode, scale::Encode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum AuctionError {
+    /// the origin must match the configured proxy
+    NotProxy,
+    WaitingReveals,
+    NotParticipant,
+    /// the sealed bid's commitment is not a 32 byte hash
+    InvalidCommitment,
+    /// the revealed bid does not open the sealed bid's commitment
+    CommitmentMismatch,
+    /// the bidder has already revealed their bid
+    AlreadyRevealed,
+    /// the bid does not suit the auction's format
+    UnsupportedBid,
+    /// the bid is below the reserve price or does not beat the highest bid
+    BidTooLow,
+    /// the auction no longer accepts bids
+    AuctionClosed,
+    /// the auction has been completed or cancelled, so bids can no longer be revealed
+    RevealPhaseClosed,
+    /// the auction has already been completed
+    AlreadyComplete,
+}
+
+/// The auction contracts are owned by the proxy which deployed them
+impl From<AccessError> for AuctionError {
+    fn from(_: AccessError) -> Self {
+        AuctionError::NotProxy
+    }
+}
+
+/// The messages every auction format implements
+///
+/// Only the proxy may bid, reveal, complete and cancel, on behalf of the bidders.
+#[ink::trait_definition]
+pub trait Auction {
+    /// get the kind of the auction
+    #[ink(message)]
+    fn get_kind(&self) -> AuctionKind;
+
+    /// get the stage of the auction's lifecycle
+    #[ink(message)]
+    fn get_status(&self) -> AuctionStatus;
+
+    /// place a bid on behalf of the bidder, while the auction is open
+    ///
+    /// * `bidder`: the account bidding
+    /// * `bid`: the bid, which must suit the auction's format
+    ///
+    #[ink(message)]
+    fn bid(&mut self, bidder: AccountId, bid: BidPayload) -> Result<(), AuctionError>;
+
+    /// reveal a sealed bid, once the deadline round's pulse is published
+    /// the first reveal closes bidding, and each bidder reveals once
+    ///
+    /// * `revealed_bid`: the revealed bid
+    ///
+    #[ink(message)]
+    fn reveal_bid(&mut self, revealed_bid: RevealedBid<AccountId>) -> Result<(), AuctionError>;
+
+    /// complete the auction, determining its outcome, at most once
+    /// the proxy only completes an auction after its reveal deadline
+    ///
+    /// * `pulse`: the drand pulse which breaks ties, the proxy passes the pulse it read
+    ///   at the last reveal so that the outcome does not depend on when it is completed
+    ///
+    #[ink(message)]
+    fn complete(&mut self, pulse: [u8; 32]) -> Result<(), AuctionError>;
+
+    /// withdraw the auction, while it is open
+    /// a cancelled auction accepts no further bids or reveals and is never completed
+    #[ink(message)]
+    fn cancel(&mut self) -> Result<(), AuctionError>;
+
+    /// get the outcome of the auction (None until the auction is complete)
+    #[ink(message)]
+    fn get_outcome(&self) -> Option<AuctionOutcome<AccountId, Balance>>;
+
+    /// get the bidders whose bids were invalid, their deposits are slashed
+    /// (empty until post-auction completion)
+    #[ink(message)]
+    fn get_invalid_bids(&self) -> Vec<AccountId>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lifecycle_transitions() {
+        use AuctionStatus::*;
+        let all = [Open, RevealPhase, Settled, Cancelled, Claimed];
+        let allowed = [
+            (Open, RevealPhase),
+            (Open, Settled),
+            (Open, Cancelled),
+            (RevealPhase, Settled),
+            (Settled, Claimed),
+        ];
+        for from in all {
+            for to in all {
+                assert_eq!(from.can_become(to), allowed.contains(&(from, to)), "{from:?} -> {to:?}");
+            }
+        }
+    }
+}
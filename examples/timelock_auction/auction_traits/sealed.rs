@@ -0,0 +1,228 @@
+use crate::{bid_commitment, AuctionError, Balance, RevealedBid, TlockMessage};
+use ink::{
+    prelude::vec::Vec,
+    primitives::AccountId,
+    storage::Mapping,
+};
+
+/// The sealed and revealed bids of a sealed-bid auction
+#[ink::storage_item]
+#[derive(Debug, Default)]
+pub struct SealedBids {
+    /// ink mapping has no support for iteration so we need to loop over this vec to read through the proposals
+    participants: Vec<AccountId>,
+    /// the sealed bid of each participant
+    sealed_bids: Mapping<AccountId, TlockMessage>,
+    /// the revealed proposals
+    revealed_bids: Vec<RevealedBid<AccountId>>,
+}
+
+impl SealedBids {
+    /// seal the bidder's bid, replacing any bid they sealed before
+    pub fn seal(&mut self, bidder: AccountId, sealed_bid: TlockMessage) -> Result<(), AuctionError> {
+        if sealed_bid.commitment.len() != 32 {
+            return Err(AuctionError::InvalidCommitment);
+        }
+        if !self.participants.contains(&bidder) {
+            self.participants.push(bidder);
+        }
+        self.sealed_bids.insert(bidder, &sealed_bid);
+        Ok(())
+    }
+
+    /// save the revealed bid if it opens the commitment of the bidder's sealed bid
+    /// each bidder reveals once
+    pub fn reveal(&mut self, revealed_bid: RevealedBid<AccountId>) -> Result<(), AuctionError> {
+        let sealed_bid = self
+            .sealed_bids
+            .get(revealed_bid.bidder)
+            .ok_or(AuctionError::NotParticipant)?;
+        if self.revealed_bids.iter().any(|b| b.bidder == revealed_bid.bidder) {
+            return Err(AuctionError::AlreadyRevealed);
+        }
+        let RevealedBid { bidder, bid, salt } = &revealed_bid;
+        if sealed_bid.commitment != bid_commitment(bidder, *bid, salt, &sealed_bid) {
+            return Err(AuctionError::CommitmentMismatch);
+        }
+        self.revealed_bids.push(revealed_bid);
+        Ok(())
+    }
+
+    /// the bidders, in the order they first bid
+    pub fn participants(&self) -> &[AccountId] {
+        &self.participants
+    }
+
+    /// the sealed bid of a participant
+    pub fn sealed_bid(&self, bidder: AccountId) -> Option<TlockMessage> {
+        self.sealed_bids.get(bidder)
+    }
+
+    /// the revealed bids, in the order they were revealed
+    pub fn revealed_bids(&self) -> &[RevealedBid<AccountId>] {
+        &self.revealed_bids
+    }
+
+    /// the participants whose bids were never revealed
+    pub fn unrevealed(&self) -> Vec<AccountId> {
+        self.participants
+            .iter()
+            .filter(|p| !self.revealed_bids.iter().any(|b| b.bidder == **p))
+            .copied()
+            .collect()
+    }
+}
+
+/// Rank the bids at or above the reserve price, highest first
+///
+/// Ties keep the order they were revealed in, rotated by an index drawn from
+/// `pulse` so that no tied bidder is favoured. The pulse is expected to be read
+/// before the ranking (e.g. at the last reveal), so that the ranking does not
+/// depend on when it is done.
+///
+/// * `bids`: the revealed bids
+/// * `reserve_price`: the minimum price the seller will accept
+/// * `pulse`: a drand pulse
+///
+pub fn rank(
+    bids: &[RevealedBid<AccountId>],
+    reserve_price: Balance,
+    pulse: [u8; 32],
+) -> Vec<RevealedBid<AccountId>> {
+    let mut ranked = bids
+        .iter()
+        .filter(|b| b.bid >= reserve_price)
+        .cloned()
+        .collect::<Vec<_>>();
+    // stable, so ties keep their reveal order
+    ranked.sort_by_key(|b| core::cmp::Reverse(b.bid));
+
+    let mut index_bytes = [0u8; 16];
+    index_bytes.copy_from_slice(&pulse[..16]);
+    let seed = u128::from_le_bytes(index_bytes);
+    let mut start = 0;
+    while start < ranked.len() {
+        let ties = ranked[start..].iter().take_while(|b| b.bid == ranked[start].bid).count();
+        let end = start.saturating_add(ties);
+        if ties > 1 {
+            // below `ties`, so it fits in a usize
+            let index = seed.checked_rem(ties as u128).unwrap_or_default() as usize;
+            ranked[start..end].rotate_left(index);
+        }
+        start = end;
+    }
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bid(seed: u8, bid: u128) -> RevealedBid<AccountId> {
+        RevealedBid {
+            bidder: AccountId::from([seed; 32]),
+            bid,
+            salt: [seed; 32],
+        }
+    }
+
+    /// a dummy sealed message committing to the bid
+    fn seal(bidder: AccountId, bid: u128, salt: [u8; 32]) -> TlockMessage {
+        let mut sealed_bid = TlockMessage {
+            ciphertext: vec![1; 32],
+            nonce: vec![2; 12],
+            capsule: vec![3; 32],
+            commitment: Vec::new(),
+        };
+        sealed_bid.commitment = bid_commitment(&bidder, bid, &salt, &sealed_bid).to_vec();
+        sealed_bid
+    }
+
+    fn pulse(first_byte: u8) -> [u8; 32] {
+        let mut pulse = [0u8; 32];
+        pulse[0] = first_byte;
+        pulse
+    }
+
+    #[test]
+    fn rank_orders_eligible_bids_highest_first() {
+        let bids = [bid(1, 1), bid(2, 3), bid(3, 2), bid(4, 5)];
+        let ranked = rank(&bids, 2, pulse(1));
+        assert_eq!(ranked, vec![bid(4, 5), bid(2, 3), bid(3, 2)]);
+    }
+
+    #[test]
+    fn rank_rotates_ties_with_the_pulse() {
+        let bids = [bid(1, 3), bid(2, 3), bid(3, 3), bid(4, 1)];
+        assert_eq!(
+            rank(&bids, 0, pulse(0)),
+            vec![bid(1, 3), bid(2, 3), bid(3, 3), bid(4, 1)]
+        );
+        assert_eq!(
+            rank(&bids, 0, pulse(1)),
+            vec![bid(2, 3), bid(3, 3), bid(1, 3), bid(4, 1)]
+        );
+        assert_eq!(
+            rank(&bids, 0, pulse(5)),
+            vec![bid(3, 3), bid(1, 3), bid(2, 3), bid(4, 1)]
+        );
+    }
+
+    #[test]
+    fn rank_rotates_every_tie_with_the_same_pulse() {
+        let bids = [bid(1, 3), bid(2, 3), bid(3, 1), bid(4, 1)];
+        let ranked = rank(&bids, 0, pulse(1));
+        assert_eq!(ranked, vec![bid(2, 3), bid(1, 3), bid(4, 1), bid(3, 1)]);
+    }
+
+    #[ink::test]
+    fn reveal_must_open_the_commitment() {
+        let alice = AccountId::from([1; 32]);
+        let mut bids = SealedBids::default();
+        let salt = [7; 32];
+        let sealed_bid = seal(alice, 10, salt);
+        assert_eq!(
+            bids.reveal(RevealedBid { bidder: alice, bid: 10, salt }),
+            Err(AuctionError::NotParticipant)
+        );
+        assert_eq!(bids.seal(alice, sealed_bid), Ok(()));
+        assert_eq!(bids.unrevealed(), vec![alice]);
+        assert_eq!(
+            bids.reveal(RevealedBid { bidder: alice, bid: 11, salt }),
+            Err(AuctionError::CommitmentMismatch)
+        );
+        assert_eq!(bids.reveal(RevealedBid { bidder: alice, bid: 10, salt }), Ok(()));
+        assert!(bids.unrevealed().is_empty());
+    }
+
+    #[ink::test]
+    fn reveal_must_match_the_sealed_ciphertext() {
+        let alice = AccountId::from([1; 32]);
+        let mut bids = SealedBids::default();
+        let salt = [7; 32];
+        // the commitment was made for another ciphertext
+        let mut sealed_bid = seal(alice, 10, salt);
+        sealed_bid.ciphertext = vec![4; 32];
+        assert_eq!(bids.seal(alice, sealed_bid), Ok(()));
+        assert_eq!(
+            bids.reveal(RevealedBid { bidder: alice, bid: 10, salt }),
+            Err(AuctionError::CommitmentMismatch)
+        );
+        assert_eq!(bids.unrevealed(), vec![alice]);
+    }
+
+    #[ink::test]
+    fn bids_are_revealed_once() {
+        let alice = AccountId::from([1; 32]);
+        let mut bids = SealedBids::default();
+        let salt = [7; 32];
+        let sealed_bid = seal(alice, 10, salt);
+        assert_eq!(bids.seal(alice, sealed_bid), Ok(()));
+        assert_eq!(bids.reveal(RevealedBid { bidder: alice, bid: 10, salt }), Ok(()));
+        assert_eq!(
+            bids.reveal(RevealedBid { bidder: alice, bid: 10, salt }),
+            Err(AuctionError::AlreadyRevealed)
+        );
+        assert_eq!(bids.revealed_bids().len(), 1);
+    }
+}
@@ -18,8 +18,8 @@ child_directories=$(find "$root" -type d)
 while read -r directory; do
   # Exclude the parent directory itself (root)
   if [ "$directory" != "$root" ]; then
-    # check if there is a Cargo.toml
-    if [ -e "$directory/Cargo.toml" ]; then
+    # check if there is a Cargo.toml for a contract (skipping shared libraries, e.g. auction_traits)
+    if [ -e "$directory/Cargo.toml" ] && grep -q "ink::contract" "$directory/lib.rs"; then
       echo "Building $directory"
       cd $directory
       cargo +nightly contract build
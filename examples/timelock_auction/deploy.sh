@@ -9,13 +9,19 @@
 
 # Default URI value
 uri="wss://etf1.idealabs.network:443"
+# the address of a deployed proxy to upgrade, instead of deploying a new one
+# (only for code which keeps the proxy's storage layout, otherwise deploy a new proxy)
+proxy=""
 
 # Parse command-line options
-while getopts ":u:" opt; do
+while getopts ":u:p:" opt; do
   case $opt in
     u)
       uri="$OPTARG"
       ;;
+    p)
+      proxy="$OPTARG"
+      ;;
     \?)
       echo "Invalid option: -$OPTARG" >&2
       exit 1
@@ -33,8 +39,30 @@ echo $uri
 # Shift the options so that the non-option arguments are left
 shift $((OPTIND-1))
 
-# navigate to compiled contracts directory
-cd ./target/ink/
+# navigate to compiled contracts directory (the workspace target)
+cd ../../target/ink/
+
+# Upgrade: replace the code of a deployed proxy and of its auction kinds, keeping its storage.
+# Running auctions keep their code, new auctions are deployed with the new code.
+if [ -n "$proxy" ]; then
+  proxy_code_hash=$(cargo contract upload tlock_proxy/tlock_proxy.wasm --suri //Alice --url $uri -x | grep "Code hash" | awk '{print $3}')
+  if [ -z "$proxy_code_hash" ]; then
+    echo "Failed to upload the proxy contract."
+    exit 1
+  fi
+  cargo contract call --contract $proxy --message upgrade --args $proxy_code_hash --suri //Alice --url $uri -x
+  echo "Upgraded the proxy. Code Hash: " $proxy_code_hash
+
+  for kind in Vickrey:vickrey_auction FirstPrice:first_price_auction English:english_auction Dutch:dutch_auction UniformPrice:uniform_price_auction; do
+    contract=${kind#*:}
+    code_hash=$(cargo contract upload $contract/$contract.wasm --suri //Alice --url $uri -x | grep "Code hash" | awk '{print $3}')
+    if [ -n "$code_hash" ]; then
+      cargo contract call --contract $proxy --message update_auction_code_hash --args ${kind%%:*} $code_hash --suri //Alice --url $uri -x
+      echo "Updated ${kind%%:*} auctions. Code Hash: " $code_hash
+    fi
+  done
+  exit 0
+fi
 # Step 2: Upload ERC721 contract and store the code hash
 erc721_code_hash=$(cargo contract upload erc721/erc721.wasm --suri //Alice --url $uri -x | grep "Code hash" | awk '{print $3}')
 
@@ -66,7 +94,19 @@ if [ -z "$proxy_contract_address" ]; then
   exit 1
 fi
 
-# Step 4: Store the results in deploy_results.txt
+# Step 4: Upload the other auction formats and register them with the proxy
+for kind in FirstPrice:first_price_auction English:english_auction Dutch:dutch_auction UniformPrice:uniform_price_auction; do
+  contract=${kind#*:}
+  code_hash=$(cargo contract upload $contract/$contract.wasm --suri //Alice --url $uri -x | grep "Code hash" | awk '{print $3}')
+  if [ -z "$code_hash" ]; then
+    echo "Failed to upload $contract contract."
+    exit 1
+  fi
+  cargo contract call --contract $proxy_contract_address --message register_auction_kind --args ${kind%%:*} $code_hash --suri //Alice --url $uri -x
+  echo "Registered ${kind%%:*} auctions. Code Hash: " $code_hash
+done
+
+# Step 5: Store the results in deploy_results.txt
 echo "auction_contract_code_hash: $vickrey_code_hash" > deploy_results.txt
 echo "erc_721_code_hash: $erc721_code_hash" >> deploy_results.txt
 echo "proxy_contract_address: $proxy_contract_address" >> deploy_results.txt
@@ -0,0 +1,201 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+pub use self::dutch_auction::{DutchAuction, DutchAuctionRef};
+
+use idl_contract_extension::ext::DrandEnvironment;
+
+#[ink::contract(env = DrandEnvironment)]
+mod dutch_auction {
+    use crate::DrandEnvironment;
+    use idl_contract_extension::access::Ownable;
+    use auction_traits::{
+        Auction, AuctionKind, AuctionOutcome, AuctionResult, AuctionStatus, BidPayload,
+        RevealedBid,
+    };
+    use ink::prelude::{vec, vec::Vec};
+
+    pub use auction_traits::AuctionError as Error;
+
+    /// An open descending (Dutch) auction
+    ///
+    /// The price starts high and drops each block until it reaches the reserve price.
+    /// The first bidder to accept the price wins and pays it.
+    #[ink(storage)]
+    pub struct DutchAuction {
+        /// the proxy (contract)
+        proxy: Ownable,
+        /// the minimum price the seller will accept
+        reserve_price: Balance,
+        /// the price when the auction opens
+        start_price: Balance,
+        /// the amount the price drops each block
+        decrement: Balance,
+        /// the block the auction opened at
+        start_block: BlockNumber,
+        /// the bidder who accepted the price, and the price they accepted
+        accepted: Option<AuctionResult<AccountId, Balance>>,
+        /// the stage of the auction's lifecycle
+        status: AuctionStatus,
+        /// the outcome of the auction, once complete
+        outcome: Option<AuctionOutcome<AccountId, Balance>>,
+    }
+
+    impl DutchAuction {
+        /// Constructor that initializes a new auction, opening at the current block
+        ///
+        /// * `proxy`: the proxy (contract) which relays bids
+        /// * `reserve_price`: the minimum price the seller will accept
+        /// * `start_price`: the price when the auction opens
+        /// * `decrement`: the amount the price drops each block
+        ///
+        #[ink(constructor)]
+        pub fn new(
+            proxy: AccountId,
+            reserve_price: Balance,
+            start_price: Balance,
+            decrement: Balance,
+        ) -> Self {
+            Self {
+                proxy: Ownable::new(proxy),
+                reserve_price,
+                start_price,
+                decrement,
+                start_block: Self::env().block_number(),
+                accepted: None,
+                status: AuctionStatus::Open,
+                outcome: None,
+            }
+        }
+
+        /// get the current price
+        #[ink(message)]
+        pub fn get_price(&self) -> Balance {
+            let elapsed = self.env().block_number().saturating_sub(self.start_block);
+            self.start_price
+                .saturating_sub(self.decrement.saturating_mul(elapsed as Balance))
+                .max(self.reserve_price)
+        }
+
+        fn ensure_proxy(&self) -> Result<(), Error> {
+            self.proxy
+                .ensure_owner(self.env().caller())
+                .map_err(Error::from)
+        }
+    }
+
+    impl Auction for DutchAuction {
+        #[ink(message)]
+        fn get_kind(&self) -> AuctionKind {
+            AuctionKind::Dutch
+        }
+
+        #[ink(message)]
+        fn get_status(&self) -> AuctionStatus {
+            self.status
+        }
+
+        /// accept the current price, only the first acceptance is taken
+        #[ink(message)]
+        fn bid(&mut self, bidder: AccountId, bid: BidPayload) -> Result<(), Error> {
+            self.ensure_proxy()?;
+            if self.accepted.is_some() || self.status != AuctionStatus::Open {
+                return Err(Error::AuctionClosed);
+            }
+            if bid != BidPayload::Accept {
+                return Err(Error::UnsupportedBid);
+            }
+            self.accepted = Some(AuctionResult {
+                winner: bidder,
+                debt: self.get_price(),
+            });
+            Ok(())
+        }
+
+        /// the price is public, so there is nothing to reveal
+        #[ink(message)]
+        fn reveal_bid(&mut self, _revealed_bid: RevealedBid<AccountId>) -> Result<(), Error> {
+            Err(Error::UnsupportedBid)
+        }
+
+        /// Complete the auction
+        /// If nobody accepted the price there is no sale.
+        #[ink(message)]
+        fn complete(&mut self, _pulse: [u8; 32]) -> Result<(), Error> {
+            self.ensure_proxy()?;
+            if !self.status.can_become(AuctionStatus::Settled) {
+                return Err(Error::AlreadyComplete);
+            }
+            self.status = AuctionStatus::Settled;
+            self.outcome = Some(match self.accepted.clone() {
+                Some(accepted) => AuctionOutcome::Sale(vec![accepted]),
+                None => AuctionOutcome::NoSale,
+            });
+            Ok(())
+        }
+
+        /// withdraw the auction, while it is open
+        #[ink(message)]
+        fn cancel(&mut self) -> Result<(), Error> {
+            self.ensure_proxy()?;
+            if !self.status.can_become(AuctionStatus::Cancelled) {
+                return Err(Error::AuctionClosed);
+            }
+            self.status = AuctionStatus::Cancelled;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn get_outcome(&self) -> Option<AuctionOutcome<AccountId, Balance>> {
+            self.outcome.clone()
+        }
+
+        /// open bids cannot be invalid
+        #[ink(message)]
+        fn get_invalid_bids(&self) -> Vec<AccountId> {
+            Vec::new()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[ink::test]
+        fn price_drops_to_the_reserve() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let auction = DutchAuction::new(accounts.alice, 4, 10, 3);
+            assert_eq!(auction.get_price(), 10);
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            assert_eq!(auction.get_price(), 7);
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            assert_eq!(auction.get_price(), 4);
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            assert_eq!(auction.get_price(), 4);
+        }
+
+        #[ink::test]
+        fn first_acceptance_wins_at_the_current_price() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = DutchAuction::new(accounts.alice, 0, 10, 2);
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            assert_eq!(auction.bid(accounts.bob, BidPayload::Open(10)), Err(Error::UnsupportedBid));
+            assert_eq!(auction.bid(accounts.bob, BidPayload::Accept), Ok(()));
+            assert_eq!(auction.bid(accounts.charlie, BidPayload::Accept), Err(Error::AuctionClosed));
+            assert_eq!(auction.complete([0; 32]), Ok(()));
+            assert_eq!(
+                auction.get_outcome(),
+                Some(AuctionOutcome::Sale(vec![AuctionResult {
+                    winner: accounts.bob,
+                    debt: 8
+                }]))
+            );
+        }
+
+        #[ink::test]
+        fn no_acceptance_is_no_sale() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = DutchAuction::new(accounts.alice, 0, 10, 2);
+            assert_eq!(auction.complete([0; 32]), Ok(()));
+            assert_eq!(auction.get_outcome(), Some(AuctionOutcome::NoSale));
+        }
+    }
+}
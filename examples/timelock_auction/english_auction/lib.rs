@@ -0,0 +1,216 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+pub use self::english_auction::{EnglishAuction, EnglishAuctionRef};
+
+use idl_contract_extension::ext::DrandEnvironment;
+
+#[ink::contract(env = DrandEnvironment)]
+mod english_auction {
+    use crate::DrandEnvironment;
+    use idl_contract_extension::access::Ownable;
+    use auction_traits::{
+        Auction, AuctionKind, AuctionOutcome, AuctionResult, AuctionStatus, BidPayload,
+        RevealedBid,
+    };
+    use ink::prelude::{vec, vec::Vec};
+
+    pub use auction_traits::AuctionError as Error;
+
+    /// An open ascending (English) auction
+    ///
+    /// Bids are public and each must beat the highest bid by the minimum increment.
+    /// The highest bidder when the auction completes wins and pays their bid.
+    #[ink(storage)]
+    pub struct EnglishAuction {
+        /// the proxy (contract)
+        proxy: Ownable,
+        /// the minimum price the seller will accept
+        reserve_price: Balance,
+        /// the minimum amount by which a bid must beat the highest bid
+        min_increment: Balance,
+        /// the highest bidder and their bid
+        highest_bid: Option<AuctionResult<AccountId, Balance>>,
+        /// the stage of the auction's lifecycle
+        status: AuctionStatus,
+        /// the outcome of the auction, once complete
+        outcome: Option<AuctionOutcome<AccountId, Balance>>,
+    }
+
+    impl EnglishAuction {
+        /// Constructor that initializes a new auction
+        ///
+        /// * `proxy`: the proxy (contract) which relays bids
+        /// * `reserve_price`: the minimum price the seller will accept
+        /// * `min_increment`: the minimum amount by which a bid must beat the highest bid
+        ///
+        #[ink(constructor)]
+        pub fn new(proxy: AccountId, reserve_price: Balance, min_increment: Balance) -> Self {
+            Self {
+                proxy: Ownable::new(proxy),
+                reserve_price,
+                min_increment,
+                highest_bid: None,
+                status: AuctionStatus::Open,
+                outcome: None,
+            }
+        }
+
+        /// get the highest bidder and their bid
+        #[ink(message)]
+        pub fn get_highest_bid(&self) -> Option<AuctionResult<AccountId, Balance>> {
+            self.highest_bid.clone()
+        }
+
+        /// get the lowest amount the next bid may be
+        #[ink(message)]
+        pub fn get_min_bid(&self) -> Balance {
+            match &self.highest_bid {
+                Some(highest_bid) => highest_bid
+                    .debt
+                    .saturating_add(self.min_increment.max(1)),
+                None => self.reserve_price,
+            }
+        }
+
+        fn ensure_proxy(&self) -> Result<(), Error> {
+            self.proxy
+                .ensure_owner(self.env().caller())
+                .map_err(Error::from)
+        }
+    }
+
+    impl Auction for EnglishAuction {
+        #[ink(message)]
+        fn get_kind(&self) -> AuctionKind {
+            AuctionKind::English
+        }
+
+        #[ink(message)]
+        fn get_status(&self) -> AuctionStatus {
+            self.status
+        }
+
+        /// place an open bid, which must be at least the minimum bid
+        #[ink(message)]
+        fn bid(&mut self, bidder: AccountId, bid: BidPayload) -> Result<(), Error> {
+            self.ensure_proxy()?;
+            if self.status != AuctionStatus::Open {
+                return Err(Error::AuctionClosed);
+            }
+            let BidPayload::Open(amount) = bid else {
+                return Err(Error::UnsupportedBid);
+            };
+            if amount < self.get_min_bid() {
+                return Err(Error::BidTooLow);
+            }
+            self.highest_bid = Some(AuctionResult {
+                winner: bidder,
+                debt: amount,
+            });
+            Ok(())
+        }
+
+        /// open bids are never sealed, so there is nothing to reveal
+        #[ink(message)]
+        fn reveal_bid(&mut self, _revealed_bid: RevealedBid<AccountId>) -> Result<(), Error> {
+            Err(Error::UnsupportedBid)
+        }
+
+        /// Complete the auction, the highest bidder wins
+        /// If there were no bids there is no sale.
+        #[ink(message)]
+        fn complete(&mut self, _pulse: [u8; 32]) -> Result<(), Error> {
+            self.ensure_proxy()?;
+            if !self.status.can_become(AuctionStatus::Settled) {
+                return Err(Error::AlreadyComplete);
+            }
+            self.status = AuctionStatus::Settled;
+            self.outcome = Some(match self.highest_bid.clone() {
+                Some(highest_bid) => AuctionOutcome::Sale(vec![highest_bid]),
+                None => AuctionOutcome::NoSale,
+            });
+            Ok(())
+        }
+
+        /// withdraw the auction, while it is open
+        #[ink(message)]
+        fn cancel(&mut self) -> Result<(), Error> {
+            self.ensure_proxy()?;
+            if !self.status.can_become(AuctionStatus::Cancelled) {
+                return Err(Error::AuctionClosed);
+            }
+            self.status = AuctionStatus::Cancelled;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn get_outcome(&self) -> Option<AuctionOutcome<AccountId, Balance>> {
+            self.outcome.clone()
+        }
+
+        /// open bids cannot be invalid
+        #[ink(message)]
+        fn get_invalid_bids(&self) -> Vec<AccountId> {
+            Vec::new()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[ink::test]
+        fn bids_must_beat_the_highest_bid() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = EnglishAuction::new(accounts.alice, 5, 2);
+            assert_eq!(auction.bid(accounts.bob, BidPayload::Open(4)), Err(Error::BidTooLow));
+            assert_eq!(auction.bid(accounts.bob, BidPayload::Open(5)), Ok(()));
+            assert_eq!(auction.get_min_bid(), 7);
+            assert_eq!(
+                auction.bid(accounts.charlie, BidPayload::Open(6)),
+                Err(Error::BidTooLow)
+            );
+            assert_eq!(auction.bid(accounts.charlie, BidPayload::Open(7)), Ok(()));
+            assert_eq!(
+                auction.get_highest_bid(),
+                Some(AuctionResult {
+                    winner: accounts.charlie,
+                    debt: 7
+                })
+            );
+        }
+
+        #[ink::test]
+        fn highest_bidder_wins() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = EnglishAuction::new(accounts.alice, 0, 0);
+            assert_eq!(auction.bid(accounts.bob, BidPayload::Open(3)), Ok(()));
+            // a zero increment still requires a strictly higher bid
+            assert_eq!(
+                auction.bid(accounts.charlie, BidPayload::Open(3)),
+                Err(Error::BidTooLow)
+            );
+            assert_eq!(auction.bid(accounts.charlie, BidPayload::Open(4)), Ok(()));
+            assert_eq!(auction.complete([0; 32]), Ok(()));
+            assert_eq!(
+                auction.get_outcome(),
+                Some(AuctionOutcome::Sale(vec![AuctionResult {
+                    winner: accounts.charlie,
+                    debt: 4
+                }]))
+            );
+            assert_eq!(
+                auction.bid(accounts.bob, BidPayload::Open(10)),
+                Err(Error::AuctionClosed)
+            );
+        }
+
+        #[ink::test]
+        fn no_bids_is_no_sale() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = EnglishAuction::new(accounts.alice, 0, 1);
+            assert_eq!(auction.bid(accounts.bob, BidPayload::Accept), Err(Error::UnsupportedBid));
+            assert_eq!(auction.complete([0; 32]), Ok(()));
+            assert_eq!(auction.get_outcome(), Some(AuctionOutcome::NoSale));
+        }
+    }
+}
@@ -1,6 +1,7 @@
 //! # ERC-721
 //!
-//! This is an ERC-721 Token implementation.
+//! This is a non-fungible token implementation of the PSP34 standard (core, metadata,
+//! enumerable, mintable and burnable), the ink! counterpart of ERC-721.
 //!
 //! ## Warning
 //!
@@ -10,13 +11,15 @@
 //! ## Overview
 //!
 //! This contract demonstrates how to build non-fungible or unique tokens using ink!.
+//! Tokens are identified by a PSP34 `Id`, any of the integer widths or raw bytes.
 //!
 //! ## Error Handling
 //!
 //! Any function that modifies the state returns a `Result` type and does not changes the
-//! state if the `Error` occurs.
-//! The errors are defined as an `enum` type. Any other error or invariant violation
-//! triggers a panic and therefore rolls back the transaction.
+//! state if the `PSP34Error` occurs. Errors specific to this contract are
+//! `PSP34Error::Custom`, named after the error (e.g. `"MissingRole"`).
+//! Any other error or invariant violation triggers a panic and therefore rolls back the
+//! transaction.
 //!
 //! ## Token Management
 //!
@@ -29,9 +32,14 @@
 //!
 //! ### Token Creation
 //!
-//! Token creation start by calling the `mint(&mut self, id: u32)` function.
+//! Token creation start by calling the `PSP34Mintable::mint(&mut self, id: Id)` function.
 //! The token owner becomes the function caller. The Token ID needs to be specified
-//! as the argument on this function call.
+//! as the argument on this function call. Alternatively `mint_next` derives an unused
+//! `Id::U128` from the latest drand pulse (see `unique_id`), so that minted ids can
+//! neither be front-run nor collide.
+//!
+//! Only accounts with the `MINTER` role can mint. The account instantiating the contract
+//! (e.g. the `TlockProxy`) is a minter and an `ADMIN`, who grants and revokes roles.
 //!
 //! ### Token Transfer
 //!
@@ -40,63 +48,162 @@
 //! - The approved address of a token
 //! - An authorized operator of the current owner of a token
 //!
-//! The token owner can transfer a token by calling the `transfer` or `transfer_from`
-//! functions. An approved address can make a token transfer by calling the
-//! `transfer_from` function. Operators can transfer tokens on another account's behalf or
-//! can approve a token transfer for a different account.
+//! `PSP34::transfer` moves a token from its owner, whoever of them calls it. Owners
+//! approve an account for a single token, or an operator for all of their tokens, with
+//! `PSP34::approve`. `PSP34TransferFrom::transfer_from` names the owner the token is
+//! moved from, and fails if they do not own it.
+//!
+//! `safe_transfer_from` also asks contract recipients to accept the token through the
+//! `Erc721Receiver` trait, so that tokens are not locked in contracts unable to move them.
 //!
 //! ### Token Removal
 //!
-//! Tokens can be destroyed by burning them. Only the token owner is allowed to burn a
-//! token.
+//! Tokens can be destroyed by burning them with `PSP34Burnable::burn`. Only the token
+//! owner, or an account approved by them, is allowed to burn a token.
+//!
+//! ### Metadata and Enumeration
+//!
+//! A collection created with `new_with_metadata` has a `name` and a `symbol` attribute,
+//! set on its `collection_id`. The owner of a token sets its attributes (e.g. a `uri`
+//! pointing at its metadata) with `set_attribute`, and anyone reads them with
+//! `PSP34Metadata::get_attribute`. Every token is listed by `token_by_index` (up to
+//! `total_supply`) and in its owner's list, read a page at a time with
+//! `tokens_of_owner`. The lists follow the `Transfer` events: minting appends, burning
+//! removes and transfers move the token between owners.
+//!
+//! ### Royalties
+//!
+//! A token minted with `mint_with_royalty` pays a share of every sale price, in basis
+//! points, to the receiver chosen by its creator. Marketplaces read it EIP-2981 style
+//! with `PSP34Royalty::royalty_info(id, sale_price)`.
 
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 pub use self::erc721::{
     Erc721,
     Erc721Ref,
+    ADMIN,
+    MAX_PAGE_SIZE,
+    MINTER,
+};
+pub use idl_contract_extension::psp34::{
+    Id,
+    PSP34Error,
 };
 
-use etf_contract_utils::ext::EtfEnvironment;
+use idl_contract_extension::ext::DrandEnvironment;
+use ink::{
+    prelude::vec::Vec,
+    primitives::AccountId,
+};
 
-#[ink::contract(env = EtfEnvironment)]
-mod erc721 {
-    use crate::EtfEnvironment;
-    use ink::storage::Mapping;
+/// Implemented by contracts which accept tokens sent with `safe_transfer_from`.
+#[ink::trait_definition]
+pub trait Erc721Receiver {
+    /// Called after token `id` was sent to the contract, returns `true` to accept it.
+    /// Otherwise the transfer is reverted.
+    #[ink(message)]
+    fn on_erc721_received(
+        &mut self,
+        operator: AccountId,
+        from: AccountId,
+        id: Id,
+        data: Vec<u8>,
+    ) -> bool;
+}
 
-    use scale::{
-        Decode,
-        Encode,
+#[ink::contract(env = DrandEnvironment)]
+mod erc721 {
+    use crate::{
+        DrandEnvironment,
+        Erc721Receiver,
+    };
+    use idl_contract_extension::{
+        access::{
+            RoleId,
+            Roles,
+        },
+        psp34::{
+            Id,
+            PSP34Burnable,
+            PSP34Enumerable,
+            PSP34Error,
+            PSP34Metadata,
+            PSP34Mintable,
+            PSP34Royalty,
+            PSP34TransferFrom,
+            PSP34,
+        },
+        types::{
+            share_of,
+            unique_id,
+            BasisPoints,
+            MAX_BASIS_POINTS,
+        },
+    };
+    use ink::{
+        codegen::TraitCallBuilder,
+        prelude::{
+            string::String,
+            vec::Vec,
+        },
+        storage::Mapping,
     };
 
-    /// A token ID.
-    pub type TokenId = u32;
+    /// The maximum number of tokens returned by a single query.
+    pub const MAX_PAGE_SIZE: u32 = 100;
+
+    /// The role of accounts which can mint tokens.
+    pub const MINTER: RoleId = 0;
+
+    /// The role of accounts which can grant and revoke roles.
+    pub const ADMIN: RoleId = 1;
+
+    /// A token (or the collection) and the key of one of its attributes.
+    type AttributeKey = (Id, Vec<u8>);
+
+    /// A contract accepting tokens sent with `safe_transfer_from`.
+    type ReceiverRef = ink::contract_ref!(Erc721Receiver, DrandEnvironment);
 
     #[ink(storage)]
     #[derive(Default)]
     pub struct Erc721 {
         /// Mapping from token to owner.
-        token_owner: Mapping<TokenId, AccountId>,
+        token_owner: Mapping<Id, AccountId>,
         /// Mapping from token to approvals users.
-        token_approvals: Mapping<TokenId, AccountId>,
+        token_approvals: Mapping<Id, AccountId>,
         /// Mapping from owner to number of owned token.
         owned_tokens_count: Mapping<AccountId, u32>,
         /// Mapping from owner to operator approvals.
         operator_approvals: Mapping<(AccountId, AccountId), ()>,
+        /// Mapping from token to royalty receiver and share of the sale price.
+        royalties: Mapping<Id, (AccountId, BasisPoints)>,
+        /// Mapping from token (or collection) and key to the value of the attribute.
+        attributes: Mapping<AttributeKey, Vec<u8>>,
+        /// Mapping from token to the keys of its attributes, removed when it is burned.
+        attribute_keys: Mapping<Id, Vec<Vec<u8>>>,
+        /// The number of tokens in existence.
+        total_supply: u32,
+        /// Mapping from position to token, over every token.
+        all_tokens: Mapping<u32, Id>,
+        /// Mapping from token to its position in `all_tokens`.
+        all_tokens_index: Mapping<Id, u32>,
+        /// Mapping from owner and position to token, over the tokens of each owner.
+        owned_tokens: Mapping<(AccountId, u32), Id>,
+        /// Mapping from token to its position in its owner's tokens.
+        owned_tokens_index: Mapping<Id, u32>,
+        /// The minters and admins.
+        roles: Roles,
+        /// The counter mixed into the next id `mint_next` derives.
+        next_id: u64,
     }
 
-    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
-    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
-    pub enum Error {
-        NotOwner,
-        NotApproved,
-        TokenExists,
-        TokenNotFound,
-        CannotInsert,
-        CannotFetchValue,
-        NotAllowed,
+    /// A `PSP34Error::Custom`, named after the error.
+    fn custom(name: &str) -> PSP34Error {
+        PSP34Error::Custom(String::from(name))
     }
 
     /// Event emitted when a token transfer occurs.
+    /// `from` is `None` when the token is minted, `to` is `None` when it is burned.
     #[ink(event)]
     pub struct Transfer {
         #[ink(topic)]
@@ -104,167 +211,200 @@ mod erc721 {
         #[ink(topic)]
         to: Option<AccountId>,
         #[ink(topic)]
-        id: TokenId,
+        id: Id,
     }
 
-    /// Event emitted when a token approve occurs.
+    /// Event emitted when an account is approved for a token, or an operator for all
+    /// tokens of the owner when `id` is `None`.
     #[ink(event)]
     pub struct Approval {
         #[ink(topic)]
-        from: AccountId,
+        owner: AccountId,
         #[ink(topic)]
-        to: AccountId,
+        operator: AccountId,
         #[ink(topic)]
-        id: TokenId,
+        id: Option<Id>,
+        approved: bool,
     }
 
-    /// Event emitted when an operator is enabled or disabled for an owner.
-    /// The operator can manage all NFTs of the owner.
+    /// Event emitted when an attribute of a token (or of the collection) is set.
     #[ink(event)]
-    pub struct ApprovalForAll {
+    pub struct AttributeSet {
         #[ink(topic)]
-        owner: AccountId,
-        #[ink(topic)]
-        operator: AccountId,
-        approved: bool,
+        id: Id,
+        key: Vec<u8>,
+        data: Vec<u8>,
     }
 
     impl Erc721 {
-        /// Creates a new ERC-721 token contract.
+        /// Creates a new PSP34 token contract.
+        /// The caller becomes a minter and an admin.
         #[ink(constructor)]
         pub fn new() -> Self {
-            Default::default()
-        }
-
-        /// Returns the balance of the owner.
-        ///
-        /// This represents the amount of unique tokens the owner has.
-        #[ink(message)]
-        pub fn balance_of(&self, owner: AccountId) -> u32 {
-            self.balance_of_or_zero(&owner)
+            let mut erc721 = Self::default();
+            let caller = Self::env().caller();
+            erc721.roles.grant(ADMIN, caller);
+            erc721.roles.grant(MINTER, caller);
+            erc721
         }
 
-        /// Returns the owner of the token.
-        #[ink(message)]
-        pub fn owner_of(&self, id: TokenId) -> Option<AccountId> {
-            self.token_owner.get(id)
-        }
-
-        /// Returns the approved account ID for this token if any.
-        #[ink(message)]
-        pub fn get_approved(&self, id: TokenId) -> Option<AccountId> {
-            self.token_approvals.get(id)
+        /// Creates a new PSP34 token contract with a name and a symbol.
+        /// The caller becomes a minter and an admin.
+        #[ink(constructor)]
+        pub fn new_with_metadata(name: String, symbol: String) -> Self {
+            let mut erc721 = Self::new();
+            let collection = erc721.collection_id();
+            erc721.set_attribute_of(collection.clone(), b"name".to_vec(), name.into_bytes());
+            erc721.set_attribute_of(collection, b"symbol".to_vec(), symbol.into_bytes());
+            erc721
         }
 
-        /// Returns `true` if the operator is approved by the owner.
+        /// Returns `true` if the account has been granted the role.
         #[ink(message)]
-        pub fn is_approved_for_all(&self, owner: AccountId, operator: AccountId) -> bool {
-            self.approved_for_all(owner, operator)
+        pub fn has_role(&self, role: RoleId, account: AccountId) -> bool {
+            self.roles.has_role(role, account)
         }
 
-        /// Approves or disapproves the operator for all tokens of the caller.
+        /// Grants the role to the account. Only admins can grant roles.
         #[ink(message)]
-        pub fn set_approval_for_all(
-            &mut self,
-            to: AccountId,
-            approved: bool,
-        ) -> Result<(), Error> {
-            self.approve_for_all(to, approved)?;
+        pub fn grant_role(&mut self, role: RoleId, account: AccountId) -> Result<(), PSP34Error> {
+            self.roles.ensure_role(ADMIN, self.env().caller())?;
+            self.roles.grant(role, account);
             Ok(())
         }
 
-        /// Approves the account to transfer the specified token on behalf of the caller.
+        /// Revokes the role from the account. Only admins can revoke roles.
         #[ink(message)]
-        pub fn approve(&mut self, to: AccountId, id: TokenId) -> Result<(), Error> {
-            self.approve_for(&to, id)?;
+        pub fn revoke_role(&mut self, role: RoleId, account: AccountId) -> Result<(), PSP34Error> {
+            self.roles.ensure_role(ADMIN, self.env().caller())?;
+            self.roles.revoke(role, account);
             Ok(())
         }
 
-        /// Transfers the token from the caller to the given destination.
+        /// Sets an attribute of the token, or of the collection for its `collection_id`.
+        /// Only the owner of the token, or an admin for the collection, can set it.
         #[ink(message)]
-        pub fn transfer(
+        pub fn set_attribute(
             &mut self,
-            destination: AccountId,
-            id: TokenId,
-        ) -> Result<(), Error> {
+            id: Id,
+            key: Vec<u8>,
+            data: Vec<u8>,
+        ) -> Result<(), PSP34Error> {
             let caller = self.env().caller();
-            self.transfer_token_from(&caller, &destination, id)?;
+            if id == self.collection_id() {
+                self.roles.ensure_role(ADMIN, caller)?;
+            } else {
+                let owner = self.token_owner.get(&id).ok_or(PSP34Error::TokenNotExists)?;
+                if owner != caller {
+                    return Err(PSP34Error::NotApproved)
+                }
+                let mut keys = self.attribute_keys.get(&id).unwrap_or_default();
+                if !keys.contains(&key) {
+                    keys.push(key.clone());
+                    self.attribute_keys.insert(&id, &keys);
+                }
+            }
+            self.set_attribute_of(id, key, data);
             Ok(())
         }
 
-        /// Transfer approved or owned token.
+        /// Returns up to `limit` tokens of the owner, starting at `offset`.
+        /// At most `MAX_PAGE_SIZE` tokens are returned.
         #[ink(message)]
-        pub fn transfer_from(
+        pub fn tokens_of_owner(&self, owner: AccountId, offset: u32, limit: u32) -> Vec<Id> {
+            let end = offset
+                .saturating_add(limit.min(MAX_PAGE_SIZE))
+                .min(self.balance_of_or_zero(&owner));
+            (offset..end)
+                .filter_map(|index| self.owned_tokens.get((owner, index)))
+                .collect()
+        }
+
+        /// Transfers approved or owned token, and asks a contract recipient to accept it
+        /// with `Erc721Receiver::on_erc721_received`.
+        #[ink(message)]
+        pub fn safe_transfer_from(
             &mut self,
             from: AccountId,
             to: AccountId,
-            id: TokenId,
-        ) -> Result<(), Error> {
-            self.transfer_token_from(&from, &to, id)?;
+            id: Id,
+            data: Vec<u8>,
+        ) -> Result<(), PSP34Error> {
+            self.transfer_token_from(&from, &to, id.clone())?;
+            if self.env().is_contract(&to) {
+                let mut receiver: ReceiverRef = to.into();
+                let accepted = receiver
+                    .call_mut()
+                    .on_erc721_received(self.env().caller(), from, id, data)
+                    .try_invoke();
+                if !matches!(accepted, Ok(Ok(true))) {
+                    return Err(PSP34Error::SafeTransferCheckFailed(String::from(
+                        "the recipient did not accept the token",
+                    )))
+                }
+            }
             Ok(())
         }
 
-        /// Creates a new token.
+        /// Creates a new token with an unused `Id::U128`, derived by the contract from the
+        /// latest drand pulse so that callers cannot take it first. Only minters can mint.
+        /// Returns the Token ID.
         #[ink(message)]
-        pub fn mint(&mut self, id: TokenId) -> Result<(), Error> {
-            let caller = self.env().caller();
-            self.add_token_to(&caller, id)?;
-            self.env().emit_event(Transfer {
-                from: Some(AccountId::from([0x0; 32])),
-                to: Some(caller),
-                id,
-            });
-            Ok(())
+        pub fn mint_next(&mut self) -> Result<Id, PSP34Error> {
+            self.roles.ensure_role(MINTER, self.env().caller())?;
+            let pulse = self.env().extension().random();
+            let nonce = self.env().account_id();
+            let mut counter = self.next_id;
+            let mut id = Id::U128(unique_id(&pulse, nonce.as_ref(), counter));
+            while self.exists(&id) {
+                counter = counter.checked_add(1).ok_or_else(|| custom("CannotInsert"))?;
+                id = Id::U128(unique_id(&pulse, nonce.as_ref(), counter));
+            }
+            self.mint(id.clone())?;
+            self.next_id = counter.saturating_add(1);
+            Ok(id)
         }
 
-        /// Deletes an existing token. Only the owner can burn the token.
+        /// Creates a new token which pays `bps` basis points of every sale to `receiver`.
         #[ink(message)]
-        pub fn burn(&mut self, id: TokenId) -> Result<(), Error> {
-            let caller = self.env().caller();
-            let Self {
-                token_owner,
-                owned_tokens_count,
-                ..
-            } = self;
-
-            let owner = token_owner.get(id).ok_or(Error::TokenNotFound)?;
-            if owner != caller {
-                return Err(Error::NotOwner)
-            };
-
-            let count = owned_tokens_count
-                .get(caller)
-                .map(|c| c - 1)
-                .ok_or(Error::CannotFetchValue)?;
-            owned_tokens_count.insert(caller, &count);
-            token_owner.remove(id);
-
-            self.env().emit_event(Transfer {
-                from: Some(caller),
-                to: Some(AccountId::from([0x0; 32])),
-                id,
-            });
-
+        pub fn mint_with_royalty(
+            &mut self,
+            id: Id,
+            receiver: AccountId,
+            bps: BasisPoints,
+        ) -> Result<(), PSP34Error> {
+            if bps > MAX_BASIS_POINTS {
+                return Err(custom("InvalidRoyalty"))
+            }
+            self.mint(id.clone())?;
+            self.royalties.insert(&id, &(receiver, bps));
             Ok(())
         }
 
-        /// Transfers token `id` `from` the sender to the `to` `AccountId`.
+        /// Sets an attribute, without checking the caller.
+        fn set_attribute_of(&mut self, id: Id, key: Vec<u8>, data: Vec<u8>) {
+            self.attributes.insert((&id, &key), &data);
+            self.env().emit_event(AttributeSet { id, key, data });
+        }
+
+        /// Transfers token `id` `from` its owner to the `to` `AccountId`.
         fn transfer_token_from(
             &mut self,
             from: &AccountId,
             to: &AccountId,
-            id: TokenId,
-        ) -> Result<(), Error> {
+            id: Id,
+        ) -> Result<(), PSP34Error> {
             let caller = self.env().caller();
-            if !self.exists(id) {
-                return Err(Error::TokenNotFound)
+            let owner = self.token_owner.get(&id).ok_or(PSP34Error::TokenNotExists)?;
+            if owner != *from {
+                return Err(PSP34Error::NotApproved)
             };
-            if !self.approved_or_owner(Some(caller), id) {
-                return Err(Error::NotApproved)
+            if caller != owner && !self.allowance(owner, caller, Some(id.clone())) {
+                return Err(PSP34Error::NotApproved)
             };
-            self.clear_approval(id);
-            self.remove_token_from(from, id)?;
-            self.add_token_to(to, id)?;
+            self.clear_approval(&id);
+            self.remove_token_from(from, &id)?;
+            self.add_token_to(to, &id)?;
             self.env().emit_event(Transfer {
                 from: Some(*from),
                 to: Some(*to),
@@ -274,11 +414,7 @@ mod erc721 {
         }
 
         /// Removes token `id` from the owner.
-        fn remove_token_from(
-            &mut self,
-            from: &AccountId,
-            id: TokenId,
-        ) -> Result<(), Error> {
+        fn remove_token_from(&mut self, from: &AccountId, id: &Id) -> Result<(), PSP34Error> {
             let Self {
                 token_owner,
                 owned_tokens_count,
@@ -286,21 +422,51 @@ mod erc721 {
             } = self;
 
             if !token_owner.contains(id) {
-                return Err(Error::TokenNotFound)
+                return Err(PSP34Error::TokenNotExists)
             }
 
             let count = owned_tokens_count
                 .get(from)
-                .map(|c| c - 1)
-                .ok_or(Error::CannotFetchValue)?;
+                .and_then(|c| c.checked_sub(1))
+                .ok_or_else(|| custom("CannotFetchValue"))?;
             owned_tokens_count.insert(from, &count);
             token_owner.remove(id);
 
+            // move the owner's last token into the removed token's position
+            let index = self
+                .owned_tokens_index
+                .take(id)
+                .ok_or_else(|| custom("CannotFetchValue"))?;
+            let last = self
+                .owned_tokens
+                .take((*from, count))
+                .ok_or_else(|| custom("CannotFetchValue"))?;
+            if last != *id {
+                self.owned_tokens.insert((*from, index), &last);
+                self.owned_tokens_index.insert(&last, &index);
+            }
+
             Ok(())
         }
 
+        /// Removes token `id` from the list of every token.
+        fn remove_from_all_tokens(&mut self, id: &Id) {
+            let Some(index) = self.all_tokens_index.take(id) else {
+                return
+            };
+            self.total_supply = self.total_supply.saturating_sub(1);
+            let last = self
+                .all_tokens
+                .take(self.total_supply)
+                .unwrap_or_else(|| id.clone());
+            if last != *id {
+                self.all_tokens.insert(index, &last);
+                self.all_tokens_index.insert(&last, &index);
+            }
+        }
+
         /// Adds the token `id` to the `to` AccountID.
-        fn add_token_to(&mut self, to: &AccountId, id: TokenId) -> Result<(), Error> {
+        fn add_token_to(&mut self, to: &AccountId, id: &Id) -> Result<(), PSP34Error> {
             let Self {
                 token_owner,
                 owned_tokens_count,
@@ -308,17 +474,20 @@ mod erc721 {
             } = self;
 
             if token_owner.contains(id) {
-                return Err(Error::TokenExists)
+                return Err(PSP34Error::TokenExists)
             }
 
             if *to == AccountId::from([0x0; 32]) {
-                return Err(Error::NotAllowed)
+                return Err(custom("NotAllowed"))
             };
 
-            let count = owned_tokens_count.get(to).map(|c| c + 1).unwrap_or(1);
+            let index = owned_tokens_count.get(to).unwrap_or(0);
 
-            owned_tokens_count.insert(to, &count);
+            owned_tokens_count.insert(to, &index.saturating_add(1));
             token_owner.insert(id, to);
+            // append the token to the owner's tokens
+            self.owned_tokens.insert((*to, index), id);
+            self.owned_tokens_index.insert(id, &index);
 
             Ok(())
         }
@@ -326,60 +495,64 @@ mod erc721 {
         /// Approves or disapproves the operator to transfer all tokens of the caller.
         fn approve_for_all(
             &mut self,
-            to: AccountId,
+            operator: AccountId,
             approved: bool,
-        ) -> Result<(), Error> {
+        ) -> Result<(), PSP34Error> {
             let caller = self.env().caller();
-            if to == caller {
-                return Err(Error::NotAllowed)
+            if operator == caller {
+                return Err(PSP34Error::SelfApprove)
             }
-            self.env().emit_event(ApprovalForAll {
+            self.env().emit_event(Approval {
                 owner: caller,
-                operator: to,
+                operator,
+                id: None,
                 approved,
             });
 
             if approved {
-                self.operator_approvals.insert((&caller, &to), &());
+                self.operator_approvals.insert((&caller, &operator), &());
             } else {
-                self.operator_approvals.remove((&caller, &to));
+                self.operator_approvals.remove((&caller, &operator));
             }
 
             Ok(())
         }
 
-        /// Approve the passed `AccountId` to transfer the specified token on behalf of
-        /// the message's sender.
-        fn approve_for(&mut self, to: &AccountId, id: TokenId) -> Result<(), Error> {
+        /// Approves or disapproves the operator to transfer the specified token on behalf
+        /// of its owner. The caller must be the owner or one of their operators.
+        fn approve_for(
+            &mut self,
+            operator: AccountId,
+            id: Id,
+            approved: bool,
+        ) -> Result<(), PSP34Error> {
             let caller = self.env().caller();
-            let owner = self.owner_of(id);
-            if !(owner == Some(caller)
-                || self.approved_for_all(owner.expect("Error with AccountId"), caller))
-            {
-                return Err(Error::NotAllowed)
-            };
-
-            if *to == AccountId::from([0x0; 32]) {
-                return Err(Error::NotAllowed)
+            if operator == caller {
+                return Err(PSP34Error::SelfApprove)
+            }
+            let owner = self.token_owner.get(&id).ok_or(PSP34Error::TokenNotExists)?;
+            if owner != caller && !self.approved_for_all(owner, caller) {
+                return Err(PSP34Error::NotApproved)
             };
 
-            if self.token_approvals.contains(id) {
-                return Err(Error::CannotInsert)
-            } else {
-                self.token_approvals.insert(id, to);
+            if approved {
+                self.token_approvals.insert(&id, &operator);
+            } else if self.token_approvals.get(&id) == Some(operator) {
+                self.clear_approval(&id);
             }
 
             self.env().emit_event(Approval {
-                from: caller,
-                to: *to,
-                id,
+                owner,
+                operator,
+                id: Some(id),
+                approved,
             });
 
             Ok(())
         }
 
         /// Removes existing approval from token `id`.
-        fn clear_approval(&mut self, id: TokenId) {
+        fn clear_approval(&mut self, id: &Id) {
             self.token_approvals.remove(id);
         }
 
@@ -393,25 +566,178 @@ mod erc721 {
             self.operator_approvals.contains((&owner, &operator))
         }
 
-        /// Returns true if the `AccountId` `from` is the owner of token `id`
-        /// or it has been approved on behalf of the token `id` owner.
-        fn approved_or_owner(&self, from: Option<AccountId>, id: TokenId) -> bool {
-            let owner = self.owner_of(id);
-            from != Some(AccountId::from([0x0; 32]))
-                && (from == owner
-                    || from == self.token_approvals.get(id)
-                    || self.approved_for_all(
-                        owner.expect("Error with AccountId"),
-                        from.expect("Error with AccountId"),
-                    ))
-        }
-
         /// Returns true if token `id` exists or false if it does not.
-        fn exists(&self, id: TokenId) -> bool {
+        fn exists(&self, id: &Id) -> bool {
             self.token_owner.contains(id)
         }
     }
 
+    impl PSP34 for Erc721 {
+        /// Returns the contract's address, as bytes.
+        #[ink(message)]
+        fn collection_id(&self) -> Id {
+            Id::Bytes(AsRef::<[u8]>::as_ref(&self.env().account_id()).to_vec())
+        }
+
+        /// Returns the balance of the owner.
+        ///
+        /// This represents the amount of unique tokens the owner has.
+        #[ink(message)]
+        fn balance_of(&self, owner: AccountId) -> u32 {
+            self.balance_of_or_zero(&owner)
+        }
+
+        /// Returns the owner of the token.
+        #[ink(message)]
+        fn owner_of(&self, id: Id) -> Option<AccountId> {
+            self.token_owner.get(&id)
+        }
+
+        /// Returns `true` if the operator is approved by the owner for the token,
+        /// or for all of the owner's tokens.
+        #[ink(message)]
+        fn allowance(&self, owner: AccountId, operator: AccountId, id: Option<Id>) -> bool {
+            self.approved_for_all(owner, operator)
+                || id.is_some_and(|id| {
+                    self.token_owner.get(&id) == Some(owner)
+                        && self.token_approvals.get(&id) == Some(operator)
+                })
+        }
+
+        /// Approves or disapproves the operator for the token, or for all tokens of the
+        /// caller when `id` is `None`.
+        #[ink(message)]
+        fn approve(
+            &mut self,
+            operator: AccountId,
+            id: Option<Id>,
+            approved: bool,
+        ) -> Result<(), PSP34Error> {
+            match id {
+                Some(id) => self.approve_for(operator, id, approved),
+                None => self.approve_for_all(operator, approved),
+            }
+        }
+
+        /// Transfers the token from its owner to the given destination.
+        #[ink(message)]
+        fn transfer(&mut self, to: AccountId, id: Id, _data: Vec<u8>) -> Result<(), PSP34Error> {
+            let from = self.token_owner.get(&id).ok_or(PSP34Error::TokenNotExists)?;
+            self.transfer_token_from(&from, &to, id)
+        }
+
+        /// Returns the number of tokens in existence.
+        #[ink(message)]
+        fn total_supply(&self) -> u128 {
+            self.total_supply as u128
+        }
+    }
+
+    impl PSP34Metadata for Erc721 {
+        /// Returns the attribute of the token, or of the collection for its
+        /// `collection_id`, if set.
+        #[ink(message)]
+        fn get_attribute(&self, id: Id, key: Vec<u8>) -> Option<Vec<u8>> {
+            self.attributes.get((id, key))
+        }
+    }
+
+    impl PSP34Enumerable for Erc721 {
+        /// Returns the token at `index` of the owner's tokens, if `index` is less than
+        /// the owner's balance.
+        #[ink(message)]
+        fn owners_token_by_index(&self, owner: AccountId, index: u128) -> Result<Id, PSP34Error> {
+            u32::try_from(index)
+                .ok()
+                .and_then(|index| self.owned_tokens.get((owner, index)))
+                .ok_or(PSP34Error::TokenNotExists)
+        }
+
+        /// Returns the token at `index` of every token, if `index < total_supply`.
+        #[ink(message)]
+        fn token_by_index(&self, index: u128) -> Result<Id, PSP34Error> {
+            u32::try_from(index)
+                .ok()
+                .and_then(|index| self.all_tokens.get(index))
+                .ok_or(PSP34Error::TokenNotExists)
+        }
+    }
+
+    impl PSP34Mintable for Erc721 {
+        /// Creates a new token owned by the caller. Only minters can mint.
+        #[ink(message)]
+        fn mint(&mut self, id: Id) -> Result<(), PSP34Error> {
+            let caller = self.env().caller();
+            self.roles.ensure_role(MINTER, caller)?;
+            self.add_token_to(&caller, &id)?;
+            self.all_tokens.insert(self.total_supply, &id);
+            self.all_tokens_index.insert(&id, &self.total_supply);
+            self.total_supply = self.total_supply.saturating_add(1);
+            self.env().emit_event(Transfer {
+                from: None,
+                to: Some(caller),
+                id,
+            });
+            Ok(())
+        }
+    }
+
+    impl PSP34Burnable for Erc721 {
+        /// Deletes an existing token of `account`. Only the owner, or an account approved
+        /// by them, can burn the token.
+        #[ink(message)]
+        fn burn(&mut self, account: AccountId, id: Id) -> Result<(), PSP34Error> {
+            let caller = self.env().caller();
+            let owner = self.token_owner.get(&id).ok_or(PSP34Error::TokenNotExists)?;
+            if owner != account
+                || (caller != owner && !self.allowance(owner, caller, Some(id.clone())))
+            {
+                return Err(PSP34Error::NotApproved)
+            };
+
+            self.clear_approval(&id);
+            self.remove_token_from(&owner, &id)?;
+            self.remove_from_all_tokens(&id);
+            self.royalties.remove(&id);
+            for key in self.attribute_keys.take(&id).unwrap_or_default() {
+                self.attributes.remove((&id, &key));
+            }
+
+            self.env().emit_event(Transfer {
+                from: Some(owner),
+                to: None,
+                id,
+            });
+
+            Ok(())
+        }
+    }
+
+    impl PSP34Royalty for Erc721 {
+        /// Returns the royalty receiver and the royalty owed for a sale of the token
+        /// at `sale_price`, if the token pays royalties.
+        #[ink(message)]
+        fn royalty_info(&self, id: Id, sale_price: Balance) -> Option<(AccountId, Balance)> {
+            self.royalties
+                .get(&id)
+                .map(|(receiver, bps)| (receiver, share_of(sale_price, bps)))
+        }
+    }
+
+    impl PSP34TransferFrom for Erc721 {
+        /// Transfers the token from `from`, who must own it, to the given destination.
+        #[ink(message)]
+        fn transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            id: Id,
+            _data: Vec<u8>,
+        ) -> Result<(), PSP34Error> {
+            self.transfer_token_from(&from, &to, id)
+        }
+    }
+
     /// Unit tests
     #[cfg(test)]
     mod tests {
@@ -425,13 +751,17 @@ mod erc721 {
             // Create a new contract instance.
             let mut erc721 = Erc721::new();
             // Token 1 does not exists.
-            assert_eq!(erc721.owner_of(1), None);
+            assert_eq!(erc721.owner_of(id(1)), None);
             // Alice does not owns tokens.
             assert_eq!(erc721.balance_of(accounts.alice), 0);
             // Create token Id 1.
-            assert_eq!(erc721.mint(1), Ok(()));
+            assert_eq!(erc721.mint(id(1)), Ok(()));
             // Alice owns 1 token.
             assert_eq!(erc721.balance_of(accounts.alice), 1);
+            // Any kind of Id can be minted.
+            assert_eq!(erc721.mint(Id::Bytes(b"one".to_vec())), Ok(()));
+            assert_eq!(erc721.mint(Id::U8(1)), Ok(()));
+            assert_eq!(erc721.balance_of(accounts.alice), 3);
         }
 
         #[ink::test]
@@ -441,16 +771,16 @@ mod erc721 {
             // Create a new contract instance.
             let mut erc721 = Erc721::new();
             // Create token Id 1.
-            assert_eq!(erc721.mint(1), Ok(()));
+            assert_eq!(erc721.mint(id(1)), Ok(()));
             // The first Transfer event takes place
             assert_eq!(1, ink::env::test::recorded_events().count());
             // Alice owns 1 token.
             assert_eq!(erc721.balance_of(accounts.alice), 1);
             // Alice owns token Id 1.
-            assert_eq!(erc721.owner_of(1), Some(accounts.alice));
+            assert_eq!(erc721.owner_of(id(1)), Some(accounts.alice));
             // Cannot create  token Id if it exists.
             // Bob cannot own token Id 1.
-            assert_eq!(erc721.mint(1), Err(Error::TokenExists));
+            assert_eq!(erc721.mint(id(1)), Err(PSP34Error::TokenExists));
         }
 
         #[ink::test]
@@ -460,7 +790,7 @@ mod erc721 {
             // Create a new contract instance.
             let mut erc721 = Erc721::new();
             // Create token Id 1 for Alice
-            assert_eq!(erc721.mint(1), Ok(()));
+            assert_eq!(erc721.mint(id(1)), Ok(()));
             // Alice owns token 1
             assert_eq!(erc721.balance_of(accounts.alice), 1);
             // Bob does not owns any token
@@ -468,7 +798,7 @@ mod erc721 {
             // The first Transfer event takes place
             assert_eq!(1, ink::env::test::recorded_events().count());
             // Alice transfers token 1 to Bob
-            assert_eq!(erc721.transfer(accounts.bob, 1), Ok(()));
+            assert_eq!(erc721.transfer(accounts.bob, id(1), Vec::new()), Ok(()));
             // The second Transfer event takes place
             assert_eq!(2, ink::env::test::recorded_events().count());
             // Bob owns token 1
@@ -482,19 +812,25 @@ mod erc721 {
             // Create a new contract instance.
             let mut erc721 = Erc721::new();
             // Transfer token fails if it does not exists.
-            assert_eq!(erc721.transfer(accounts.bob, 2), Err(Error::TokenNotFound));
+            assert_eq!(
+                erc721.transfer(accounts.bob, id(2), Vec::new()),
+                Err(PSP34Error::TokenNotExists)
+            );
             // Token Id 2 does not exists.
-            assert_eq!(erc721.owner_of(2), None);
+            assert_eq!(erc721.owner_of(id(2)), None);
             // Create token Id 2.
-            assert_eq!(erc721.mint(2), Ok(()));
+            assert_eq!(erc721.mint(id(2)), Ok(()));
             // Alice owns 1 token.
             assert_eq!(erc721.balance_of(accounts.alice), 1);
             // Token Id 2 is owned by Alice.
-            assert_eq!(erc721.owner_of(2), Some(accounts.alice));
+            assert_eq!(erc721.owner_of(id(2)), Some(accounts.alice));
             // Set Bob as caller
             set_caller(accounts.bob);
             // Bob cannot transfer not owned tokens.
-            assert_eq!(erc721.transfer(accounts.eve, 2), Err(Error::NotApproved));
+            assert_eq!(
+                erc721.transfer(accounts.eve, id(2), Vec::new()),
+                Err(PSP34Error::NotApproved)
+            );
         }
 
         #[ink::test]
@@ -504,20 +840,21 @@ mod erc721 {
             // Create a new contract instance.
             let mut erc721 = Erc721::new();
             // Create token Id 1.
-            assert_eq!(erc721.mint(1), Ok(()));
+            assert_eq!(erc721.mint(id(1)), Ok(()));
             // Token Id 1 is owned by Alice.
-            assert_eq!(erc721.owner_of(1), Some(accounts.alice));
+            assert_eq!(erc721.owner_of(id(1)), Some(accounts.alice));
             // Approve token Id 1 transfer for Bob on behalf of Alice.
-            assert_eq!(erc721.approve(accounts.bob, 1), Ok(()));
+            assert_eq!(erc721.approve(accounts.bob, Some(id(1)), true), Ok(()));
+            assert!(erc721.allowance(accounts.alice, accounts.bob, Some(id(1))));
+            assert!(!erc721.allowance(accounts.alice, accounts.bob, None));
             // Set Bob as caller
             set_caller(accounts.bob);
             // Bob transfers token Id 1 from Alice to Eve.
-            assert_eq!(
-                erc721.transfer_from(accounts.alice, accounts.eve, 1),
-                Ok(())
-            );
+            assert_eq!(erc721.transfer(accounts.eve, id(1), Vec::new()), Ok(()));
             // TokenId 3 is owned by Eve.
-            assert_eq!(erc721.owner_of(1), Some(accounts.eve));
+            assert_eq!(erc721.owner_of(id(1)), Some(accounts.eve));
+            // The approval is cleared by the transfer.
+            assert!(!erc721.allowance(accounts.eve, accounts.bob, Some(id(1))));
             // Alice does not owns tokens.
             assert_eq!(erc721.balance_of(accounts.alice), 0);
             // Bob does not owns tokens.
@@ -533,40 +870,40 @@ mod erc721 {
             // Create a new contract instance.
             let mut erc721 = Erc721::new();
             // Create token Id 1.
-            assert_eq!(erc721.mint(1), Ok(()));
+            assert_eq!(erc721.mint(id(1)), Ok(()));
             // Create token Id 2.
-            assert_eq!(erc721.mint(2), Ok(()));
+            assert_eq!(erc721.mint(id(2)), Ok(()));
             // Alice owns 2 tokens.
             assert_eq!(erc721.balance_of(accounts.alice), 2);
-            // Approve token Id 1 transfer for Bob on behalf of Alice.
-            assert_eq!(erc721.set_approval_for_all(accounts.bob, true), Ok(()));
+            // Alice cannot approve herself.
+            assert_eq!(
+                erc721.approve(accounts.alice, None, true),
+                Err(PSP34Error::SelfApprove)
+            );
+            // Approve all tokens of Alice for Bob.
+            assert_eq!(erc721.approve(accounts.bob, None, true), Ok(()));
             // Bob is an approved operator for Alice
-            assert!(erc721.is_approved_for_all(accounts.alice, accounts.bob));
+            assert!(erc721.allowance(accounts.alice, accounts.bob, None));
+            assert!(erc721.allowance(accounts.alice, accounts.bob, Some(id(2))));
             // Set Bob as caller
             set_caller(accounts.bob);
             // Bob transfers token Id 1 from Alice to Eve.
-            assert_eq!(
-                erc721.transfer_from(accounts.alice, accounts.eve, 1),
-                Ok(())
-            );
+            assert_eq!(erc721.transfer(accounts.eve, id(1), Vec::new()), Ok(()));
             // TokenId 1 is owned by Eve.
-            assert_eq!(erc721.owner_of(1), Some(accounts.eve));
+            assert_eq!(erc721.owner_of(id(1)), Some(accounts.eve));
             // Alice owns 1 token.
             assert_eq!(erc721.balance_of(accounts.alice), 1);
             // Bob transfers token Id 2 from Alice to Eve.
-            assert_eq!(
-                erc721.transfer_from(accounts.alice, accounts.eve, 2),
-                Ok(())
-            );
+            assert_eq!(erc721.transfer(accounts.eve, id(2), Vec::new()), Ok(()));
             // Bob does not own tokens.
             assert_eq!(erc721.balance_of(accounts.bob), 0);
             // Eve owns 2 tokens.
             assert_eq!(erc721.balance_of(accounts.eve), 2);
             // Remove operator approval for Bob on behalf of Alice.
             set_caller(accounts.alice);
-            assert_eq!(erc721.set_approval_for_all(accounts.bob, false), Ok(()));
+            assert_eq!(erc721.approve(accounts.bob, None, false), Ok(()));
             // Bob is not an approved operator for Alice.
-            assert!(!erc721.is_approved_for_all(accounts.alice, accounts.bob));
+            assert!(!erc721.allowance(accounts.alice, accounts.bob, None));
         }
 
         #[ink::test]
@@ -576,7 +913,7 @@ mod erc721 {
             // Create a new contract instance.
             let mut erc721 = Erc721::new();
             // Create token Id 1.
-            assert_eq!(erc721.mint(1), Ok(()));
+            assert_eq!(erc721.mint(id(1)), Ok(()));
             // Alice owns 1 token.
             assert_eq!(erc721.balance_of(accounts.alice), 1);
             // Bob does not owns tokens.
@@ -587,8 +924,13 @@ mod erc721 {
             set_caller(accounts.eve);
             // Eve is not an approved operator by Alice.
             assert_eq!(
-                erc721.transfer_from(accounts.alice, accounts.frank, 1),
-                Err(Error::NotApproved)
+                erc721.transfer(accounts.frank, id(1), Vec::new()),
+                Err(PSP34Error::NotApproved)
+            );
+            // Nor can she approve herself or others.
+            assert_eq!(
+                erc721.approve(accounts.bob, Some(id(1)), true),
+                Err(PSP34Error::NotApproved)
             );
             // Alice owns 1 token.
             assert_eq!(erc721.balance_of(accounts.alice), 1);
@@ -605,25 +947,30 @@ mod erc721 {
             // Create a new contract instance.
             let mut erc721 = Erc721::new();
             // Create token Id 1 for Alice
-            assert_eq!(erc721.mint(1), Ok(()));
+            assert_eq!(erc721.mint(id(1)), Ok(()));
             // Alice owns 1 token.
             assert_eq!(erc721.balance_of(accounts.alice), 1);
             // Alice owns token Id 1.
-            assert_eq!(erc721.owner_of(1), Some(accounts.alice));
+            assert_eq!(erc721.owner_of(id(1)), Some(accounts.alice));
             // Destroy token Id 1.
-            assert_eq!(erc721.burn(1), Ok(()));
+            assert_eq!(erc721.burn(accounts.alice, id(1)), Ok(()));
             // Alice does not owns tokens.
             assert_eq!(erc721.balance_of(accounts.alice), 0);
             // Token Id 1 does not exists
-            assert_eq!(erc721.owner_of(1), None);
+            assert_eq!(erc721.owner_of(id(1)), None);
         }
 
         #[ink::test]
         fn burn_fails_token_not_found() {
+            let accounts =
+                ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
             // Create a new contract instance.
             let mut erc721 = Erc721::new();
             // Try burning a non existent token
-            assert_eq!(erc721.burn(1), Err(Error::TokenNotFound));
+            assert_eq!(
+                erc721.burn(accounts.alice, id(1)),
+                Err(PSP34Error::TokenNotExists)
+            );
         }
 
         #[ink::test]
@@ -633,14 +980,246 @@ mod erc721 {
             // Create a new contract instance.
             let mut erc721 = Erc721::new();
             // Create token Id 1 for Alice
-            assert_eq!(erc721.mint(1), Ok(()));
+            assert_eq!(erc721.mint(id(1)), Ok(()));
+            // The token is not Eve's.
+            assert_eq!(erc721.burn(accounts.eve, id(1)), Err(PSP34Error::NotApproved));
             // Try burning this token with a different account
             set_caller(accounts.eve);
-            assert_eq!(erc721.burn(1), Err(Error::NotOwner));
+            assert_eq!(erc721.burn(accounts.alice, id(1)), Err(PSP34Error::NotApproved));
+        }
+
+        #[ink::test]
+        fn royalty_is_a_share_of_the_sale_price() {
+            let accounts =
+                ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut erc721 = Erc721::new();
+            assert_eq!(erc721.mint(id(1)), Ok(()));
+            // Token 1 pays no royalties.
+            assert_eq!(erc721.royalty_info(id(1), 1000), None);
+            // Royalties cannot exceed the sale price.
+            assert_eq!(
+                erc721.mint_with_royalty(id(2), accounts.bob, 10_001),
+                Err(custom("InvalidRoyalty"))
+            );
+            assert_eq!(erc721.owner_of(id(2)), None);
+            // Token 2 pays 2.5% to Bob.
+            assert_eq!(erc721.mint_with_royalty(id(2), accounts.bob, 250), Ok(()));
+            assert_eq!(erc721.owner_of(id(2)), Some(accounts.alice));
+            assert_eq!(erc721.royalty_info(id(2), 1000), Some((accounts.bob, 25)));
+            assert_eq!(erc721.royalty_info(id(2), 39), Some((accounts.bob, 0)));
+            assert_eq!(erc721.burn(accounts.alice, id(2)), Ok(()));
+            assert_eq!(erc721.royalty_info(id(2), 1000), None);
+        }
+
+        #[ink::test]
+        fn metadata_works() {
+            let accounts =
+                ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let erc721 = Erc721::new();
+            assert_eq!(erc721.get_attribute(erc721.collection_id(), b"name".to_vec()), None);
+            let mut erc721 =
+                Erc721::new_with_metadata(String::from("Timelock"), String::from("TLK"));
+            let collection = erc721.collection_id();
+            assert_eq!(
+                erc721.get_attribute(collection.clone(), b"name".to_vec()),
+                Some(b"Timelock".to_vec())
+            );
+            assert_eq!(
+                erc721.get_attribute(collection.clone(), b"symbol".to_vec()),
+                Some(b"TLK".to_vec())
+            );
+            let (key, uri) = (b"uri".to_vec(), b"ipfs://token/1".to_vec());
+            assert_eq!(
+                erc721.set_attribute(id(1), key.clone(), uri.clone()),
+                Err(PSP34Error::TokenNotExists)
+            );
+            assert_eq!(erc721.mint(id(1)), Ok(()));
+            assert_eq!(erc721.set_attribute(id(1), key.clone(), uri.clone()), Ok(()));
+            assert_eq!(erc721.get_attribute(id(1), key.clone()), Some(uri.clone()));
+            // Only the owner can set the attributes of a token, and admins those of
+            // the collection.
+            set_caller(accounts.bob);
+            assert_eq!(
+                erc721.set_attribute(id(1), key.clone(), Vec::new()),
+                Err(PSP34Error::NotApproved)
+            );
+            assert_eq!(
+                erc721.set_attribute(collection, b"name".to_vec(), Vec::new()),
+                Err(custom("MissingRole"))
+            );
+            assert_eq!(erc721.get_attribute(id(1), key.clone()), Some(uri));
+            // Burning the token removes its attributes.
+            set_caller(accounts.alice);
+            assert_eq!(erc721.burn(accounts.alice, id(1)), Ok(()));
+            assert_eq!(erc721.get_attribute(id(1), key), None);
+        }
+
+        #[ink::test]
+        fn enumeration_follows_transfers() {
+            let accounts =
+                ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut erc721 = Erc721::new();
+            for n in 1..=4 {
+                assert_eq!(erc721.mint(id(n)), Ok(()));
+            }
+            assert_eq!(erc721.total_supply(), 4);
+            assert_eq!(erc721.tokens_of_owner(accounts.alice, 0, 10), ids(&[1, 2, 3, 4]));
+            // Alice's last token takes the place of the token she sends.
+            assert_eq!(erc721.transfer(accounts.bob, id(2), Vec::new()), Ok(()));
+            assert_eq!(erc721.tokens_of_owner(accounts.alice, 0, 10), ids(&[1, 4, 3]));
+            assert_eq!(erc721.tokens_of_owner(accounts.bob, 0, 10), ids(&[2]));
+            assert_eq!(erc721.owners_token_by_index(accounts.bob, 0), Ok(id(2)));
+            assert_eq!(
+                erc721.owners_token_by_index(accounts.bob, 1),
+                Err(PSP34Error::TokenNotExists)
+            );
+            // Transfers keep every token listed, burning removes it.
+            assert_eq!(erc721.total_supply(), 4);
+            assert_eq!(erc721.burn(accounts.alice, id(1)), Ok(()));
+            assert_eq!(erc721.total_supply(), 3);
+            let all = (0..4).map(|i| erc721.token_by_index(i).ok()).collect::<Vec<_>>();
+            assert_eq!(all, vec![Some(id(4)), Some(id(2)), Some(id(3)), None]);
+            assert_eq!(erc721.tokens_of_owner(accounts.alice, 0, 10), ids(&[3, 4]));
+            // A burned token can be minted again.
+            assert_eq!(erc721.mint(id(1)), Ok(()));
+            assert_eq!(erc721.token_by_index(3), Ok(id(1)));
+            assert_eq!(erc721.tokens_of_owner(accounts.alice, 0, 10), ids(&[3, 4, 1]));
+            assert_eq!(erc721.token_by_index(u128::MAX), Err(PSP34Error::TokenNotExists));
+        }
+
+        #[ink::test]
+        fn tokens_of_owner_is_paginated() {
+            let accounts =
+                ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut erc721 = Erc721::new();
+            for n in 0..(MAX_PAGE_SIZE + 10) {
+                assert_eq!(erc721.mint(id(n)), Ok(()));
+            }
+            let alice = accounts.alice;
+            assert_eq!(erc721.tokens_of_owner(alice, 0, u32::MAX).len(), 100);
+            assert_eq!(
+                erc721.tokens_of_owner(alice, 105, 10),
+                ids(&[105, 106, 107, 108, 109])
+            );
+            assert!(erc721.tokens_of_owner(alice, u32::MAX, u32::MAX).is_empty());
+            assert!(erc721.tokens_of_owner(accounts.bob, 0, 10).is_empty());
+        }
+
+        #[ink::test]
+        fn only_minters_mint() {
+            let accounts =
+                ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut erc721 = Erc721::new();
+            assert!(erc721.has_role(ADMIN, accounts.alice));
+            assert!(erc721.has_role(MINTER, accounts.alice));
+            set_caller(accounts.bob);
+            assert_eq!(erc721.mint(id(1)), Err(custom("MissingRole")));
+            assert_eq!(erc721.mint_next(), Err(custom("MissingRole")));
+            // Only admins grant roles.
+            assert_eq!(
+                erc721.grant_role(MINTER, accounts.bob),
+                Err(custom("MissingRole"))
+            );
+            set_caller(accounts.alice);
+            assert_eq!(erc721.grant_role(MINTER, accounts.bob), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(erc721.mint(id(1)), Ok(()));
+            set_caller(accounts.alice);
+            assert_eq!(erc721.revoke_role(MINTER, accounts.bob), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(erc721.mint(id(2)), Err(custom("MissingRole")));
+            assert_eq!(erc721.total_supply(), 1);
+        }
+
+        #[ink::test]
+        fn mint_next_skips_used_ids() {
+            let accounts =
+                ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            setup_ext_pulse([7; 32]);
+            let mut erc721 = Erc721::new();
+            let nonce = ink::env::account_id::<DrandEnvironment>();
+            let next = |counter| Id::U128(unique_id(&[7; 32], nonce.as_ref(), counter));
+            // The id derived with counter 1 is already taken.
+            assert_eq!(erc721.mint(next(1)), Ok(()));
+            assert_eq!(erc721.mint_next(), Ok(next(0)));
+            assert_eq!(erc721.mint_next(), Ok(next(2)));
+            assert_eq!(erc721.mint(next(2)), Err(PSP34Error::TokenExists));
+            // Burned ids are not minted again.
+            assert_eq!(erc721.burn(accounts.alice, next(0)), Ok(()));
+            assert_eq!(erc721.mint_next(), Ok(next(3)));
+            assert_eq!(
+                erc721.tokens_of_owner(accounts.alice, 0, 10),
+                vec![next(1), next(2), next(3)]
+            );
+        }
+
+        #[ink::test]
+        fn mint_next_ids_depend_on_the_pulse() {
+            let mut erc721 = Erc721::new();
+            setup_ext_pulse([1; 32]);
+            let first = erc721.mint_next();
+            setup_ext_pulse([2; 32]);
+            let second = erc721.mint_next();
+            match (first, second) {
+                (Ok(Id::U128(first)), Ok(Id::U128(second))) => {
+                    // The counter keeps the ids apart, the pulse makes them unpredictable.
+                    assert_eq!((first as u64, second as u64), (0, 1));
+                    assert_ne!(first >> 64, second >> 64);
+                }
+                ids => panic!("unexpected ids {ids:?}"),
+            }
+        }
+
+        #[ink::test]
+        fn safe_transfer_to_account_works() {
+            let accounts =
+                ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut erc721 = Erc721::new();
+            assert_eq!(erc721.mint(id(1)), Ok(()));
+            assert_eq!(
+                erc721.safe_transfer_from(accounts.alice, accounts.bob, id(1), Vec::new()),
+                Ok(())
+            );
+            assert_eq!(erc721.owner_of(id(1)), Some(accounts.bob));
+            // Like `transfer`, only approved accounts can transfer.
+            assert_eq!(
+                erc721.safe_transfer_from(accounts.bob, accounts.alice, id(1), Vec::new()),
+                Err(PSP34Error::NotApproved)
+            );
+        }
+
+        fn id(n: u32) -> Id {
+            Id::U32(n)
+        }
+
+        fn ids(ns: &[u32]) -> Vec<Id> {
+            ns.iter().copied().map(id).collect()
         }
 
         fn set_caller(sender: AccountId) {
             ink::env::test::set_caller::<ink::env::DefaultEnvironment>(sender);
         }
+
+        /// register a drand chain extension returning `pulse`
+        fn setup_ext_pulse(pulse: [u8; 32]) {
+            struct MockDrandExtension([u8; 32]);
+            impl ink::env::test::ChainExtension for MockDrandExtension {
+                fn ext_id(&self) -> u16 {
+                    12
+                }
+
+                fn call(
+                    &mut self,
+                    _func_id: u16,
+                    _input: &[u8],
+                    output: &mut Vec<u8>,
+                ) -> u32 {
+                    scale::Encode::encode_to(&self.0, output);
+                    0
+                }
+            }
+
+            ink::env::test::register_chain_extension(MockDrandExtension(pulse));
+        }
     }
 }
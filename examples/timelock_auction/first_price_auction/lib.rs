@@ -0,0 +1,226 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+pub use self::first_price_auction::{FirstPriceAuction, FirstPriceAuctionRef};
+
+use idl_contract_extension::ext::DrandEnvironment;
+
+#[ink::contract(env = DrandEnvironment)]
+mod first_price_auction {
+    use crate::DrandEnvironment;
+    use idl_contract_extension::access::Ownable;
+    use auction_traits::{
+        sealed::{self, SealedBids},
+        Auction, AuctionKind, AuctionOutcome, AuctionResult, AuctionStatus, BidPayload,
+        RevealedBid,
+    };
+    use ink::prelude::{vec, vec::Vec};
+
+    pub use auction_traits::AuctionError as Error;
+
+    /// A sealed-bid first-price auction: the highest bidder pays their bid
+    #[ink(storage)]
+    pub struct FirstPriceAuction {
+        /// the proxy (contract)
+        proxy: Ownable,
+        /// the minimum price the seller will accept
+        reserve_price: Balance,
+        /// the sealed and revealed bids
+        bids: SealedBids,
+        /// the stage of the auction's lifecycle
+        status: AuctionStatus,
+        /// the outcome of the auction, once complete
+        outcome: Option<AuctionOutcome<AccountId, Balance>>,
+        /// participants whose bids were not revealed when the auction completed
+        invalid_bids: Vec<AccountId>,
+    }
+
+    impl FirstPriceAuction {
+        /// Constructor that initializes a new auction
+        ///
+        /// * `proxy`: the proxy (contract) which relays bids
+        /// * `reserve_price`: the minimum price the seller will accept
+        ///
+        #[ink(constructor)]
+        pub fn new(proxy: AccountId, reserve_price: Balance) -> Self {
+            Self {
+                proxy: Ownable::new(proxy),
+                reserve_price,
+                bids: SealedBids::default(),
+                status: AuctionStatus::Open,
+                outcome: None,
+                invalid_bids: Vec::new(),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_reserve_price(&self) -> Balance {
+            self.reserve_price
+        }
+
+        /// get the revealed bids (empty until post-auction completion)
+        #[ink(message)]
+        pub fn get_revealed_bids(&self) -> Vec<RevealedBid<AccountId>> {
+            self.bids.revealed_bids().to_vec()
+        }
+
+        fn ensure_proxy(&self) -> Result<(), Error> {
+            self.proxy
+                .ensure_owner(self.env().caller())
+                .map_err(Error::from)
+        }
+    }
+
+    impl Auction for FirstPriceAuction {
+        #[ink(message)]
+        fn get_kind(&self) -> AuctionKind {
+            AuctionKind::FirstPrice
+        }
+
+        #[ink(message)]
+        fn get_status(&self) -> AuctionStatus {
+            self.status
+        }
+
+        /// seal a bid, committed to with `bid_commitment`
+        /// bidding again replaces the sealed bid
+        #[ink(message)]
+        fn bid(&mut self, bidder: AccountId, bid: BidPayload) -> Result<(), Error> {
+            self.ensure_proxy()?;
+            if self.status != AuctionStatus::Open {
+                return Err(Error::AuctionClosed);
+            }
+            let BidPayload::Sealed(sealed_bid) = bid else {
+                return Err(Error::UnsupportedBid);
+            };
+            self.bids.seal(bidder, sealed_bid)
+        }
+
+        #[ink(message)]
+        fn reveal_bid(&mut self, revealed_bid: RevealedBid<AccountId>) -> Result<(), Error> {
+            self.ensure_proxy()?;
+            if !matches!(self.status, AuctionStatus::Open | AuctionStatus::RevealPhase) {
+                return Err(Error::RevealPhaseClosed);
+            }
+            self.bids.reveal(revealed_bid)?;
+            self.status = AuctionStatus::RevealPhase;
+            Ok(())
+        }
+
+        /// Complete the auction
+        ///
+        /// The highest bid at or above the reserve price wins and pays their bid.
+        /// Ties are broken with `pulse`. Bids which were never
+        /// revealed are marked invalid.
+        ///
+        /// * `pulse`: the drand pulse which breaks ties
+        ///
+        #[ink(message)]
+        fn complete(&mut self, pulse: [u8; 32]) -> Result<(), Error> {
+            self.ensure_proxy()?;
+            if !self.status.can_become(AuctionStatus::Settled) {
+                return Err(Error::AlreadyComplete);
+            }
+            self.status = AuctionStatus::Settled;
+            self.invalid_bids = self.bids.unrevealed();
+            let ranked = sealed::rank(self.bids.revealed_bids(), self.reserve_price, pulse);
+            self.outcome = Some(match ranked.first() {
+                Some(highest) => AuctionOutcome::Sale(vec![AuctionResult {
+                    winner: highest.bidder,
+                    debt: highest.bid,
+                }]),
+                None => AuctionOutcome::NoSale,
+            });
+            Ok(())
+        }
+
+        /// withdraw the auction, while it is open
+        #[ink(message)]
+        fn cancel(&mut self) -> Result<(), Error> {
+            self.ensure_proxy()?;
+            if !self.status.can_become(AuctionStatus::Cancelled) {
+                return Err(Error::AuctionClosed);
+            }
+            self.status = AuctionStatus::Cancelled;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn get_outcome(&self) -> Option<AuctionOutcome<AccountId, Balance>> {
+            self.outcome.clone()
+        }
+
+        #[ink(message)]
+        fn get_invalid_bids(&self) -> Vec<AccountId> {
+            self.invalid_bids.clone()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use auction_traits::{bid_commitment, TlockMessage};
+
+        #[ink::test]
+        fn highest_bidder_pays_their_bid() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = FirstPriceAuction::new(accounts.alice, 2);
+            let bids = [
+                seal(accounts.bob, 3),
+                seal(accounts.charlie, 7),
+                seal(accounts.django, 1),
+            ];
+            bids.iter().for_each(|(sealed_bid, revealed_bid)| {
+                assert_eq!(auction.bid(revealed_bid.bidder, sealed_bid.clone()), Ok(()));
+            });
+            bids.iter().for_each(|(_, revealed_bid)| {
+                assert_eq!(auction.reveal_bid(revealed_bid.clone()), Ok(()));
+            });
+            assert_eq!(auction.complete([0; 32]), Ok(()));
+            assert_eq!(
+                auction.get_outcome(),
+                Some(AuctionOutcome::Sale(vec![AuctionResult {
+                    winner: accounts.charlie,
+                    debt: 7
+                }]))
+            );
+        }
+
+        #[ink::test]
+        fn unrevealed_bids_are_invalid() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = FirstPriceAuction::new(accounts.alice, 0);
+            let (sealed_bid, _) = seal(accounts.bob, 3);
+            let _ = auction.bid(accounts.bob, sealed_bid);
+            assert_eq!(auction.complete([0; 32]), Ok(()));
+            assert_eq!(auction.get_invalid_bids(), vec![accounts.bob]);
+            assert_eq!(auction.get_outcome(), Some(AuctionOutcome::NoSale));
+        }
+
+        #[ink::test]
+        fn open_bids_are_unsupported() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = FirstPriceAuction::new(accounts.alice, 0);
+            assert_eq!(
+                auction.bid(accounts.bob, BidPayload::Open(3)),
+                Err(Error::UnsupportedBid)
+            );
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                auction.bid(accounts.bob, seal(accounts.bob, 3).0),
+                Err(Error::NotProxy)
+            );
+        }
+
+        /// seal a bid with a dummy ciphertext and a real commitment
+        fn seal(bidder: AccountId, bid: u128) -> (BidPayload, RevealedBid<AccountId>) {
+            let salt = [bid as u8; 32];
+            let mut sealed_bid = TlockMessage {
+                ciphertext: vec![1; 32],
+                nonce: vec![2; 12],
+                capsule: vec![3; 32],
+                commitment: Vec::new(),
+            };
+            sealed_bid.commitment = bid_commitment(&bidder, bid, &salt, &sealed_bid).to_vec();
+            (BidPayload::Sealed(sealed_bid), RevealedBid { bidder, bid, salt })
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use ink::{primitives::AccountId, storage::Mapping};
+
+/// the balance type of the `DrandEnvironment`
+pub type Balance = u128;
+
+/// Deposits held by the proxy on behalf of bidders, per auction
+///
+/// The total held for an auction is always the sum of the deposits
+/// of its bidders, so releasing every deposit drains the auction.
+#[ink::storage_item]
+#[derive(Debug, Default)]
+pub struct Escrow {
+    /// the deposit held for each (auction, bidder)
+    deposits: Mapping<(AccountId, AccountId), Balance>,
+    /// the total deposit held for each auction
+    totals: Mapping<AccountId, Balance>,
+    /// the number of bidders holding a deposit (possibly zero) in each auction
+    bidders: Mapping<AccountId, u32>,
+}
+
+impl Escrow {
+    /// hold `amount` for the bidder, on top of anything already held
+    /// returns the bidder's new deposit
+    pub fn deposit(&mut self, auction_id: AccountId, bidder: AccountId, amount: Balance) -> Balance {
+        if !self.holds(auction_id, bidder) {
+            self.bidders.insert(auction_id, &self.bidders(auction_id).saturating_add(1));
+        }
+        let deposit = self.deposit_of(auction_id, bidder).saturating_add(amount);
+        let total = self.total(auction_id).saturating_add(amount);
+        self.deposits.insert((auction_id, bidder), &deposit);
+        self.totals.insert(auction_id, &total);
+        deposit
+    }
+
+    /// the deposit held for the bidder
+    pub fn deposit_of(&self, auction_id: AccountId, bidder: AccountId) -> Balance {
+        self.deposits.get((auction_id, bidder)).unwrap_or(0)
+    }
+
+    /// whether a deposit is held for the bidder, even an empty one
+    pub fn holds(&self, auction_id: AccountId, bidder: AccountId) -> bool {
+        self.deposits.contains((auction_id, bidder))
+    }
+
+    /// the number of bidders a deposit is held for
+    pub fn bidders(&self, auction_id: AccountId) -> u32 {
+        self.bidders.get(auction_id).unwrap_or(0)
+    }
+
+    /// the total deposit held for the auction
+    pub fn total(&self, auction_id: AccountId) -> Balance {
+        self.totals.get(auction_id).unwrap_or(0)
+    }
+
+    /// stop holding the bidder's deposit
+    /// returns the amount released, which the caller must pay out
+    pub fn release(&mut self, auction_id: AccountId, bidder: AccountId) -> Balance {
+        let Some(deposit) = self.deposits.take((auction_id, bidder)) else {
+            return 0;
+        };
+        let bidders = self.bidders(auction_id).saturating_sub(1);
+        if bidders == 0 {
+            self.bidders.remove(auction_id);
+        } else {
+            self.bidders.insert(auction_id, &bidders);
+        }
+        let total = self.total(auction_id).saturating_sub(deposit);
+        if total == 0 {
+            self.totals.remove(auction_id);
+        } else {
+            self.totals.insert(auction_id, &total);
+        }
+        deposit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[ink::test]
+    fn deposits_accumulate() {
+        let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+        let mut escrow = Escrow::default();
+        assert_eq!(escrow.deposit(accounts.django, accounts.alice, 10), 10);
+        assert_eq!(escrow.deposit(accounts.django, accounts.alice, 5), 15);
+        assert_eq!(escrow.deposit(accounts.django, accounts.bob, 7), 7);
+        assert_eq!(escrow.deposit_of(accounts.django, accounts.alice), 15);
+        assert_eq!(escrow.deposit_of(accounts.django, accounts.bob), 7);
+        assert_eq!(escrow.total(accounts.django), 22);
+    }
+
+    #[ink::test]
+    fn deposits_are_held_per_auction() {
+        let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+        let mut escrow = Escrow::default();
+        escrow.deposit(accounts.django, accounts.alice, 10);
+        escrow.deposit(accounts.eve, accounts.alice, 3);
+        assert_eq!(escrow.deposit_of(accounts.django, accounts.alice), 10);
+        assert_eq!(escrow.deposit_of(accounts.eve, accounts.alice), 3);
+        assert_eq!(escrow.release(accounts.eve, accounts.alice), 3);
+        assert_eq!(escrow.total(accounts.django), 10);
+        assert_eq!(escrow.total(accounts.eve), 0);
+    }
+
+    #[ink::test]
+    fn release_pays_out_each_deposit_once() {
+        let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+        let mut escrow = Escrow::default();
+        escrow.deposit(accounts.django, accounts.alice, 10);
+        escrow.deposit(accounts.django, accounts.bob, 7);
+
+        assert_eq!(escrow.release(accounts.django, accounts.alice), 10);
+        assert_eq!(escrow.release(accounts.django, accounts.alice), 0);
+        assert_eq!(escrow.deposit_of(accounts.django, accounts.alice), 0);
+        assert_eq!(escrow.total(accounts.django), 7);
+
+        assert_eq!(escrow.release(accounts.django, accounts.bob), 7);
+        assert_eq!(escrow.total(accounts.django), 0);
+    }
+
+    #[ink::test]
+    fn bidders_are_counted_once() {
+        let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+        let mut escrow = Escrow::default();
+        escrow.deposit(accounts.django, accounts.alice, 10);
+        escrow.deposit(accounts.django, accounts.alice, 5);
+        // an empty deposit still counts
+        escrow.deposit(accounts.django, accounts.bob, 0);
+        assert!(escrow.holds(accounts.django, accounts.bob));
+        assert_eq!(escrow.bidders(accounts.django), 2);
+
+        escrow.release(accounts.django, accounts.bob);
+        escrow.release(accounts.django, accounts.bob);
+        assert!(!escrow.holds(accounts.django, accounts.bob));
+        assert_eq!(escrow.bidders(accounts.django), 1);
+        escrow.release(accounts.django, accounts.alice);
+        assert_eq!(escrow.bidders(accounts.django), 0);
+    }
+
+    #[ink::test]
+    fn total_is_the_sum_of_deposits() {
+        let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+        let mut escrow = Escrow::default();
+        let bidders = [accounts.alice, accounts.bob, accounts.charlie, accounts.eve];
+        for (i, bidder) in bidders.iter().enumerate() {
+            escrow.deposit(accounts.django, *bidder, i as Balance + 1);
+            let sum: Balance = bidders
+                .iter()
+                .map(|b| escrow.deposit_of(accounts.django, *b))
+                .sum();
+            assert_eq!(escrow.total(accounts.django), sum);
+        }
+        let mut released = 0;
+        for bidder in bidders.iter() {
+            released += escrow.release(accounts.django, *bidder);
+        }
+        assert_eq!(released, 10);
+        assert_eq!(escrow.total(accounts.django), 0);
+    }
+}
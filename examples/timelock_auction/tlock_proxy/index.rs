@@ -0,0 +1,48 @@
+use auction_traits::AuctionStatus;
+use idl_contract_extension::{index::Index, psp34::Id};
+use ink::primitives::AccountId;
+
+pub use idl_contract_extension::index::MAX_PAGE_SIZE;
+
+/// The lists auctions are indexed under
+#[derive(Clone, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+// the scale derive casts the index of variants with fields to u8
+#[allow(clippy::cast_possible_truncation)]
+pub enum IndexKey {
+    /// every auction, in the order they were created
+    All,
+    /// the auctions created by an account
+    Owner(AccountId),
+    /// the auctions an account has bid in
+    Bidder(AccountId),
+    /// the auctions selling a token of a PSP34 collection
+    Asset(AccountId, Id),
+    /// the auctions with a status
+    Status(AuctionStatus),
+}
+
+/// Lists of auction ids, one per `IndexKey`
+pub type AuctionIndex = Index<IndexKey>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[ink::test]
+    fn lists_are_independent() {
+        let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+        let mut index = AuctionIndex::default();
+        index.insert(IndexKey::Owner(accounts.django), accounts.alice);
+        index.insert(IndexKey::Owner(accounts.eve), accounts.bob);
+        index.insert(IndexKey::Status(AuctionStatus::Open), accounts.alice);
+        assert_eq!(index.page(IndexKey::Owner(accounts.django), 0, 10), vec![accounts.alice]);
+        assert_eq!(index.page(IndexKey::Owner(accounts.eve), 0, 10), vec![accounts.bob]);
+        assert!(index.contains(IndexKey::Status(AuctionStatus::Open), accounts.alice));
+        assert!(!index.contains(IndexKey::Status(AuctionStatus::Settled), accounts.alice));
+        assert_eq!(index.last(IndexKey::Bidder(accounts.alice)), None);
+    }
+}
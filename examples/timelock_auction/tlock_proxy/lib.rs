@@ -1,23 +1,58 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 pub use self::tlock_proxy::{
+    AuctionCancelled,
+    AuctionCreated,
+    AuctionDetails,
+    AuctionSettled,
+    AuctionTerms,
+    BidPlaced,
+    BidRevealed,
+    Claimed,
+    DeadlineExtended,
+    DeadlineExtension,
+    Error,
+    Forfeited,
+    Refunded,
+    Slashed,
     TlockProxy,
     TlockProxyRef,
+    CLAIM_PERIOD,
 };
+pub use index::{IndexKey, MAX_PAGE_SIZE};
 
-use etf_contract_utils::ext::EtfEnvironment;
+use idl_contract_extension::ext::DrandEnvironment;
 
-#[ink::contract(env = EtfEnvironment)]
+/// per-bidder deposit accounting
+mod escrow;
+/// paginated lists of auctions
+mod index;
+
+#[ink::contract(env = DrandEnvironment)]
 mod tlock_proxy {
-    use crate::EtfEnvironment;
+    use crate::{
+        escrow::Escrow,
+        index::{AuctionIndex, IndexKey},
+        DrandEnvironment,
+    };
+    use auction_traits::{
+        Auction, AuctionConfig, AuctionError, AuctionKind, AuctionOutcome, AuctionResult,
+        AuctionStatus, BidPayload, RevealedBid,
+    };
+    use dutch_auction::DutchAuctionRef;
+    use english_auction::EnglishAuctionRef;
     use erc721::Erc721Ref;
-    use ink::prelude::vec::Vec;
-    use ink::ToAccountId;
-    use vickrey_auction::{RevealedBid, VickreyAuctionRef};
-
-    use sha3::{
-        digest::{ExtendableOutput, Update, XofReader},
-        Shake128,
+    use first_price_auction::FirstPriceAuctionRef;
+    use ink::prelude::{vec, vec::Vec};
+    use idl_contract_extension::{
+        access::{AccessError, Ownable},
+        psp34::{Id, PSP34Royalty, PSP34TransferFrom, PSP34},
+        types::{share_of, unique_id, BasisPoints, MAX_BASIS_POINTS},
     };
+    use ink::codegen::TraitCallBuilder;
+    use ink::storage::{Lazy, Mapping};
+    use ink::ToAccountId;
+    use uniform_price_auction::UniformPriceAuctionRef;
+    use vickrey_auction::VickreyAuctionRef;
 
     /// A custom type for storing auction's details
     #[derive(Clone, Debug, scale::Decode, scale::Encode, PartialEq)]
@@ -28,24 +63,65 @@ mod tlock_proxy {
     pub struct AuctionDetails {
         name: Vec<u8>,
         auction_id: AccountId,
-        asset_id: u32,
+        kind: AuctionKind,
+        /// the PSP34 collection of the assets on sale
+        collection: AccountId,
+        /// the assets on sale, the i-th is won by the i-th result of the auction
+        asset_ids: Vec<Id>,
         owner: AccountId,
         deposit: Balance,
+        reserve_price: Balance,
+        /// the block from which bids are no longer accepted and sealed bids can be revealed
         deadline: BlockNumber,
+        /// the block from which bids can no longer be revealed and the auction can be completed
+        reveal_deadline: BlockNumber,
         published: Timestamp,
-        status: u8,
-        bids: u8,
+        status: AuctionStatus,
+        bids: u32,
+        /// the number of prizes won but not yet claimed
+        unclaimed: u32,
+        /// how late bids extend the deadline, if at all
+        extension: Option<DeadlineExtension>,
+        /// the number of blocks the deadline has been extended by
+        extended_by: BlockNumber,
+        /// the protocol fee when the auction was created, taken from every sale
+        protocol_fee: BasisPoints,
     }
 
-    /// A custom type for representing the relationship between a bidder and an auction
+    /// The deadlines and prices of a new auction
     #[derive(Clone, Debug, scale::Decode, scale::Encode, PartialEq)]
     #[cfg_attr(
         feature = "std",
         derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
     )]
-    pub struct Bid {
-        auction_id: AccountId,
-        bidder: AccountId,
+    pub struct AuctionTerms {
+        /// the block from which bids are no longer accepted
+        pub deadline: BlockNumber,
+        /// the block from which bids can no longer be revealed, at or after the deadline
+        pub reveal_deadline: BlockNumber,
+        /// the deposit each bidder must place
+        pub deposit: Balance,
+        /// the minimum price the seller will accept
+        pub reserve_price: Balance,
+        /// extend the deadline when bids arrive late (only for auctions with public bids)
+        pub extension: Option<DeadlineExtension>,
+    }
+
+    /// Soft-close: a bid placed in the last `window` blocks before the deadline
+    /// pushes the deadline (and the reveal deadline) out by `extension` blocks,
+    /// by at most `max_extension` blocks in total
+    #[derive(Clone, Copy, Debug, scale::Decode, scale::Encode, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct DeadlineExtension {
+        /// the number of blocks before the deadline in which bids extend it
+        pub window: BlockNumber,
+        /// the number of blocks each late bid extends the deadline by
+        pub extension: BlockNumber,
+        /// the maximum number of blocks the deadline can be extended by
+        pub max_extension: BlockNumber,
     }
 
     #[derive(Clone, PartialEq, Debug, scale::Decode, scale::Encode)]
@@ -56,7 +132,7 @@ mod tlock_proxy {
     pub enum Error {
         /// the erc721 token could not be minted
         NFTMintFailed,
-        /// the erc721 token could not be transferred
+        /// the PSP34 token could not be transferred
         NftTransferFailed,
         /// the balance transfer failed
         BalanceTransferFailed,
@@ -68,6 +144,18 @@ mod tlock_proxy {
         AuctionAlreadyComplete,
         /// the auction deadline has not been reached
         AuctionInProgress,
+        /// the auction deadline has passed, so bids are no longer accepted
+        BiddingClosed,
+        /// the reveal deadline has not been reached
+        RevealPhaseInProgress,
+        /// the reveal deadline has passed or the auction is complete
+        RevealPhaseClosed,
+        /// the auction has been cancelled
+        AuctionCancelled,
+        /// the reveal deadline is before the auction deadline
+        InvalidDeadline,
+        /// the bidder has already revealed their bid
+        AlreadyRevealed,
         /// the auction requires a minimum deposit
         DepositTooLow,
         /// the current amount transferred was incorrect
@@ -78,6 +166,36 @@ mod tlock_proxy {
         AuctionDoesNotExist,
         /// the auction winner has not been determined
         NoWinnerDetermined,
+        /// the auction has not been completed
+        AuctionNotComplete,
+        /// the caller has no prize or deposit to claim
+        NothingToClaim,
+        /// this function is callable only by the proxy owner
+        NotOwner,
+        /// no auction contract is registered for the kind of auction
+        UnknownAuctionKind,
+        /// the auction config is invalid (e.g. no units on sale)
+        InvalidAuctionConfig,
+        /// the code of the proxy could not be replaced (e.g. the code hash was not uploaded)
+        UpgradeFailed,
+        /// the protocol fee is more than the whole price
+        InvalidFee,
+        /// the protocol fee and the royalty together are more than the price
+        PaymentExceedsPrice,
+        /// the bidder has not placed a sealed bid
+        NotParticipant,
+        /// the sealed bid's commitment is not a 32 byte hash
+        InvalidCommitment,
+        /// the revealed bid does not open the sealed bid's commitment
+        CommitmentMismatch,
+        /// the bid does not suit the auction's format
+        UnsupportedBid,
+        /// the bid is below the reserve price or does not beat the highest bid
+        BidTooLow,
+        /// the claim period is over, so the prize was forfeited (see `CLAIM_PERIOD`)
+        ClaimPeriodOver,
+        /// the winner can still claim their prize (see `CLAIM_PERIOD`)
+        ClaimPeriodInProgress,
         /// placeholder
         Other,
     }
@@ -85,31 +203,202 @@ mod tlock_proxy {
     /// The ERC-20 result type.
     pub type Result<T> = core::result::Result<T, Error>;
 
+    impl From<AccessError> for Error {
+        fn from(_: AccessError) -> Self {
+            Error::NotOwner
+        }
+    }
+
+    /// The errors of the child auctions, as seen by the proxy's callers
+    impl From<AuctionError> for Error {
+        fn from(error: AuctionError) -> Self {
+            match error {
+                // the auction was not deployed by this proxy
+                AuctionError::NotProxy => Error::AuctionUnverified,
+                AuctionError::WaitingReveals => Error::RevealPhaseInProgress,
+                AuctionError::NotParticipant => Error::NotParticipant,
+                AuctionError::InvalidCommitment => Error::InvalidCommitment,
+                AuctionError::CommitmentMismatch => Error::CommitmentMismatch,
+                AuctionError::AlreadyRevealed => Error::AlreadyRevealed,
+                AuctionError::UnsupportedBid => Error::UnsupportedBid,
+                AuctionError::BidTooLow => Error::BidTooLow,
+                AuctionError::AuctionClosed => Error::BiddingClosed,
+                AuctionError::RevealPhaseClosed => Error::RevealPhaseClosed,
+                AuctionError::AlreadyComplete => Error::AuctionAlreadyComplete,
+            }
+        }
+    }
+
+    /// The number of blocks after the reveal deadline in which winners claim their prizes.
+    /// Once it is over, an unclaimed prize is forfeited with `reclaim`: the winner's deposit
+    /// is paid to the auction owner and the asset is returned to them
+    pub const CLAIM_PERIOD: BlockNumber = 14_400;
+
     /// Defines the storage of your contract.
-    /// Add new fields to the below struct in order
-    /// to add new static storage fields to your contract.
     #[ink(storage)]
     pub struct TlockProxy {
         /// The owner of the contract
-        owner: AccountId,
+        owner: Ownable,
         /// the erc721 contract AccountId
         erc721: AccountId,
-        /// Stores references to all auctions
-        auctions: Vec<AuctionDetails>,
-        /// Stores references to all auctions
-        bids: Vec<Bid>,
-        /// The TlockAuction contract code hash
-        auction_contract_code_hash: Hash,
+        /// The code hash of the contract deployed for each kind of auction
+        auction_code_hashes: Mapping<AuctionKind, Hash>,
+        /// Deposits held on behalf of bidders
+        escrow: Escrow,
+        /// The details of each auction
+        auctions: Mapping<AccountId, AuctionDetails>,
+        /// The auctions, listed by owner, bidder, asset and status
+        index: AuctionIndex,
+        /// The winners who have claimed their prize, per auction
+        prizes_claimed: Mapping<(AccountId, AccountId), ()>,
+        /// The pulse read at the last reveal of each auction, which breaks its ties
+        reveal_pulses: Mapping<AccountId, [u8; 32]>,
+        /// The share of every sale paid to the owner (unset, i.e. 0, until configured)
+        protocol_fee: Lazy<BasisPoints>,
     }
 
+    /// the child auction contract, called through the `Auction` trait
+    type AuctionRef = ink::contract_ref!(Auction, DrandEnvironment);
+
+    /// a collection of the assets on sale, called through the `PSP34` trait
+    type CollectionRef = ink::contract_ref!(PSP34, DrandEnvironment);
+
+    /// a collection which moves tokens from a named owner, see `PSP34TransferFrom`
+    type TransferFromRef = ink::contract_ref!(PSP34TransferFrom, DrandEnvironment);
+
+    /// the royalties of a collection, if it implements them
+    type RoyaltyRef = ink::contract_ref!(PSP34Royalty, DrandEnvironment);
+
+    // Every change to an auction emits an event, so that indexers can rebuild
+    // the state of every auction from the proxy's events alone.
+
+    /// A new auction was deployed
     #[ink(event)]
     pub struct AuctionCreated {
         #[ink(topic)]
-        auction_id: AccountId,
+        pub auction_id: AccountId,
+        #[ink(topic)]
+        pub owner: AccountId,
+        /// the PSP34 collection of the assets on sale
+        #[ink(topic)]
+        pub collection: AccountId,
+        /// the assets on sale
+        pub asset_ids: Vec<Id>,
+        pub kind: AuctionKind,
+        /// the block from which bids are no longer accepted
+        pub deadline: BlockNumber,
+        /// the block from which bids can no longer be revealed
+        pub reveal_deadline: BlockNumber,
+    }
+
+    /// A bid was placed, and the bidder's deposit topped up
+    #[ink(event)]
+    pub struct BidPlaced {
+        #[ink(topic)]
+        pub auction_id: AccountId,
+        #[ink(topic)]
+        pub bidder: AccountId,
+        /// the deposit now held for the bidder
+        pub deposit: Balance,
+    }
+
+    /// A late bid extended the deadline
+    #[ink(event)]
+    pub struct DeadlineExtended {
+        #[ink(topic)]
+        pub auction_id: AccountId,
+        /// the new deadline
+        pub deadline: BlockNumber,
+        /// the new reveal deadline
+        pub reveal_deadline: BlockNumber,
+    }
+
+    /// A sealed bid was revealed
+    #[ink(event)]
+    pub struct BidRevealed {
+        #[ink(topic)]
+        pub auction_id: AccountId,
+        #[ink(topic)]
+        pub bidder: AccountId,
+        /// the amount bid
+        pub bid: Balance,
+    }
+
+    /// The deposit of a bidder whose bid was never revealed was paid to the auction owner
+    #[ink(event)]
+    pub struct Slashed {
+        #[ink(topic)]
+        pub auction_id: AccountId,
+        #[ink(topic)]
+        pub bidder: AccountId,
+        /// the deposit slashed
+        pub amount: Balance,
+    }
+
+    /// The auction was completed
+    #[ink(event)]
+    pub struct AuctionSettled {
+        #[ink(topic)]
+        pub auction_id: AccountId,
+        /// the winners and the price each one pays, or no sale
+        pub outcome: AuctionOutcome<AccountId, Balance>,
+    }
+
+    /// A winner paid for and received their prize
+    #[ink(event)]
+    pub struct Claimed {
+        #[ink(topic)]
+        pub auction_id: AccountId,
+        #[ink(topic)]
+        pub winner: AccountId,
+        /// the asset won
+        pub asset_id: Id,
+        /// the price paid by the winner
+        pub price: Balance,
+        /// the part of the price paid to the proxy owner
+        pub protocol_fee: Balance,
+        /// the part of the price paid to the asset's royalty receiver
+        pub royalty: Balance,
+    }
+
+    /// A winner did not claim their prize in time, so their deposit was paid to the
+    /// auction owner and the asset returned to them
+    #[ink(event)]
+    pub struct Forfeited {
+        #[ink(topic)]
+        pub auction_id: AccountId,
+        #[ink(topic)]
+        pub winner: AccountId,
+        /// the asset returned to the auction owner
+        pub asset_id: Id,
+        /// the deposit paid to the auction owner
+        pub deposit: Balance,
+    }
+
+    /// A deposit (or what is left of it) was returned to a bidder
+    #[ink(event)]
+    pub struct Refunded {
+        #[ink(topic)]
+        pub auction_id: AccountId,
+        #[ink(topic)]
+        pub bidder: AccountId,
+        /// the amount refunded, including any cancellation penalty
+        pub amount: Balance,
+    }
+
+    /// The auction was cancelled by its owner
+    #[ink(event)]
+    pub struct AuctionCancelled {
+        #[ink(topic)]
+        pub auction_id: AccountId,
+        /// the penalty paid to each bidder, on top of their deposit
+        pub penalty: Balance,
     }
 
     impl TlockProxy {
         /// Constructor
+        /// Vickrey auctions are registered with the given auction code hash,
+        /// other kinds must be registered by the owner with `register_auction_kind`
         #[ink(constructor)]
         pub fn new(
             owner: AccountId, // needed?
@@ -121,120 +410,378 @@ mod tlock_proxy {
                 .endowment(0)
                 .salt_bytes([0xde, 0xad, 0xbe, 0xef])
                 .instantiate();
+            Self::with_collection(owner, auction_contract_code_hash, erc721.to_account_id())
+        }
+
+        /// a proxy minting the assets of new auctions in an existing collection
+        fn with_collection(
+            owner: AccountId,
+            auction_contract_code_hash: Hash,
+            erc721: AccountId,
+        ) -> Self {
+            let mut auction_code_hashes = Mapping::default();
+            auction_code_hashes.insert(AuctionKind::Vickrey, &auction_contract_code_hash);
             Self {
-                owner,
-                erc721: erc721.to_account_id(),
-                auctions: Vec::new(),
-                bids: Vec::new(),
-                auction_contract_code_hash,
+                owner: Ownable::new(owner),
+                erc721,
+                auction_code_hashes,
+                escrow: Escrow::default(),
+                auctions: Mapping::default(),
+                index: AuctionIndex::default(),
+                prizes_claimed: Mapping::default(),
+                reveal_pulses: Mapping::default(),
+                protocol_fee: Lazy::new(),
+            }
+        }
+
+        /// replace the code of the proxy, keeping its address and storage
+        /// callable only by the owner. Nothing is migrated, so the new code must keep
+        /// the storage layout: a proxy whose layout changes is redeployed
+        ///
+        /// * `code_hash`: The code hash of the uploaded proxy contract
+        ///
+        #[ink(message)]
+        pub fn upgrade(&mut self, code_hash: Hash) -> Result<()> {
+            self.owner.ensure_owner(self.env().caller())?;
+            self.env()
+                .set_code_hash(&code_hash)
+                .map_err(|_| Error::UpgradeFailed)
+        }
+
+        /// register (or replace) the contract deployed for a kind of auction
+        /// callable only by the owner
+        ///
+        /// * `kind`: The kind of auction
+        /// * `code_hash`: The code hash of the uploaded auction contract
+        ///
+        #[ink(message)]
+        pub fn register_auction_kind(&mut self, kind: AuctionKind, code_hash: Hash) -> Result<()> {
+            self.owner.ensure_owner(self.env().caller())?;
+            self.auction_code_hashes.insert(kind, &code_hash);
+            Ok(())
+        }
+
+        /// deploy new auctions of a registered kind with fixed code
+        /// callable only by the owner. Auctions deployed before keep running their code
+        ///
+        /// * `kind`: The kind of auction
+        /// * `code_hash`: The code hash of the uploaded auction contract
+        ///
+        #[ink(message)]
+        pub fn update_auction_code_hash(
+            &mut self,
+            kind: AuctionKind,
+            code_hash: Hash,
+        ) -> Result<()> {
+            self.owner.ensure_owner(self.env().caller())?;
+            if !self.auction_code_hashes.contains(kind) {
+                return Err(Error::UnknownAuctionKind);
+            }
+            self.auction_code_hashes.insert(kind, &code_hash);
+            Ok(())
+        }
+
+        /// set the share of every sale paid to the owner, for auctions created from now on
+        /// callable only by the owner
+        ///
+        /// * `bps`: The protocol fee, in basis points of the price
+        ///
+        #[ink(message)]
+        pub fn set_protocol_fee(&mut self, bps: BasisPoints) -> Result<()> {
+            self.owner.ensure_owner(self.env().caller())?;
+            if bps > MAX_BASIS_POINTS {
+                return Err(Error::InvalidFee);
             }
+            self.protocol_fee.set(&bps);
+            Ok(())
+        }
+
+        /// get the protocol fee of new auctions, in basis points
+        #[ink(message)]
+        pub fn get_protocol_fee(&self) -> BasisPoints {
+            self.protocol_fee.get().unwrap_or(0)
+        }
+
+        /// hand the proxy to a new owner
+        /// callable only by the owner
+        ///
+        /// * `new_owner`: The account which becomes the owner
+        ///
+        #[ink(message)]
+        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<()> {
+            self.owner.transfer_ownership(self.env().caller(), new_owner)?;
+            Ok(())
+        }
+
+        /// get the code hash of the contract deployed for a kind of auction
+        ///
+        /// * `kind`: The kind of auction
+        ///
+        #[ink(message)]
+        pub fn get_auction_code_hash(&self, kind: AuctionKind) -> Option<Hash> {
+            self.auction_code_hashes.get(kind)
         }
 
         /// deploys a new auction contract if rules are satisfied.
+        /// an asset is minted for each unit on sale
+        ///
+        /// * `name`: The auction name
+        /// * `config`: The kind of auction and its parameters
+        /// * `terms`: The deadlines, deposit and reserve price of the auction
+        ///
         #[ink(message)]
         pub fn new_auction(
             &mut self,
-            name: [u8; 48],
-            deadline: BlockNumber,
-            deposit: Balance,
+            name: [u8; 32],
+            config: AuctionConfig,
+            terms: AuctionTerms,
         ) -> Result<AccountId> {
-            let caller = self.env().caller();
-            let contract_acct_id = self.env().account_id();
-            // random asset id creation with on-chain randomness
-            let mut seed = self.env().extension().secret();
-            seed.clone().iter().enumerate().for_each(|(i, bit)| {
-                seed[i] = *bit ^ name[i];
-            });
-
-            let mut hasher = Shake128::default();
-            let bytes = seed.to_vec();
-            hasher.update(&bytes.clone());
-            let mut reader = hasher.finalize_xof();
-            let mut asset_id_bytes = [0u8; 4];
-            reader.read(&mut asset_id_bytes);
-            let asset_id = u32::from_le_bytes(asset_id_bytes);
-
-            // try to mint the asset
+            let code_hash = self.check_auction(&config, &terms)?;
+            // the proxy is the only minter, and the erc721 contract derives the asset ids
             let mut erc721_contract: Erc721Ref =
                 ink::env::call::FromAccountId::from_account_id(self.erc721);
-            erc721_contract
-                .mint(asset_id)
-                .map_err(|_| Error::NFTMintFailed)?;
+            let mut asset_ids = Vec::new();
+            for _ in 0..config.units() {
+                let asset_id = erc721_contract
+                    .mint_next()
+                    .map_err(|_| Error::NFTMintFailed)?;
+                asset_ids.push(asset_id);
+            }
 
-            let auction_contract = VickreyAuctionRef::new(contract_acct_id, asset_id)
-                .endowment(0)
-                .code_hash(self.auction_contract_code_hash)
-                .salt_bytes(name.as_slice())
-                .instantiate();
-            let account_id = auction_contract.to_account_id();
+            let auction_id = self.deploy_auction(
+                config.clone(),
+                code_hash,
+                asset_ids[0].clone(),
+                terms.reserve_price,
+            );
             let auction = AuctionDetails {
-                name: name.to_vec().clone(),
-                auction_id: account_id,
-                asset_id,
+                name: name.to_vec(),
+                auction_id,
+                kind: config.kind(),
+                collection: self.erc721,
+                asset_ids,
+                owner: self.env().caller(),
+                deposit: terms.deposit,
+                reserve_price: terms.reserve_price,
+                deadline: terms.deadline,
+                reveal_deadline: terms.reveal_deadline,
+                published: self.env().block_timestamp(),
+                status: AuctionStatus::Open,
+                bids: 0,
+                unclaimed: 0,
+                extension: terms.extension,
+                extended_by: 0,
+                protocol_fee: self.get_protocol_fee(),
+            };
+            Ok(self.open(&auction))
+        }
+
+        /// deploys a new auction contract for a token the caller already owns
+        /// the caller must have approved the proxy to transfer the token, which
+        /// the proxy holds until the auction settles or is cancelled
+        ///
+        /// * `name`: The auction name
+        /// * `collection`: The contract of the token, a PSP34 collection with `PSP34TransferFrom`
+        /// * `token_id`: The token on sale
+        /// * `config`: The kind of auction and its parameters, for a single unit
+        /// * `terms`: The deadlines, deposit and reserve price of the auction
+        ///
+        #[ink(message)]
+        pub fn new_auction_for(
+            &mut self,
+            name: [u8; 32],
+            collection: AccountId,
+            token_id: Id,
+            config: AuctionConfig,
+            terms: AuctionTerms,
+        ) -> Result<AccountId> {
+            let code_hash = self.check_auction(&config, &terms)?;
+            if config.units() != 1 {
+                return Err(Error::InvalidAuctionConfig);
+            }
+            let caller = self.env().caller();
+            // moved from the caller by name, so nobody lists a token someone else approved
+            let mut collection_ref: TransferFromRef = collection.into();
+            collection_ref
+                .transfer_from(caller, self.env().account_id(), token_id.clone(), Vec::new())
+                .map_err(|_| Error::AssetTransferFailed)?;
+
+            let auction_id = self.deploy_auction(
+                config.clone(),
+                code_hash,
+                token_id.clone(),
+                terms.reserve_price,
+            );
+            let auction = AuctionDetails {
+                name: name.to_vec(),
+                auction_id,
+                kind: config.kind(),
+                collection,
+                asset_ids: vec![token_id],
                 owner: caller,
-                deposit,
-                deadline,
+                deposit: terms.deposit,
+                reserve_price: terms.reserve_price,
+                deadline: terms.deadline,
+                reveal_deadline: terms.reveal_deadline,
                 published: self.env().block_timestamp(),
-                status: 0,
+                status: AuctionStatus::Open,
                 bids: 0,
+                unclaimed: 0,
+                extension: terms.extension,
+                extended_by: 0,
+                protocol_fee: self.get_protocol_fee(),
             };
-            self.auctions.push(auction);
-            ink::codegen::EmitEvent::<TlockProxy>::emit_event(self.env(), AuctionCreated {
-                auction_id: account_id,
-            });
-            Ok(account_id)
+            Ok(self.open(&auction))
         }
 
         /// sends a bid to a specific auction (auction_id) if the status and dealine are valid
         /// and all conditions are satisfied
+        /// a bid close to the deadline extends it, if the auction is configured to
+        /// the transferred value is held in escrow, and together with any value already held
+        /// for the caller it must cover the auction's deposit
+        ///
+        /// * `auction_id`: The auction contract account id
+        /// * `bid`: The bid, in the shape the kind of auction expects
+        ///   (e.g. committed to with `bid_commitment`, for sealed-bid auctions)
+        ///
         #[ink(message, payable)]
-        pub fn bid(&mut self, auction_id: AccountId) -> Result<()> {
+        pub fn bid(&mut self, auction_id: AccountId, bid: BidPayload) -> Result<()> {
             let caller = self.env().caller();
             let mut auction_data = self.get_auction_by_auction_id(auction_id)?;
+            match auction_data.0.status {
+                AuctionStatus::Open => {}
+                AuctionStatus::Cancelled => return Err(Error::AuctionCancelled),
+                _ => return Err(Error::BiddingClosed),
+            }
             if !self.is_deadline_future(auction_data.0.deadline) {
-                return Err(Error::AuctionAlreadyComplete);
+                return Err(Error::BiddingClosed);
             }
             // check min deposit
             let transferred_value = self.env().transferred_value();
-            if transferred_value < auction_data.0.deposit {
+            let held = self.escrow.deposit_of(auction_id, caller);
+            if held.saturating_add(transferred_value) < auction_data.0.deposit {
                 return Err(Error::DepositTooLow);
             }
 
-            auction_data
-                .1
-                .bid(caller)
-                .map(|_| {
-                    // update the number of bids
-                    let mut new_auction_data = auction_data.0.clone();
-                    new_auction_data.bids += 1;
-                    self.auctions[auction_data.2] = new_auction_data;
-                    // update the bids map
-                    self.bids.push(Bid {
-                        auction_id,
-                        bidder: caller,
-                    });
-                })
-                .map_err(|_| Error::Other)?;
+            auction_data.1.bid(caller, bid)?;
+            let deposit = self.escrow.deposit(auction_id, caller, transferred_value);
+            // update the bidder's index
+            self.index.insert(IndexKey::Bidder(caller), auction_id);
+            self.env().emit_event(BidPlaced {
+                auction_id,
+                bidder: caller,
+                deposit,
+            });
+            // update the number of bids
+            let mut new_auction_data = auction_data.0.clone();
+            new_auction_data.bids = new_auction_data.bids.saturating_add(1);
+            self.extend_deadline(&mut new_auction_data);
+            self.auctions.insert(auction_id, &new_auction_data);
             Ok(())
         }
 
-        /// complete the auction
+        /// complete the auction, once the reveal deadline has passed
+        /// the deposits of bidders who never revealed their bids are slashed
+        /// and paid to the auction owner. Any assets which were not sold are
+        /// returned to the auction owner.
         #[ink(message)]
         pub fn complete(&mut self, auction_id: AccountId) -> Result<()> {
             let mut auction_data = self.get_auction_by_auction_id(auction_id)?;
-            // check deadline
+            // check deadlines
             if self.is_deadline_future(auction_data.0.deadline) {
                 return Err(Error::AuctionInProgress);
             }
+            if self.is_deadline_future(auction_data.0.reveal_deadline) {
+                return Err(Error::RevealPhaseInProgress);
+            }
+            self.ensure_transition(&auction_data.0, AuctionStatus::Settled)?;
 
-            auction_data.1.complete().map_err(|_| Error::Other)?;
+            // ties are broken with the pulse of the last reveal, whenever this is called
+            let pulse = self.reveal_pulses.take(auction_id).unwrap_or_default();
+            auction_data.1.complete(pulse)?;
+            let mut slashed: Balance = 0;
+            for bidder in auction_data.1.get_invalid_bids() {
+                let amount = self.escrow.release(auction_id, bidder);
+                self.env().emit_event(Slashed {
+                    auction_id,
+                    bidder,
+                    amount,
+                });
+                slashed = slashed.saturating_add(amount);
+            }
+            if slashed > 0 {
+                self.env()
+                    .transfer(auction_data.0.owner, slashed)
+                    .map_err(|_| Error::BalanceTransferFailed)?;
+            }
+            let outcome = auction_data
+                .1
+                .get_outcome()
+                .ok_or(Error::NoWinnerDetermined)?;
+            let sold = match &outcome {
+                AuctionOutcome::Sale(results) => results.len(),
+                AuctionOutcome::NoSale => 0,
+            };
+            for asset_id in auction_data.0.asset_ids.iter().skip(sold) {
+                self.transfer_asset(&auction_data.0, auction_data.0.owner, asset_id.clone())?;
+            }
             let mut new_auction_data = auction_data.0.clone();
-            new_auction_data.status = 1;
-            self.auctions[auction_data.2] = new_auction_data;
+            new_auction_data.unclaimed = u32::try_from(sold).unwrap_or(u32::MAX);
+            self.set_status(&mut new_auction_data, AuctionStatus::Settled);
+            self.env().emit_event(AuctionSettled {
+                auction_id,
+                outcome,
+            });
+            self.close_if_claimed(&mut new_auction_data);
+            Ok(())
+        }
+
+        /// cancel an open auction before its deadline, returning the assets to the owner
+        /// callable only by the auction owner
+        ///
+        /// Once bids have been placed, the owner must transfer a penalty of the auction's
+        /// deposit for each bidder. Bidders then `claim` their deposit and the penalty.
+        ///
+        /// * `auction_id`: The auction contract account id
+        ///
+        #[ink(message, payable)]
+        pub fn cancel_auction(&mut self, auction_id: AccountId) -> Result<()> {
+            let mut auction_data = self.get_auction_by_auction_id(auction_id)?;
+            if self.env().caller() != auction_data.0.owner {
+                return Err(Error::NotAuctionOwner);
+            }
+            if !self.is_deadline_future(auction_data.0.deadline) {
+                return Err(Error::BiddingClosed);
+            }
+            self.ensure_transition(&auction_data.0, AuctionStatus::Cancelled)?;
+            let bidders = self.escrow.bidders(auction_id) as Balance;
+            let penalty = if bidders > 0 { auction_data.0.deposit } else { 0 };
+            if self.env().transferred_value() != penalty.saturating_mul(bidders) {
+                return Err(Error::InvalidCurrencyAmountTransferred);
+            }
+
+            auction_data.1.cancel()?;
+            for asset_id in auction_data.0.asset_ids.iter() {
+                self.transfer_asset(&auction_data.0, auction_data.0.owner, asset_id.clone())?;
+            }
+            let mut new_auction_data = auction_data.0.clone();
+            self.set_status(&mut new_auction_data, AuctionStatus::Cancelled);
+            self.env().emit_event(AuctionCancelled {
+                auction_id,
+                penalty,
+            });
             Ok(())
         }
 
         /// claim a prize or reclaim deposit, post-auction
+        ///
+        /// The winner's deposit is credited against their debt, so they must transfer
+        /// exactly `debt - deposit` (or nothing, if the deposit covers the debt).
+        /// The owner is paid the debt and any surplus deposit is refunded to the winner.
+        /// Winners claim within `CLAIM_PERIOD` blocks of the reveal deadline, after which
+        /// their prize can be forfeited with `reclaim`.
+        /// Every other bidder is refunded their deposit.
+        /// If the auction was cancelled, every bidder is refunded their deposit and the penalty.
         #[ink(message, payable)]
         pub fn claim(&mut self, auction_id: AccountId) -> Result<()> {
             let caller = self.env().caller();
@@ -242,37 +789,156 @@ mod tlock_proxy {
 
             let auction_data = self.get_auction_by_auction_id(auction_id)?;
 
-            if self.is_deadline_future(auction_data.0.deadline) {
-                return Err(Error::AuctionInProgress);
+            match auction_data.0.status {
+                AuctionStatus::Settled => {}
+                AuctionStatus::Open | AuctionStatus::RevealPhase => {
+                    return Err(Error::AuctionNotComplete)
+                }
+                AuctionStatus::Cancelled => {
+                    if !self.escrow.holds(auction_id, caller) || transferred_value > 0 {
+                        return Err(Error::NothingToClaim);
+                    }
+                    let refund = self
+                        .escrow
+                        .release(auction_id, caller)
+                        .saturating_add(auction_data.0.deposit);
+                    self.env()
+                        .transfer(caller, refund)
+                        .map_err(|_| Error::BalanceTransferFailed)?;
+                    self.env().emit_event(Refunded {
+                        auction_id,
+                        bidder: caller,
+                        amount: refund,
+                    });
+                    return Ok(());
+                }
+                AuctionStatus::Claimed => return Err(Error::NothingToClaim),
             }
 
-            if let Some(result) = auction_data.1.get_winner() {
-                let winner = result.winner;
-                let debt = result.debt;
-                if winner.eq(&caller) {
-                    if !transferred_value.eq(&debt) {
+            let deposit = self.escrow.deposit_of(auction_id, caller);
+            let won = self.unclaimed_prize(&auction_data, caller);
+            match won.clone() {
+                Some((asset_id, debt)) => {
+                    if !self.is_deadline_future(Self::claim_deadline(&auction_data.0)) {
+                        return Err(Error::ClaimPeriodOver);
+                    }
+                    if !transferred_value.eq(&debt.saturating_sub(deposit)) {
                         return Err(Error::InvalidCurrencyAmountTransferred);
                     }
+                    self.escrow.release(auction_id, caller);
+                    self.prizes_claimed.insert((auction_id, caller), &());
+                    let (protocol_fee, royalty) =
+                        self.pay_out(&auction_data.0, asset_id.clone(), debt)?;
                     // transfer NFT ownership
-                    // fetch asset id from contract
-                    let asset_id = auction_data.1.get_asset_id();
-                    let mut erc721: Erc721Ref =
-                        ink::env::call::FromAccountId::from_account_id(self.erc721);
-                    erc721
-                        .transfer(winner, asset_id)
-                        .map_err(|_| Error::NftTransferFailed)?;
-                    // fetch owner from asset details
-                    let owner = auction_data.0.owner;
-                    // transfer tokens
+                    self.transfer_asset(&auction_data.0, caller, asset_id.clone())?;
+                    self.env().emit_event(Claimed {
+                        auction_id,
+                        winner: caller,
+                        asset_id,
+                        price: debt,
+                        protocol_fee,
+                        royalty,
+                    });
+                    let surplus = deposit.saturating_sub(debt);
+                    if surplus > 0 {
+                        self.env()
+                            .transfer(caller, surplus)
+                            .map_err(|_| Error::BalanceTransferFailed)?;
+                        self.env().emit_event(Refunded {
+                            auction_id,
+                            bidder: caller,
+                            amount: surplus,
+                        });
+                    }
+                }
+                _ => {
+                    if deposit == 0 || transferred_value > 0 {
+                        return Err(Error::NothingToClaim);
+                    }
+                    self.escrow.release(auction_id, caller);
                     self.env()
-                        .transfer(owner, transferred_value)
+                        .transfer(caller, deposit)
                         .map_err(|_| Error::BalanceTransferFailed)?;
+                    self.env().emit_event(Refunded {
+                        auction_id,
+                        bidder: caller,
+                        amount: deposit,
+                    });
                 }
             }
+            let mut new_auction_data = auction_data.0.clone();
+            if won.is_some() {
+                new_auction_data.unclaimed = new_auction_data.unclaimed.saturating_sub(1);
+                self.auctions.insert(auction_id, &new_auction_data);
+            }
+            self.close_if_claimed(&mut new_auction_data);
             Ok(())
         }
 
-        // Reveals a single bid
+        /// forfeit a prize which was not claimed within `CLAIM_PERIOD` blocks of the
+        /// reveal deadline, callable by anyone. The winner's deposit is paid to the
+        /// auction owner and the asset is returned to them
+        ///
+        /// * `auction_id`: The auction contract account id
+        /// * `winner`: The winner who did not claim their prize
+        ///
+        #[ink(message)]
+        pub fn reclaim(&mut self, auction_id: AccountId, winner: AccountId) -> Result<()> {
+            let auction_data = self.get_auction_by_auction_id(auction_id)?;
+            match auction_data.0.status {
+                AuctionStatus::Settled => {}
+                AuctionStatus::Open | AuctionStatus::RevealPhase => {
+                    return Err(Error::AuctionNotComplete)
+                }
+                AuctionStatus::Cancelled | AuctionStatus::Claimed => {
+                    return Err(Error::NothingToClaim)
+                }
+            }
+            if self.is_deadline_future(Self::claim_deadline(&auction_data.0)) {
+                return Err(Error::ClaimPeriodInProgress);
+            }
+            let (asset_id, _) = self
+                .unclaimed_prize(&auction_data, winner)
+                .ok_or(Error::NothingToClaim)?;
+
+            let deposit = self.escrow.release(auction_id, winner);
+            self.prizes_claimed.insert((auction_id, winner), &());
+            if deposit > 0 {
+                self.env()
+                    .transfer(auction_data.0.owner, deposit)
+                    .map_err(|_| Error::BalanceTransferFailed)?;
+            }
+            self.transfer_asset(&auction_data.0, auction_data.0.owner, asset_id.clone())?;
+            self.env().emit_event(Forfeited {
+                auction_id,
+                winner,
+                asset_id,
+                deposit,
+            });
+            let mut new_auction_data = auction_data.0.clone();
+            new_auction_data.unclaimed = new_auction_data.unclaimed.saturating_sub(1);
+            self.auctions.insert(auction_id, &new_auction_data);
+            self.close_if_claimed(&mut new_auction_data);
+            Ok(())
+        }
+
+        /// get the deposit held on behalf of a bidder in an auction
+        ///
+        /// * `auction_id`: The auction contract account id
+        /// * `bidder`: The bidder account id
+        ///
+        #[ink(message)]
+        pub fn get_deposit(&self, auction_id: AccountId, bidder: AccountId) -> Balance {
+            self.escrow.deposit_of(auction_id, bidder)
+        }
+
+        /// reveal a single bid, between the deadline and the reveal deadline
+        /// the first reveal moves the auction to its reveal phase, and the latest pulse is kept
+        /// to break the ties of the auction when it is completed
+        ///
+        /// * `auction_id`: The auction contract account id
+        /// * `revealed_bid`: The revealed bid
+        ///
         #[ink(message)]
         pub fn reveal_bid(
             &mut self,
@@ -280,46 +946,84 @@ mod tlock_proxy {
             revealed_bid: RevealedBid<AccountId>,
         ) -> Result<()> {
             let mut auction_data = self.get_auction_by_auction_id(auction_id)?;
-            // check deadline
+            // check deadlines
             if self.is_deadline_future(auction_data.0.deadline) {
                 return Err(Error::AuctionInProgress);
             }
-            auction_data
-                .1
-                .save_revealed_bid(revealed_bid)
-                .map_err(|_| Error::Other)?;
+            if !self.is_deadline_future(auction_data.0.reveal_deadline) {
+                return Err(Error::RevealPhaseClosed);
+            }
+            match auction_data.0.status {
+                AuctionStatus::Open | AuctionStatus::RevealPhase => {}
+                AuctionStatus::Cancelled => return Err(Error::AuctionCancelled),
+                _ => return Err(Error::RevealPhaseClosed),
+            }
+            let (bidder, bid) = (revealed_bid.bidder, revealed_bid.bid);
+            auction_data.1.reveal_bid(revealed_bid)?;
+            // a block without a pulse keeps the pulse of an earlier reveal
+            let pulse = self.env().extension().random();
+            if pulse != [0u8; 32] {
+                self.reveal_pulses.insert(auction_id, &pulse);
+            }
+            self.env().emit_event(BidRevealed {
+                auction_id,
+                bidder,
+                bid,
+            });
+            if auction_data.0.status == AuctionStatus::Open {
+                let mut new_auction_data = auction_data.0.clone();
+                self.set_status(&mut new_auction_data, AuctionStatus::RevealPhase);
+            }
             Ok(())
-        
         }
 
         /// get the winner and payment owed
-        /// by the winner of an auction
+        /// by the winner of an auction (the first, if several units were sold)
+        #[ink(message)]
+        pub fn get_winner(&self, auction_id: AccountId) -> Result<AuctionResult<AccountId, Balance>> {
+            match self.get_outcome(auction_id)? {
+                Some(AuctionOutcome::Sale(results)) if !results.is_empty() => Ok(results[0].clone()),
+                _ => Err(Error::NoWinnerDetermined),
+            }
+        }
+
+        /// get the outcome of an auction (None until the auction is complete)
         #[ink(message)]
-        pub fn get_winner(
+        pub fn get_outcome(
             &self,
             auction_id: AccountId,
-        ) -> Result<vickrey_auction::AuctionResult<AccountId, Balance>> {
+        ) -> Result<Option<AuctionOutcome<AccountId, Balance>>> {
             let auction_data = self.get_auction_by_auction_id(auction_id)?;
-            if let Some(winner) = auction_data.1.get_winner() {
-                return Ok(winner);
-            }
-            Err(Error::NoWinnerDetermined)
+            Ok(auction_data.1.get_outcome())
         }
 
-        /// get the winner and payment owed
-        /// by the winner of an auction
+        /// get the most recently created auction
         #[ink(message)]
-        pub fn get_latest_auction(
-            &self,
-        ) -> Result<AccountId> {
-            self.auctions.last().map(|x| x.auction_id).ok_or(Error::AuctionDoesNotExist)
+        pub fn get_latest_auction(&self) -> Result<AccountId> {
+            self.index
+                .last(IndexKey::All)
+                .ok_or(Error::AuctionDoesNotExist)
         }
 
+        /// get the number of auctions listed under a key of the index
+        /// e.g. `All` for the number of auctions created
+        ///
+        /// * `key`: The index key
+        ///
+        #[ink(message)]
+        pub fn get_auction_count(&self, key: IndexKey) -> u32 {
+            self.index.len(key)
+        }
 
-        /// Fetch a list of all auctions
+        /// Fetch a page of all auctions, in the order they were created
+        /// at most `MAX_PAGE_SIZE` auctions are returned
+        ///
+        /// * `offset`: The position of the first auction
+        /// * `limit`: The maximum number of auctions
+        ///
         #[ink(message)]
-        pub fn get_auctions(&self) -> Result<Vec<AuctionDetails>> {
-            Ok(self.auctions.clone())
+        pub fn get_auctions(&self, offset: u32, limit: u32) -> Result<Vec<AuctionDetails>> {
+            Ok(self.page(IndexKey::All, offset, limit))
         }
 
         /// Fetch auction details by auction contract account id
@@ -332,44 +1036,128 @@ mod tlock_proxy {
             Ok(auction.0)
         }
 
+        /// Fetch the details of the latest auction selling an asset minted by the proxy
+        ///
+        /// * `asset_id`: The asset id
+        ///
         #[ink(message)]
-        pub fn get_auction_details_by_asset_id(&self, asset_id: u32) -> Result<AuctionDetails> {
-            if let Some(auction) = self.auctions.iter().find(|x| x.asset_id == asset_id) {
-                return Ok(auction.clone());
-            }
-            Err(Error::AuctionDoesNotExist)
+        pub fn get_auction_details_by_asset_id(&self, asset_id: Id) -> Result<AuctionDetails> {
+            self.get_auction_details_by_token(self.erc721, asset_id)
         }
 
-        /// Fetch all auctions owned by the owner
+        /// Fetch the details of the latest auction selling a token of any collection
+        ///
+        /// * `collection`: The PSP34 collection of the token
+        /// * `token_id`: The token id
+        ///
+        #[ink(message)]
+        pub fn get_auction_details_by_token(
+            &self,
+            collection: AccountId,
+            token_id: Id,
+        ) -> Result<AuctionDetails> {
+            let auction_id = self
+                .index
+                .last(IndexKey::Asset(collection, token_id))
+                .ok_or(Error::AuctionDoesNotExist)?;
+            self.get_auction_details(auction_id)
+        }
+
+        /// Fetch a page of the auctions owned by the owner
         ///
         /// * `owner`: The auction owner account id
+        /// * `offset`: The position of the first auction
+        /// * `limit`: The maximum number of auctions
         ///
         #[ink(message)]
-        pub fn get_auctions_by_owner(&self, owner: AccountId) -> Result<Vec<AuctionDetails>> {
-            Ok(self
-                .auctions
-                .iter()
-                .filter(|x| x.owner == owner)
-                .cloned()
-                .collect::<Vec<AuctionDetails>>())
+        pub fn get_auctions_by_owner(
+            &self,
+            owner: AccountId,
+            offset: u32,
+            limit: u32,
+        ) -> Result<Vec<AuctionDetails>> {
+            Ok(self.page(IndexKey::Owner(owner), offset, limit))
         }
 
-        /// Fetch all auctions in which the bidder has placed a bid
+        /// Fetch a page of the auctions in which the bidder has placed a bid
         ///
         /// * `bidder`: The bidder account id
+        /// * `offset`: The position of the first auction
+        /// * `limit`: The maximum number of auctions
+        ///
+        #[ink(message)]
+        pub fn get_auctions_by_bidder(
+            &self,
+            bidder: AccountId,
+            offset: u32,
+            limit: u32,
+        ) -> Result<Vec<AuctionDetails>> {
+            Ok(self.page(IndexKey::Bidder(bidder), offset, limit))
+        }
+
+        /// Fetch a page of the auctions with a status
+        /// the order of these auctions changes as auctions change status
+        ///
+        /// * `status`: The auction status
+        /// * `offset`: The position of the first auction
+        /// * `limit`: The maximum number of auctions
         ///
         #[ink(message)]
-        pub fn get_auctions_by_bidder(&self, bidder: AccountId) -> Result<Vec<AuctionDetails>> {
-            Ok(self
-                .auctions
-                .iter()
-                .filter(|x| {
-                    self.bids
-                        .iter()
-                        .any(|y| y.bidder == bidder && y.auction_id == x.auction_id)
-                })
-                .cloned()
-                .collect::<Vec<AuctionDetails>>())
+        pub fn get_auctions_by_status(
+            &self,
+            status: AuctionStatus,
+            offset: u32,
+            limit: u32,
+        ) -> Result<Vec<AuctionDetails>> {
+            Ok(self.page(IndexKey::Status(status), offset, limit))
+        }
+
+        /// push the deadline out if a bid arrives within the auction's extension window
+        fn extend_deadline(&self, auction: &mut AuctionDetails) {
+            let Some(extension) = auction.extension else {
+                return;
+            };
+            let blocks_left = auction.deadline.saturating_sub(self.env().block_number());
+            if blocks_left > extension.window {
+                return;
+            }
+            let blocks = extension
+                .extension
+                .min(extension.max_extension.saturating_sub(auction.extended_by));
+            if blocks == 0 {
+                return;
+            }
+            auction.deadline = auction.deadline.saturating_add(blocks);
+            auction.reveal_deadline = auction.reveal_deadline.saturating_add(blocks);
+            auction.extended_by = auction.extended_by.saturating_add(blocks);
+            self.env().emit_event(DeadlineExtended {
+                auction_id: auction.auction_id,
+                deadline: auction.deadline,
+                reveal_deadline: auction.reveal_deadline,
+            });
+        }
+
+        /// the block from which the winners of the auction can no longer claim their prizes
+        fn claim_deadline(auction: &AuctionDetails) -> BlockNumber {
+            auction.reveal_deadline.saturating_add(CLAIM_PERIOD)
+        }
+
+        /// the asset won by the winner and their debt, unless they claimed it already
+        fn unclaimed_prize(
+            &self,
+            auction_data: &(AuctionDetails, AuctionRef),
+            winner: AccountId,
+        ) -> Option<(Id, Balance)> {
+            if self.prizes_claimed.contains((auction_data.0.auction_id, winner)) {
+                return None;
+            }
+            match auction_data.1.get_outcome() {
+                Some(AuctionOutcome::Sale(results)) => results
+                    .iter()
+                    .position(|r| r.winner.eq(&winner))
+                    .map(|unit| (auction_data.0.asset_ids[unit].clone(), results[unit].debt)),
+                _ => None,
+            }
         }
 
         /// check if the deadline has already passed
@@ -386,236 +1174,548 @@ mod tlock_proxy {
         fn get_auction_by_auction_id(
             &self,
             auction_id: AccountId,
-        ) -> Result<(AuctionDetails, VickreyAuctionRef, usize)> {
-            let (index, auction) = self
-                .auctions
-                .iter()
-                .enumerate()
-                .find(|(_, x)| x.auction_id == auction_id)
-                .ok_or(Error::AuctionDoesNotExist)?;
-            let auction_contract: VickreyAuctionRef =
-                ink::env::call::FromAccountId::from_account_id(auction.auction_id);
-            // clippy calls out the next line, but it must be cloned (since AuctionResult does not implement Copy, because Vec does not)
-            Ok((auction.clone(), auction_contract, index))
+        ) -> Result<(AuctionDetails, AuctionRef)> {
+            let auction = self.auctions.get(auction_id).ok_or(Error::AuctionDoesNotExist)?;
+            let auction_contract: AuctionRef = auction.auction_id.into();
+            Ok((auction, auction_contract))
+        }
+
+        /// check the deadlines and config of a new auction
+        /// returns the code hash of the contract to deploy
+        fn check_auction(&self, config: &AuctionConfig, terms: &AuctionTerms) -> Result<Hash> {
+            if terms.reveal_deadline < terms.deadline {
+                return Err(Error::InvalidDeadline);
+            }
+            // sealed bids are revealed after a fixed deadline, which cannot move
+            if terms.extension.is_some() && config.kind().is_sealed() {
+                return Err(Error::InvalidAuctionConfig);
+            }
+            let code_hash = self
+                .auction_code_hashes
+                .get(config.kind())
+                .ok_or(Error::UnknownAuctionKind)?;
+            if config.units() == 0 {
+                return Err(Error::InvalidAuctionConfig);
+            }
+            Ok(code_hash)
+        }
+
+        /// instantiate the child auction contract for a config
+        /// `first_asset_id` is only recorded by vickrey auctions
+        /// the salt is unique to each auction, so that auctions never collide
+        fn deploy_auction(
+            &self,
+            config: AuctionConfig,
+            code_hash: Hash,
+            first_asset_id: Id,
+            reserve_price: Balance,
+        ) -> AccountId {
+            let contract_acct_id = self.env().account_id();
+            let salt = unique_id(
+                &self.env().extension().random(),
+                contract_acct_id.as_ref(),
+                self.index.len(IndexKey::All).into(),
+            )
+            .to_le_bytes();
+            match config {
+                AuctionConfig::FirstPrice => {
+                    FirstPriceAuctionRef::new(contract_acct_id, reserve_price)
+                        .endowment(0)
+                        .code_hash(code_hash)
+                        .salt_bytes(salt)
+                        .instantiate()
+                        .to_account_id()
+                }
+                AuctionConfig::Vickrey => {
+                    VickreyAuctionRef::new(contract_acct_id, first_asset_id, reserve_price)
+                        .endowment(0)
+                        .code_hash(code_hash)
+                        .salt_bytes(salt)
+                        .instantiate()
+                        .to_account_id()
+                }
+                AuctionConfig::English { min_increment } => {
+                    EnglishAuctionRef::new(contract_acct_id, reserve_price, min_increment)
+                        .endowment(0)
+                        .code_hash(code_hash)
+                        .salt_bytes(salt)
+                        .instantiate()
+                        .to_account_id()
+                }
+                AuctionConfig::Dutch {
+                    start_price,
+                    decrement,
+                } => DutchAuctionRef::new(contract_acct_id, reserve_price, start_price, decrement)
+                    .endowment(0)
+                    .code_hash(code_hash)
+                    .salt_bytes(salt)
+                    .instantiate()
+                    .to_account_id(),
+                AuctionConfig::UniformPrice { units } => {
+                    UniformPriceAuctionRef::new(contract_acct_id, reserve_price, units)
+                        .endowment(0)
+                        .code_hash(code_hash)
+                        .salt_bytes(salt)
+                        .instantiate()
+                        .to_account_id()
+                }
+            }
+        }
+
+        /// list a new auction and announce it
+        /// returns the auction contract account id
+        fn open(&mut self, auction: &AuctionDetails) -> AccountId {
+            self.list(auction);
+            self.env().emit_event(AuctionCreated {
+                auction_id: auction.auction_id,
+                owner: auction.owner,
+                collection: auction.collection,
+                asset_ids: auction.asset_ids.clone(),
+                kind: auction.kind,
+                deadline: auction.deadline,
+                reveal_deadline: auction.reveal_deadline,
+            });
+            auction.auction_id
+        }
+
+        /// transfer an asset held by the proxy
+        fn transfer_asset(
+            &self,
+            auction: &AuctionDetails,
+            to: AccountId,
+            asset_id: Id,
+        ) -> Result<()> {
+            let mut collection: CollectionRef = auction.collection.into();
+            collection
+                .transfer(to, asset_id, Vec::new())
+                .map_err(|_| Error::NftTransferFailed)
+        }
+
+        /// split the price of a prize between the proxy owner, the royalty receiver
+        /// of the asset and the auction owner, who receives the rest, and pay them
+        /// fails, paying nobody, if the protocol fee and royalty exceed the price
+        /// collections which do not implement `PSP34Royalty` pay no royalty
+        /// returns the protocol fee and royalty paid
+        fn pay_out(
+            &self,
+            auction: &AuctionDetails,
+            asset_id: Id,
+            price: Balance,
+        ) -> Result<(Balance, Balance)> {
+            let collection: RoyaltyRef = auction.collection.into();
+            let (receiver, royalty) = collection
+                .call()
+                .royalty_info(asset_id, price)
+                .try_invoke()
+                .ok()
+                .and_then(|result| result.ok())
+                .flatten()
+                .unwrap_or((auction.owner, 0));
+            let protocol_fee = share_of(price, auction.protocol_fee);
+            let proceeds = price
+                .checked_sub(protocol_fee)
+                .and_then(|rest| rest.checked_sub(royalty))
+                .ok_or(Error::PaymentExceedsPrice)?;
+            let payments = [
+                (self.owner.owner(), protocol_fee),
+                (receiver, royalty),
+                (auction.owner, proceeds),
+            ];
+            for (to, amount) in payments.into_iter().filter(|(_, amount)| *amount > 0) {
+                self.env()
+                    .transfer(to, amount)
+                    .map_err(|_| Error::BalanceTransferFailed)?;
+            }
+            Ok((protocol_fee, royalty))
+        }
+
+        /// store a new auction and list it in the index
+        fn list(&mut self, auction: &AuctionDetails) {
+            let auction_id = auction.auction_id;
+            self.auctions.insert(auction_id, auction);
+            self.index.insert(IndexKey::All, auction_id);
+            self.index.insert(IndexKey::Owner(auction.owner), auction_id);
+            self.index.insert(IndexKey::Status(auction.status), auction_id);
+            for asset_id in auction.asset_ids.iter() {
+                self.index
+                    .insert(IndexKey::Asset(auction.collection, asset_id.clone()), auction_id);
+            }
+        }
+
+        /// check that the lifecycle allows the auction to move to `next`
+        fn ensure_transition(&self, auction: &AuctionDetails, next: AuctionStatus) -> Result<()> {
+            if auction.status.can_become(next) {
+                return Ok(());
+            }
+            Err(match auction.status {
+                AuctionStatus::Cancelled => Error::AuctionCancelled,
+                AuctionStatus::Settled | AuctionStatus::Claimed => Error::AuctionAlreadyComplete,
+                AuctionStatus::Open | AuctionStatus::RevealPhase => Error::AuctionNotComplete,
+            })
+        }
+
+        /// move a settled auction to `Claimed` once every prize and deposit is claimed
+        fn close_if_claimed(&mut self, auction: &mut AuctionDetails) {
+            if auction.unclaimed == 0 && self.escrow.total(auction.auction_id) == 0 {
+                self.set_status(auction, AuctionStatus::Claimed);
+            }
+        }
+
+        /// update the status of an auction, keeping the index in sync
+        /// the caller must have checked the transition with `ensure_transition`
+        fn set_status(&mut self, auction: &mut AuctionDetails, status: AuctionStatus) {
+            debug_assert!(auction.status.can_become(status));
+            self.index.remove(IndexKey::Status(auction.status), auction.auction_id);
+            auction.status = status;
+            self.index.insert(IndexKey::Status(status), auction.auction_id);
+            self.auctions.insert(auction.auction_id, auction);
+        }
+
+        /// the details of a page of the auctions listed under the key
+        fn page(&self, key: IndexKey, offset: u32, limit: u32) -> Vec<AuctionDetails> {
+            self.index
+                .page(key, offset, limit)
+                .into_iter()
+                .filter_map(|auction_id| self.auctions.get(auction_id))
+                .collect()
         }
     }
 
-    /// all tests are done through e2e currently, since this contract
-    /// depends on uploaded code hashes and cross contract calls
+    /// Unit tests cover the checks and bookkeeping done before any call to a child
+    /// auction or collection. Deploying and calling them is tested in the drink!
+    /// sandbox (see `sandbox/tests/timelock_auction.rs`).
     #[cfg(test)]
     mod tests {
-        // use super::*;
-    }
-
-    /// E2E Tests
-    ///
-    /// When running these you need to make sure that you:
-    /// - Compile the tests with the `e2e-tests` feature flag enabled (`--features e2e-tests`)
-    /// - Are running a Substrate node which contains `pallet-contracts` in the background
-    #[cfg(all(test, feature = "e2e-tests"))]
-    mod e2e_tests {
         use super::*;
-        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
-
-        /// We test that we can upload and instantiate the contract using its default constructor.
-        #[ink_e2e::test(environment = crate::EtfEnvironment)]
-        async fn default_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
-            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            let erc721_code_hash = client
-                .upload("erc721", &ink_e2e::alice(), None)
-                .await
-                .expect("upload should be ok")
-                .code_hash;
-
-            let auction_code_hash = client
-                .upload("vickrey_auction", &ink_e2e::alice(), None)
-                .await
-                .expect("should be ok")
-                .code_hash;
-            let tlock_proxy = TlockProxyRef::new(accounts.bob, auction_code_hash, erc721_code_hash);
-            // When: I instantiate the contract
-            let contract_account_id = client
-                .instantiate("tlock_proxy", &ink_e2e::alice(), tlock_proxy, 0, None)
-                .await
-                .expect("instantiate failed")
-                .account_id;
-
-            let get_auctions =
-                ink_e2e::MessageBuilder::<crate::EtfEnvironment, TlockProxyRef>::from_account_id(
-                    contract_account_id,
-                )
-                .call(|proxy| proxy.get_auctions());
-
-            let get_auctions_res = client
-                .call(&ink_e2e::bob(), get_auctions, 0, None)
-                .await
-                .expect("get failed");
-
-            assert!(matches!(
-                get_auctions_res
-                    .return_value()
-                    .expect("should be empty")
-                    .is_empty(),
-                true
-            ));
-            Ok(())
+        use ink::env::{test, DefaultEnvironment};
+
+        /// the balance every account needs to exist
+        const ENDOWMENT: Balance = 1_000_000;
+
+        fn contract_id() -> AccountId {
+            AccountId::from([0x42; 32])
         }
 
-        #[ink_e2e::test(environment = crate::EtfEnvironment)]
-        async fn new_auction_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
-            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            let erc721_code_hash = client
-                .upload("erc721", &ink_e2e::alice(), None)
-                .await
-                .expect("upload should be ok")
-                .code_hash;
-
-            let auction_code_hash = client
-                .upload("vickrey_auction", &ink_e2e::alice(), None)
-                .await
-                .expect("should be ok")
-                .code_hash;
-            let tlock_proxy = TlockProxyRef::new(accounts.bob, auction_code_hash, erc721_code_hash);
-            // When: I instantiate the contract
-            let contract_account_id = client
-                .instantiate("tlock_proxy", &ink_e2e::alice(), tlock_proxy, 0, None)
-                .await
-                .expect("instantiate failed")
-                .account_id;
-
-            // And: I create a new auction
-            let new_auction =
-                ink_e2e::MessageBuilder::<crate::EtfEnvironment, TlockProxyRef>::from_account_id(
-                    contract_account_id,
-                )
-                .call(|proxy| proxy.new_auction(b"my_auction".to_vec(), 1u32, 1u64, 1));
-
-            let new_auction_res = client
-                .call(&ink_e2e::bob(), new_auction, 0, None)
-                .await
-                .expect("get failed");
-
-            let auction_contract_id = new_auction_res.return_value().ok().unwrap();
-
-            let get_auctions =
-                ink_e2e::MessageBuilder::<crate::EtfEnvironment, TlockProxyRef>::from_account_id(
-                    contract_account_id,
-                )
-                .call(|proxy| proxy.get_auctions());
-
-            let get_auctions_by_id =
-                ink_e2e::MessageBuilder::<crate::EtfEnvironment, TlockProxyRef>::from_account_id(
-                    contract_account_id,
-                )
-                .call(|proxy| proxy.get_auction_details(auction_contract_id));
-
-            let get_auctions_res = client
-                .call(&ink_e2e::bob(), get_auctions, 0, None)
-                .await
-                .expect("get failed");
-
-            let get_auction_by_id_res = client
-                .call(&ink_e2e::bob(), get_auctions_by_id, 0, None)
-                .await
-                .expect("failed");
-
-            let expected_auction_details = AuctionDetails {
-                name: b"my_auction".to_vec(),
-                auction_id: auction_contract_id,
-                asset_id: 1u32,
-                owner: accounts.alice,
-                deposit: 1,
-                deadline: 1u32,
-                status: 0,
-                bids: 0,
+        fn collection() -> AccountId {
+            AccountId::from([0x72; 32])
+        }
+
+        /// a proxy owned by alice, selling tokens of `collection`
+        fn setup() -> TlockProxy {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            test::set_callee::<DefaultEnvironment>(contract_id());
+            test::set_account_balance::<DefaultEnvironment>(contract_id(), ENDOWMENT);
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            TlockProxy::with_collection(accounts.alice, Hash::from([1; 32]), collection())
+        }
+
+        /// an open vickrey auction of token `n`, whose deadline is block 5
+        /// and whose reveal deadline is block 7
+        fn auction(auction_id: AccountId, owner: AccountId, n: u32) -> AuctionDetails {
+            AuctionDetails {
+                name: vec![n as u8; 32],
+                auction_id,
+                kind: AuctionKind::Vickrey,
+                collection: collection(),
+                asset_ids: vec![Id::U32(n)],
+                owner,
+                deposit: 10,
+                reserve_price: 0,
+                deadline: 5,
+                reveal_deadline: 7,
                 published: 0,
+                status: AuctionStatus::Open,
+                bids: 0,
+                unclaimed: 0,
+                extension: None,
+                extended_by: 0,
+                protocol_fee: 0,
+            }
+        }
+
+        fn terms(deadline: BlockNumber, reveal_deadline: BlockNumber) -> AuctionTerms {
+            AuctionTerms {
+                deadline,
+                reveal_deadline,
+                deposit: 10,
+                reserve_price: 0,
+                extension: None,
+            }
+        }
+
+        fn advance_to(block: BlockNumber) {
+            while ink::env::block_number::<DefaultEnvironment>() < block {
+                test::advance_block::<DefaultEnvironment>();
+            }
+        }
+
+        #[ink::test]
+        fn only_the_owner_configures_the_proxy() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut proxy = setup();
+            let code_hash = proxy.get_auction_code_hash(AuctionKind::Vickrey);
+            assert_eq!(code_hash, Some(Hash::from([1; 32])));
+
+            test::set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(proxy.set_protocol_fee(100), Err(Error::NotOwner));
+            assert_eq!(
+                proxy.register_auction_kind(AuctionKind::English, Hash::from([2; 32])),
+                Err(Error::NotOwner)
+            );
+            assert_eq!(proxy.transfer_ownership(accounts.bob), Err(Error::NotOwner));
+
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(proxy.set_protocol_fee(MAX_BASIS_POINTS + 1), Err(Error::InvalidFee));
+            assert_eq!(proxy.set_protocol_fee(250), Ok(()));
+            assert_eq!(proxy.get_protocol_fee(), 250);
+            // only registered kinds can be updated
+            assert_eq!(
+                proxy.update_auction_code_hash(AuctionKind::English, Hash::from([2; 32])),
+                Err(Error::UnknownAuctionKind)
+            );
+            let res = proxy.register_auction_kind(AuctionKind::English, Hash::from([2; 32]));
+            assert_eq!(res, Ok(()));
+            assert_eq!(
+                proxy.update_auction_code_hash(AuctionKind::English, Hash::from([3; 32])),
+                Ok(())
+            );
+            let code_hash = proxy.get_auction_code_hash(AuctionKind::English);
+            assert_eq!(code_hash, Some(Hash::from([3; 32])));
+            assert_eq!(proxy.transfer_ownership(accounts.bob), Ok(()));
+            assert_eq!(proxy.set_protocol_fee(0), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn new_auctions_are_checked_before_deploying() {
+            let mut proxy = setup();
+            let name = [1; 32];
+            assert_eq!(
+                proxy.new_auction(name, AuctionConfig::Vickrey, terms(5, 4)),
+                Err(Error::InvalidDeadline)
+            );
+            // sealed bids are revealed after a fixed deadline, which cannot move
+            let extension = DeadlineExtension { window: 1, extension: 1, max_extension: 1 };
+            let extended = AuctionTerms { extension: Some(extension), ..terms(5, 7) };
+            assert_eq!(
+                proxy.new_auction(name, AuctionConfig::Vickrey, extended),
+                Err(Error::InvalidAuctionConfig)
+            );
+            assert_eq!(
+                proxy.new_auction(name, AuctionConfig::FirstPrice, terms(5, 7)),
+                Err(Error::UnknownAuctionKind)
+            );
+            assert_eq!(
+                proxy.register_auction_kind(AuctionKind::UniformPrice, Hash::from([2; 32])),
+                Ok(())
+            );
+            assert_eq!(
+                proxy.new_auction(name, AuctionConfig::UniformPrice { units: 0 }, terms(5, 7)),
+                Err(Error::InvalidAuctionConfig)
+            );
+            // an existing token is a single unit
+            assert_eq!(
+                proxy.new_auction_for(
+                    name,
+                    collection(),
+                    Id::U32(1),
+                    AuctionConfig::UniformPrice { units: 2 },
+                    terms(5, 7),
+                ),
+                Err(Error::InvalidAuctionConfig)
+            );
+            assert_eq!(proxy.get_auction_count(IndexKey::All), 0);
+        }
+
+        #[ink::test]
+        fn auctions_are_indexed() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut proxy = setup();
+            assert_eq!(proxy.get_latest_auction(), Err(Error::AuctionDoesNotExist));
+            proxy.list(&auction(accounts.django, accounts.bob, 1));
+            proxy.list(&auction(accounts.eve, accounts.charlie, 2));
+            proxy.list(&auction(accounts.frank, accounts.bob, 3));
+
+            let ids = |page: Result<Vec<AuctionDetails>>| {
+                page.unwrap().iter().map(|a| a.auction_id).collect::<Vec<_>>()
             };
-            assert!(matches!(
-                get_auctions_res
-                    .return_value()
-                    .expect("should be non-empty")
-                    .len(),
-                1
-            ));
-            assert!(matches!(
-                get_auction_by_id_res.return_value().expect("should be ok"),
-                expected_auction_details
-            ));
-            Ok(())
+            assert_eq!(
+                ids(proxy.get_auctions(0, 10)),
+                vec![accounts.django, accounts.eve, accounts.frank]
+            );
+            assert_eq!(ids(proxy.get_auctions(1, 1)), vec![accounts.eve]);
+            assert_eq!(
+                ids(proxy.get_auctions_by_owner(accounts.bob, 0, 10)),
+                vec![accounts.django, accounts.frank]
+            );
+            assert_eq!(proxy.get_latest_auction(), Ok(accounts.frank));
+            assert_eq!(
+                proxy.get_auction_details_by_asset_id(Id::U32(2)).map(|a| a.auction_id),
+                Ok(accounts.eve)
+            );
+            assert_eq!(
+                proxy.get_auction_details_by_token(accounts.alice, Id::U32(2)),
+                Err(Error::AuctionDoesNotExist)
+            );
+            assert_eq!(proxy.get_auction_details(accounts.alice), Err(Error::AuctionDoesNotExist));
+
+            // the status lists follow the status of each auction
+            let mut details = proxy.get_auction_details(accounts.eve).unwrap();
+            proxy.set_status(&mut details, AuctionStatus::Cancelled);
+            assert_eq!(proxy.get_auction_count(IndexKey::Status(AuctionStatus::Open)), 2);
+            assert_eq!(
+                ids(proxy.get_auctions_by_status(AuctionStatus::Cancelled, 0, 10)),
+                vec![accounts.eve]
+            );
+            assert_eq!(
+                proxy.get_auction_details(accounts.eve).unwrap().status,
+                AuctionStatus::Cancelled
+            );
         }
 
-        #[ink_e2e::test(environment = crate::EtfEnvironment)]
-        async fn bid_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
-            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            let erc721_code_hash = client
-                .upload("erc721", &ink_e2e::alice(), None)
-                .await
-                .expect("upload should be ok")
-                .code_hash;
-
-            let auction_code_hash = client
-                .upload("vickrey_auction", &ink_e2e::alice(), None)
-                .await
-                .expect("should be ok")
-                .code_hash;
-            let tlock_proxy = TlockProxyRef::new(accounts.bob, auction_code_hash, erc721_code_hash);
-            // When: I instantiate the contract
-            let contract_account_id = client
-                .instantiate("tlock_proxy", &ink_e2e::alice(), tlock_proxy, 0, None)
-                .await
-                .expect("instantiate failed")
-                .account_id;
-
-            // And: I create a new auction
-            let new_auction =
-                ink_e2e::MessageBuilder::<crate::EtfEnvironment, TlockProxyRef>::from_account_id(
-                    contract_account_id,
-                )
-                .call(|proxy| {
-                    proxy.new_auction(
-                        b"my_auction".to_vec(),
-                        1u32,
-                        1000000000u64, // some slot waaaay in the future
-                        1,
-                    )
-                });
+        #[ink::test]
+        fn bids_are_checked_before_reaching_the_auction() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut proxy = setup();
+            let bid = || BidPayload::Open(10);
+            assert_eq!(proxy.bid(accounts.django, bid()), Err(Error::AuctionDoesNotExist));
 
-            let new_auction_res = client
-                .call(&ink_e2e::alice(), new_auction, 0, None)
-                .await
-                .expect("get failed");
-
-            let auction_acct_id = new_auction_res.return_value().ok().unwrap();
-
-            let bid_call =
-                ink_e2e::MessageBuilder::<crate::EtfEnvironment, TlockProxyRef>::from_account_id(
-                    contract_account_id,
-                )
-                .call(|p| p.bid(auction_acct_id));
-
-            let bid_res = client
-                .call(&ink_e2e::alice(), bid_call, 1, None)
-                .await
-                .expect("failed");
-
-            assert!(matches!(bid_res.return_value(), Ok(())));
-
-            let acct_bytes: [u8; 32] = *ink_e2e::alice().public_key().to_account_id().as_ref();
-            let acct_id = AccountId::from(acct_bytes);
-            let bid_query =
-                ink_e2e::MessageBuilder::<crate::EtfEnvironment, TlockProxyRef>::from_account_id(
-                    contract_account_id,
-                )
-                .call(|proxy| proxy.get_auctions_by_bidder(acct_id));
-
-            let bid_query_res = client
-                .call(&ink_e2e::alice(), bid_query, 0, None)
-                .await
-                .expect("get failed");
-
-            let res = bid_query_res.return_value().expect("should exist");
-            assert!(matches!(res.len(), 1));
-            Ok(())
+            proxy.list(&auction(accounts.django, accounts.bob, 1));
+            test::set_caller::<DefaultEnvironment>(accounts.charlie);
+            test::set_value_transferred::<DefaultEnvironment>(9);
+            assert_eq!(proxy.bid(accounts.django, bid()), Err(Error::DepositTooLow));
+
+            let mut details = proxy.get_auction_details(accounts.django).unwrap();
+            proxy.set_status(&mut details, AuctionStatus::Cancelled);
+            assert_eq!(proxy.bid(accounts.django, bid()), Err(Error::AuctionCancelled));
+
+            proxy.list(&auction(accounts.eve, accounts.bob, 2));
+            advance_to(5);
+            test::set_value_transferred::<DefaultEnvironment>(10);
+            assert_eq!(proxy.bid(accounts.eve, bid()), Err(Error::BiddingClosed));
+            assert_eq!(proxy.get_deposit(accounts.eve, accounts.charlie), 0);
+        }
+
+        #[test]
+        fn auction_errors_are_mapped() {
+            assert_eq!(Error::from(AuctionError::CommitmentMismatch), Error::CommitmentMismatch);
+            assert_eq!(Error::from(AuctionError::BidTooLow), Error::BidTooLow);
+            assert_eq!(Error::from(AuctionError::AuctionClosed), Error::BiddingClosed);
+            assert_eq!(Error::from(AuctionError::AlreadyComplete), Error::AuctionAlreadyComplete);
+        }
+
+        #[ink::test]
+        fn deadlines_gate_reveals_and_completion() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut proxy = setup();
+            proxy.list(&auction(accounts.django, accounts.bob, 1));
+            let revealed = RevealedBid { bidder: accounts.charlie, bid: 10, salt: [0; 32] };
+
+            assert_eq!(proxy.complete(accounts.django), Err(Error::AuctionInProgress));
+            assert_eq!(
+                proxy.reveal_bid(accounts.django, revealed.clone()),
+                Err(Error::AuctionInProgress)
+            );
+            advance_to(5);
+            assert_eq!(proxy.complete(accounts.django), Err(Error::RevealPhaseInProgress));
+            advance_to(7);
+            assert_eq!(
+                proxy.reveal_bid(accounts.django, revealed),
+                Err(Error::RevealPhaseClosed)
+            );
+
+            // the lifecycle is checked before the auction is completed
+            let mut details = proxy.get_auction_details(accounts.django).unwrap();
+            proxy.set_status(&mut details, AuctionStatus::Cancelled);
+            assert_eq!(proxy.complete(accounts.django), Err(Error::AuctionCancelled));
+        }
+
+        #[ink::test]
+        fn only_the_owner_cancels_before_the_deadline() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut proxy = setup();
+            proxy.list(&auction(accounts.django, accounts.bob, 1));
+            proxy.escrow.deposit(accounts.django, accounts.charlie, 10);
+            proxy.escrow.deposit(accounts.django, accounts.eve, 10);
+
+            assert_eq!(proxy.cancel_auction(accounts.django), Err(Error::NotAuctionOwner));
+            test::set_caller::<DefaultEnvironment>(accounts.bob);
+            // the owner pays the deposit to each of the two bidders
+            test::set_value_transferred::<DefaultEnvironment>(10);
+            assert_eq!(
+                proxy.cancel_auction(accounts.django),
+                Err(Error::InvalidCurrencyAmountTransferred)
+            );
+            advance_to(5);
+            test::set_value_transferred::<DefaultEnvironment>(20);
+            assert_eq!(proxy.cancel_auction(accounts.django), Err(Error::BiddingClosed));
+        }
+
+        #[ink::test]
+        fn cancelled_auctions_refund_the_deposit_and_penalty() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut proxy = setup();
+            proxy.list(&auction(accounts.django, accounts.bob, 1));
+            proxy.escrow.deposit(accounts.django, accounts.charlie, 10);
+            test::set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(proxy.claim(accounts.django), Err(Error::AuctionNotComplete));
+
+            let mut details = proxy.get_auction_details(accounts.django).unwrap();
+            proxy.set_status(&mut details, AuctionStatus::Cancelled);
+            // the proxy holds the deposit and the owner's penalty
+            test::set_account_balance::<DefaultEnvironment>(contract_id(), ENDOWMENT + 20);
+            test::set_account_balance::<DefaultEnvironment>(accounts.charlie, ENDOWMENT);
+            assert_eq!(proxy.claim(accounts.django), Ok(()));
+            assert_eq!(
+                test::get_account_balance::<DefaultEnvironment>(accounts.charlie),
+                Ok(ENDOWMENT + 20)
+            );
+            assert_eq!(proxy.get_deposit(accounts.django, accounts.charlie), 0);
+            assert_eq!(proxy.claim(accounts.django), Err(Error::NothingToClaim));
+            test::set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(proxy.claim(accounts.django), Err(Error::NothingToClaim));
+        }
+
+        #[ink::test]
+        fn settled_auctions_close_once_everything_is_claimed() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let mut proxy = setup();
+            let mut details = auction(accounts.django, accounts.bob, 1);
+            proxy.list(&details);
+            proxy.escrow.deposit(accounts.django, accounts.charlie, 10);
+            proxy.set_status(&mut details, AuctionStatus::Settled);
+
+            proxy.close_if_claimed(&mut details);
+            assert_eq!(details.status, AuctionStatus::Settled);
+            proxy.escrow.release(accounts.django, accounts.charlie);
+            proxy.close_if_claimed(&mut details);
+            assert_eq!(details.status, AuctionStatus::Claimed);
+            assert_eq!(proxy.get_auction_count(IndexKey::Status(AuctionStatus::Claimed)), 1);
+            assert_eq!(proxy.get_auction_count(IndexKey::Status(AuctionStatus::Settled)), 0);
+        }
+
+        #[ink::test]
+        fn late_bids_extend_the_deadline_up_to_the_maximum() {
+            let accounts = test::default_accounts::<DefaultEnvironment>();
+            let proxy = setup();
+            let mut details = auction(accounts.django, accounts.bob, 1);
+            details.extension =
+                Some(DeadlineExtension { window: 2, extension: 3, max_extension: 4 });
+
+            // outside the window
+            advance_to(2);
+            proxy.extend_deadline(&mut details);
+            assert_eq!((details.deadline, details.reveal_deadline), (5, 7));
+            advance_to(3);
+            proxy.extend_deadline(&mut details);
+            let deadlines = (details.deadline, details.reveal_deadline, details.extended_by);
+            assert_eq!(deadlines, (8, 10, 3));
+            advance_to(7);
+            proxy.extend_deadline(&mut details);
+            let deadlines = (details.deadline, details.reveal_deadline, details.extended_by);
+            assert_eq!(deadlines, (9, 11, 4));
+            advance_to(8);
+            proxy.extend_deadline(&mut details);
+            assert_eq!(details.deadline, 9);
         }
     }
 }
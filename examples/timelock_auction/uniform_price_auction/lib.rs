@@ -0,0 +1,269 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+pub use self::uniform_price_auction::{UniformPriceAuction, UniformPriceAuctionRef};
+
+use idl_contract_extension::ext::DrandEnvironment;
+
+#[ink::contract(env = DrandEnvironment)]
+mod uniform_price_auction {
+    use crate::DrandEnvironment;
+    use idl_contract_extension::access::Ownable;
+    use auction_traits::{
+        sealed::{self, SealedBids},
+        Auction, AuctionKind, AuctionOutcome, AuctionResult, AuctionStatus, BidPayload,
+        RevealedBid,
+    };
+    use ink::prelude::vec::Vec;
+
+    pub use auction_traits::AuctionError as Error;
+
+    /// A sealed-bid multi-unit auction
+    ///
+    /// Each bidder bids for a single unit. The highest `units` bids win,
+    /// and every winner pays the same (uniform) price: the highest losing bid.
+    #[ink(storage)]
+    pub struct UniformPriceAuction {
+        /// the proxy (contract)
+        proxy: Ownable,
+        /// the minimum price the seller will accept, per unit
+        reserve_price: Balance,
+        /// the number of units on sale
+        units: u32,
+        /// the sealed and revealed bids
+        bids: SealedBids,
+        /// the stage of the auction's lifecycle
+        status: AuctionStatus,
+        /// the outcome of the auction, once complete
+        outcome: Option<AuctionOutcome<AccountId, Balance>>,
+        /// participants whose bids were not revealed when the auction completed
+        invalid_bids: Vec<AccountId>,
+    }
+
+    impl UniformPriceAuction {
+        /// Constructor that initializes a new auction
+        ///
+        /// * `proxy`: the proxy (contract) which relays bids
+        /// * `reserve_price`: the minimum price the seller will accept, per unit
+        /// * `units`: the number of units on sale
+        ///
+        #[ink(constructor)]
+        pub fn new(proxy: AccountId, reserve_price: Balance, units: u32) -> Self {
+            Self {
+                proxy: Ownable::new(proxy),
+                reserve_price,
+                units,
+                bids: SealedBids::default(),
+                status: AuctionStatus::Open,
+                outcome: None,
+                invalid_bids: Vec::new(),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_reserve_price(&self) -> Balance {
+            self.reserve_price
+        }
+
+        #[ink(message)]
+        pub fn get_units(&self) -> u32 {
+            self.units
+        }
+
+        /// get the revealed bids (empty until post-auction completion)
+        #[ink(message)]
+        pub fn get_revealed_bids(&self) -> Vec<RevealedBid<AccountId>> {
+            self.bids.revealed_bids().to_vec()
+        }
+
+        fn ensure_proxy(&self) -> Result<(), Error> {
+            self.proxy
+                .ensure_owner(self.env().caller())
+                .map_err(Error::from)
+        }
+    }
+
+    impl Auction for UniformPriceAuction {
+        #[ink(message)]
+        fn get_kind(&self) -> AuctionKind {
+            AuctionKind::UniformPrice
+        }
+
+        #[ink(message)]
+        fn get_status(&self) -> AuctionStatus {
+            self.status
+        }
+
+        /// seal a bid for one unit, committed to with `bid_commitment`
+        /// bidding again replaces the sealed bid
+        #[ink(message)]
+        fn bid(&mut self, bidder: AccountId, bid: BidPayload) -> Result<(), Error> {
+            self.ensure_proxy()?;
+            if self.status != AuctionStatus::Open {
+                return Err(Error::AuctionClosed);
+            }
+            let BidPayload::Sealed(sealed_bid) = bid else {
+                return Err(Error::UnsupportedBid);
+            };
+            self.bids.seal(bidder, sealed_bid)
+        }
+
+        #[ink(message)]
+        fn reveal_bid(&mut self, revealed_bid: RevealedBid<AccountId>) -> Result<(), Error> {
+            self.ensure_proxy()?;
+            if !matches!(self.status, AuctionStatus::Open | AuctionStatus::RevealPhase) {
+                return Err(Error::RevealPhaseClosed);
+            }
+            self.bids.reveal(revealed_bid)?;
+            self.status = AuctionStatus::RevealPhase;
+            Ok(())
+        }
+
+        /// Complete the auction
+        ///
+        /// The highest `units` bids at or above the reserve price win, highest first.
+        /// Each winner pays the highest losing bid, but never less than the reserve price.
+        /// Ties are broken with `pulse`. Bids which were never
+        /// revealed are marked invalid. Units which are not won remain unsold.
+        ///
+        /// * `pulse`: the drand pulse which breaks ties
+        ///
+        #[ink(message)]
+        fn complete(&mut self, pulse: [u8; 32]) -> Result<(), Error> {
+            self.ensure_proxy()?;
+            if !self.status.can_become(AuctionStatus::Settled) {
+                return Err(Error::AlreadyComplete);
+            }
+            self.status = AuctionStatus::Settled;
+            self.invalid_bids = self.bids.unrevealed();
+            let ranked = sealed::rank(self.bids.revealed_bids(), self.reserve_price, pulse);
+            let units = self.units as usize;
+            let price = ranked
+                .get(units)
+                .map(|b| b.bid)
+                .unwrap_or(0)
+                .max(self.reserve_price);
+            let results = ranked
+                .iter()
+                .take(units)
+                .map(|b| AuctionResult {
+                    winner: b.bidder,
+                    debt: price,
+                })
+                .collect::<Vec<_>>();
+            self.outcome = Some(if results.is_empty() {
+                AuctionOutcome::NoSale
+            } else {
+                AuctionOutcome::Sale(results)
+            });
+            Ok(())
+        }
+
+        /// withdraw the auction, while it is open
+        #[ink(message)]
+        fn cancel(&mut self) -> Result<(), Error> {
+            self.ensure_proxy()?;
+            if !self.status.can_become(AuctionStatus::Cancelled) {
+                return Err(Error::AuctionClosed);
+            }
+            self.status = AuctionStatus::Cancelled;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn get_outcome(&self) -> Option<AuctionOutcome<AccountId, Balance>> {
+            self.outcome.clone()
+        }
+
+        #[ink(message)]
+        fn get_invalid_bids(&self) -> Vec<AccountId> {
+            self.invalid_bids.clone()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use auction_traits::{bid_commitment, TlockMessage};
+
+        #[ink::test]
+        fn winners_pay_the_highest_losing_bid() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = UniformPriceAuction::new(accounts.alice, 1, 2);
+            place(
+                &mut auction,
+                &[
+                    (accounts.bob, 3),
+                    (accounts.charlie, 7),
+                    (accounts.django, 5),
+                    (accounts.eve, 4),
+                ],
+            );
+            assert_eq!(auction.complete([0; 32]), Ok(()));
+            assert_eq!(
+                auction.get_outcome(),
+                Some(AuctionOutcome::Sale(vec![
+                    AuctionResult {
+                        winner: accounts.charlie,
+                        debt: 4
+                    },
+                    AuctionResult {
+                        winner: accounts.django,
+                        debt: 4
+                    },
+                ]))
+            );
+        }
+
+        #[ink::test]
+        fn price_is_clamped_at_reserve_when_units_remain() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = UniformPriceAuction::new(accounts.alice, 5, 3);
+            place(
+                &mut auction,
+                &[(accounts.bob, 6), (accounts.charlie, 9), (accounts.django, 2)],
+            );
+            assert_eq!(auction.complete([0; 32]), Ok(()));
+            // django's bid is below the reserve, so only two of the three units sell
+            assert_eq!(
+                auction.get_outcome(),
+                Some(AuctionOutcome::Sale(vec![
+                    AuctionResult {
+                        winner: accounts.charlie,
+                        debt: 5
+                    },
+                    AuctionResult {
+                        winner: accounts.bob,
+                        debt: 5
+                    },
+                ]))
+            );
+        }
+
+        #[ink::test]
+        fn no_bids_above_reserve_is_no_sale() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = UniformPriceAuction::new(accounts.alice, 10, 2);
+            place(&mut auction, &[(accounts.bob, 6)]);
+            assert_eq!(auction.complete([0; 32]), Ok(()));
+            assert_eq!(auction.get_outcome(), Some(AuctionOutcome::NoSale));
+        }
+
+        /// seal every bid, then reveal them, with a dummy ciphertext and a real commitment
+        fn place(auction: &mut UniformPriceAuction, bids: &[(AccountId, u128)]) {
+            for (bidder, bid) in bids.iter().copied() {
+                let salt = [bid as u8; 32];
+                let mut sealed_bid = TlockMessage {
+                    ciphertext: vec![1; 32],
+                    nonce: vec![2; 12],
+                    capsule: vec![3; 32],
+                    commitment: Vec::new(),
+                };
+                sealed_bid.commitment = bid_commitment(&bidder, bid, &salt, &sealed_bid).to_vec();
+                assert_eq!(auction.bid(bidder, BidPayload::Sealed(sealed_bid)), Ok(()));
+            }
+            for (bidder, bid) in bids.iter().copied() {
+                let salt = [bid as u8; 32];
+                assert_eq!(auction.reveal_bid(RevealedBid { bidder, bid, salt }), Ok(()));
+            }
+        }
+    }
+}
@@ -1,283 +1,580 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 pub use self::vickrey_auction::{VickreyAuction, VickreyAuctionRef};
+pub use auction_traits::{
+    bid_commitment, AuctionOutcome, AuctionResult, BidPayload, RevealedBid, TlockMessage,
+};
 use ink::prelude::vec::Vec;
-/// The result of an auction
-#[derive(Clone, Debug, scale::Decode, scale::Encode, PartialEq)]
-#[cfg_attr(
-    feature = "std",
-    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
-)]
-pub struct AuctionResult<AccountId, Balance> {
-    pub winner: AccountId,
-    pub debt: Balance,
-}
-
-/// A custom type for storing revealed bids
-#[derive(Clone, Debug, scale::Decode, scale::Encode, PartialEq)]
-#[cfg_attr(
-    feature = "std",
-    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
-)]
-pub struct RevealedBid<AccountId> {
-    /// the bidder
-    bidder: AccountId,
-    /// the (supposedly) revealed amount they bid
-    bid: u128,
-}
 
-use etf_contract_utils::ext::EtfEnvironment;
+use idl_contract_extension::ext::DrandEnvironment;
 
-#[ink::contract(env = EtfEnvironment)]
+#[ink::contract(env = DrandEnvironment)]
 mod vickrey_auction {
-    use crate::{AuctionResult, EtfEnvironment, RevealedBid, Vec};
-
-    #[derive(PartialEq, Debug, scale::Decode, scale::Encode)]
-    #[cfg_attr(
-        feature = "std",
-        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
-    )]
-    pub enum Error {
-        /// the origin must match the configured proxy
-        NotProxy,
-        WaitingReveals,
-        NotParticipant,
-    }
+    use crate::{AuctionOutcome, AuctionResult, BidPayload, DrandEnvironment, RevealedBid, TlockMessage, Vec};
+    use idl_contract_extension::{access::Ownable, psp34::Id};
+    use ink::prelude::vec;
+    use auction_traits::{
+        sealed::{self, SealedBids},
+        Auction, AuctionKind, AuctionStatus,
+    };
+
+    pub use auction_traits::AuctionError as Error;
 
     /// the auction storage
     #[ink(storage)]
     pub struct VickreyAuction {
         /// the proxy (contract)
-        proxy: AccountId,
+        proxy: Ownable,
         /// the item being auctioned
         asset_id: AssetId,
-        /// ink mapping has no support for iteration so we need to loop over this vec to read through the proposals
-        /// but maybe could do a struct instead? (acctid, vec, vec, vec)
-        participants: Vec<AccountId>,
-        /// the participant who won and how much they owe
-        winner: Option<AuctionResult<AccountId, Balance>>,
-        /// the decrypted proposals
-        revealed_bids: Vec<RevealedBid<AccountId>>,
+        /// the minimum price the seller will accept
+        reserve_price: Balance,
+        /// the sealed and revealed bids
+        bids: SealedBids,
+        /// the stage of the auction's lifecycle
+        status: AuctionStatus,
+        /// the outcome of the auction, once complete
+        outcome: Option<AuctionOutcome<AccountId, Balance>>,
+        /// participants whose bids were not revealed when the auction completed
+        invalid_bids: Vec<AccountId>,
     }
 
-    /// A proposal has been accepted
-    #[ink(event)]
-    pub struct BidSuccess {}
-
-    /// A bid has been executed
-    #[ink(event)]
-    pub struct BidComplete {
-        pub winner: bool,
-    }
-
-    /// the nft (ERC721) asset id type
-    type AssetId = u32;
+    /// the nft (PSP34) asset id type
+    type AssetId = Id;
 
     impl VickreyAuction {
         /// Constructor that initializes a new auction
+        ///
+        /// * `proxy`: the proxy (contract) which relays bids
+        /// * `asset_id`: the item being auctioned
+        /// * `reserve_price`: the minimum price the seller will accept
+        ///
         #[ink(constructor)]
-        pub fn new(proxy: AccountId, asset_id: u32) -> Self {
-            let participants: Vec<AccountId> = Vec::new();
-            let revealed_bids: Vec<RevealedBid<AccountId>> = Vec::new();
-
+        pub fn new(proxy: AccountId, asset_id: AssetId, reserve_price: Balance) -> Self {
             Self {
-                proxy,
+                proxy: Ownable::new(proxy),
                 asset_id,
-                participants,
-                winner: None,
-                revealed_bids,
+                reserve_price,
+                bids: SealedBids::default(),
+                status: AuctionStatus::Open,
+                outcome: None,
+                invalid_bids: Vec::new(),
             }
         }
 
         /// get the version of the contract
         #[ink(message)]
         pub fn get_asset_id(&self) -> AssetId {
-            self.asset_id
+            self.asset_id.clone()
         }
 
         #[ink(message)]
         pub fn get_proxy(&self) -> AccountId {
-            self.proxy
+            self.proxy.owner()
+        }
+
+        #[ink(message)]
+        pub fn get_reserve_price(&self) -> Balance {
+            self.reserve_price
         }
 
+        /// get the winner and their debt, if the asset was sold
         #[ink(message)]
         pub fn get_winner(&self) -> Option<AuctionResult<AccountId, Balance>> {
-            self.winner.clone()
+            match &self.outcome {
+                Some(AuctionOutcome::Sale(results)) => results.first().cloned(),
+                _ => None,
+            }
         }
 
         /// get participants
         #[ink(message)]
         pub fn get_participants(&self) -> Vec<AccountId> {
-            self.participants.clone()
+            self.bids.participants().to_vec()
         }
 
         /// get the revealed bids (empty until post-auction completion)
         #[ink(message)]
         pub fn get_revealed_bids(&self) -> Vec<RevealedBid<AccountId>> {
-            self.revealed_bids.clone()
+            self.bids.revealed_bids().to_vec()
+        }
+
+        /// get the sealed bid placed by a participant
+        #[ink(message)]
+        pub fn get_sealed_bid(&self, bidder: AccountId) -> Option<TlockMessage> {
+            self.bids.sealed_bid(bidder)
+        }
+
+        fn ensure_proxy(&self) -> Result<(), Error> {
+            self.proxy
+                .ensure_owner(self.env().caller())
+                .map_err(Error::from)
+        }
+    }
+
+    impl Auction for VickreyAuction {
+        #[ink(message)]
+        fn get_kind(&self) -> AuctionKind {
+            AuctionKind::Vickrey
+        }
+
+        #[ink(message)]
+        fn get_status(&self) -> AuctionStatus {
+            self.status
         }
 
         /// add a proposal to an active auction during the bidding phase
-        /// a proposal is a signed, timelocked bid
+        /// a proposal is a sealed bid, committed to with `bid_commitment`.
+        /// Bidding again replaces the sealed bid.
         ///
         /// * `bidder`: the account bidding
+        /// * `bid`: the sealed bid
         ///
         #[ink(message)]
-        pub fn bid(
-            &mut self,
-            bidder: AccountId,
-        ) -> Result<(), Error> {
-            let who = self.env().caller();
-            if who != self.proxy {
-                return Err(Error::NotProxy);
+        fn bid(&mut self, bidder: AccountId, bid: BidPayload) -> Result<(), Error> {
+            self.ensure_proxy()?;
+            if self.status != AuctionStatus::Open {
+                return Err(Error::AuctionClosed);
             }
+            let BidPayload::Sealed(sealed_bid) = bid else {
+                return Err(Error::UnsupportedBid);
+            };
+            self.bids.seal(bidder, sealed_bid)
+        }
 
-            if !self.participants.contains(&bidder.clone()) {
-                self.participants.push(bidder);
+        /// Takes de incoming reveled bid and saves it in the revealed bids
+        /// if it opens the commitment of the bidder's sealed bid
+        ///
+        /// * `revealed_bid`: the revealed bid
+        ///
+        #[ink(message)]
+        fn reveal_bid(&mut self, revealed_bid: RevealedBid<AccountId>) -> Result<(), Error> {
+            self.ensure_proxy()?;
+            if !matches!(self.status, AuctionStatus::Open | AuctionStatus::RevealPhase) {
+                return Err(Error::RevealPhaseClosed);
             }
-
-            Self::env().emit_event(BidSuccess {});
+            self.bids.reveal(revealed_bid)?;
+            self.status = AuctionStatus::RevealPhase;
             Ok(())
         }
 
-        /// Takes de incoming reveled bid and saves it in the revealed_bids array
+        /// Complete the auction, callable only by the proxy once the reveal deadline passed
+        /// Checks the revealed bids and determines the winner.
+        /// Bids which were never revealed are marked invalid.
         ///
-        /// * `revealed_bid`: the revealed bid
+        /// Only bids at or above the reserve price can win. The winner pays the
+        /// second highest bid, but never less than the reserve price. Ties for the
+        /// highest bid are broken with `pulse`, and the winner pays the tied bid.
+        /// If no bid meets the reserve price there is no sale.
+        ///
+        /// * `pulse`: the drand pulse which breaks ties
         ///
         #[ink(message)]
-        pub fn save_revealed_bid(
-            &mut self,
-            revealed_bid: RevealedBid<AccountId>,
-        ) -> Result<(), Error> {
-            let who = self.env().caller();
-            if who != self.proxy {
-                return Err(Error::NotProxy);
-            }
-            if !self.participants.contains(&revealed_bid.bidder.clone()) {
-                return Err(Error::NotParticipant);
+        fn complete(&mut self, pulse: [u8; 32]) -> Result<(), Error> {
+            self.ensure_proxy()?;
+            if !self.status.can_become(AuctionStatus::Settled) {
+                return Err(Error::AlreadyComplete);
             }
-            self.revealed_bids.push(RevealedBid {
-                bidder: revealed_bid.bidder,
-                bid: revealed_bid.bid,
+            self.status = AuctionStatus::Settled;
+            self.invalid_bids = self.bids.unrevealed();
+            let ranked = sealed::rank(self.bids.revealed_bids(), self.reserve_price, pulse);
+            self.outcome = Some(match ranked.first() {
+                Some(highest) => {
+                    let second_highest_bid = ranked.get(1).map(|b| b.bid).unwrap_or(0);
+                    AuctionOutcome::Sale(vec![AuctionResult {
+                        winner: highest.bidder,
+                        debt: second_highest_bid.max(self.reserve_price),
+                    }])
+                }
+                None => AuctionOutcome::NoSale,
             });
             Ok(())
         }
 
-        /// Complete the auction
-        /// Checks the revealed bids and determines the winner
-        ///
+        /// withdraw the auction, while it is open
         #[ink(message)]
-        pub fn complete(&mut self) -> Result<(), Error> {
-            let mut highest_bid: u128 = 0;
-            let mut second_highest_bid: u128 = 0;
-            let mut winner: Option<AccountId> = None;
-            for bid in self.revealed_bids.iter() {
-                let bidder = bid.bidder;
-                let b = bid.bid;
-                if b > highest_bid {
-                    second_highest_bid = highest_bid;
-                    highest_bid = b;
-                    winner = Some(bidder);
-                } else if b > second_highest_bid {
-                    second_highest_bid = b;
-                }
-            }
-            if let Some(w) = winner {
-                self.winner = Some(AuctionResult {
-                    winner: w,
-                    debt: second_highest_bid,
-                });
+        fn cancel(&mut self) -> Result<(), Error> {
+            self.ensure_proxy()?;
+            if !self.status.can_become(AuctionStatus::Cancelled) {
+                return Err(Error::AuctionClosed);
             }
+            self.status = AuctionStatus::Cancelled;
             Ok(())
         }
+
+        #[ink(message)]
+        fn get_outcome(&self) -> Option<AuctionOutcome<AccountId, Balance>> {
+            self.outcome.clone()
+        }
+
+        #[ink(message)]
+        fn get_invalid_bids(&self) -> Vec<AccountId> {
+            self.invalid_bids.clone()
+        }
     }
 
     #[cfg(test)]
     mod tests {
         use super::*;
+        use crate::bid_commitment;
 
         #[ink::test]
         fn bid_success() {
-            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            let mut auction = VickreyAuction::new(accounts.alice, 1u32);
-            let res = auction.bid(accounts.alice);
-            assert!(!res.is_err());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 0);
+            let (sealed_bid, _) = seal(accounts.alice, 4);
+            let res = auction.bid(accounts.alice, BidPayload::Sealed(sealed_bid.clone()));
+            assert!(res.is_ok());
 
-            let participants = auction.participants;
-            assert_eq!(participants.clone().len(), 1);
+            let participants = auction.get_participants();
+            assert_eq!(participants.len(), 1);
+            assert_eq!(auction.get_sealed_bid(accounts.alice), Some(sealed_bid));
         }
 
         #[ink::test]
         fn bid_fails_when_not_proxy() {
-            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            let mut auction = VickreyAuction::new(accounts.alice, 1u32);
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 0);
             ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
-            let res = auction.bid(accounts.alice);
+            let res = auction.bid(accounts.alice, BidPayload::Sealed(seal(accounts.alice, 4).0));
             assert!(res.is_err());
             assert_eq!(res, Err(Error::NotProxy));
         }
 
+        #[ink::test]
+        fn bid_fails_with_invalid_commitment() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 0);
+            let (mut sealed_bid, _) = seal(accounts.alice, 4);
+            sealed_bid.commitment = vec![1; 16];
+            let res = auction.bid(accounts.alice, BidPayload::Sealed(sealed_bid));
+            assert_eq!(res, Err(Error::InvalidCommitment));
+            assert!(auction.get_participants().is_empty());
+        }
+
+        #[ink::test]
+        fn rebid_replaces_sealed_bid() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 0);
+            let _ = auction.bid(accounts.bob, BidPayload::Sealed(seal(accounts.bob, 4).0));
+            let (sealed_bid, revealed_bid) = seal(accounts.bob, 5);
+            let _ = auction.bid(accounts.bob, BidPayload::Sealed(sealed_bid.clone()));
+            assert_eq!(auction.get_participants().len(), 1);
+            assert_eq!(auction.get_sealed_bid(accounts.bob), Some(sealed_bid));
+            // only the latest bid can be revealed
+            assert_eq!(
+                auction.reveal_bid(seal(accounts.bob, 4).1),
+                Err(Error::CommitmentMismatch)
+            );
+            assert_eq!(auction.reveal_bid(revealed_bid), Ok(()));
+        }
+
+        #[ink::test]
+        fn reveal_fails_when_not_participant() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 0);
+            let res = auction.reveal_bid(seal(accounts.bob, 4).1);
+            assert_eq!(res, Err(Error::NotParticipant));
+        }
+
+        #[ink::test]
+        fn reveal_fails_twice() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 0);
+            let (sealed_bid, revealed_bid) = seal(accounts.bob, 4);
+            let _ = auction.bid(accounts.bob, BidPayload::Sealed(sealed_bid));
+            assert_eq!(auction.reveal_bid(revealed_bid.clone()), Ok(()));
+            assert_eq!(auction.reveal_bid(revealed_bid.clone()), Err(Error::AlreadyRevealed));
+            assert_eq!(auction.get_revealed_bids(), vec![revealed_bid]);
+        }
+
+        #[ink::test]
+        fn complete_fails_when_not_proxy() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 0);
+            let _ = auction.bid(accounts.bob, BidPayload::Sealed(seal(accounts.bob, 4).0));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(auction.complete([0; 32]), Err(Error::NotProxy));
+            assert_eq!(auction.get_status(), AuctionStatus::Open);
+            assert_eq!(auction.get_outcome(), None);
+        }
+
+        #[ink::test]
+        fn reveal_fails_when_commitment_not_opened() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 0);
+            let (sealed_bid, mut revealed_bid) = seal(accounts.bob, 4);
+            let _ = auction.bid(accounts.bob, BidPayload::Sealed(sealed_bid));
+            // claim a higher bid than was committed to
+            revealed_bid.bid = 100;
+            let res = auction.reveal_bid(revealed_bid);
+            assert_eq!(res, Err(Error::CommitmentMismatch));
+            assert!(auction.get_revealed_bids().is_empty());
+        }
+
+        #[ink::test]
+        fn reveal_fails_when_ciphertext_not_committed_to() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 0);
+            let (mut sealed_bid, revealed_bid) = seal(accounts.bob, 4);
+            // swap in a ciphertext which was not committed to
+            sealed_bid.ciphertext = vec![4; 32];
+            let _ = auction.bid(accounts.bob, BidPayload::Sealed(sealed_bid));
+            let res = auction.reveal_bid(revealed_bid);
+            assert_eq!(res, Err(Error::CommitmentMismatch));
+            assert!(auction.get_revealed_bids().is_empty());
+        }
+
         #[ink::test]
         fn complete_auction_success_single_participant() {
-            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            let mut auction = VickreyAuction::new(accounts.alice, 1u32);
-
-            let res = auction.bid(accounts.alice);
-            assert!(!res.is_err());
-            let revealed_bid = RevealedBid {
-                bidder: accounts.alice,
-                bid: 4,
-            };
-            let _res = auction.save_revealed_bid(revealed_bid.clone());
-            assert_eq!(auction.revealed_bids[0], revealed_bid);
-            let res = auction.complete();
-            assert!(!res.is_err());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 0);
+
+            let (sealed_bid, revealed_bid) = seal(accounts.alice, 4);
+            let res = auction.bid(accounts.alice, BidPayload::Sealed(sealed_bid));
+            assert!(res.is_ok());
+            let _res = auction.reveal_bid(revealed_bid.clone());
+            assert_eq!(auction.get_revealed_bids()[0], revealed_bid);
+            let res = auction.complete([0; 32]);
+            assert!(res.is_ok());
             assert_eq!(
-                auction.winner,
+                auction.get_winner(),
                 Some(AuctionResult {
                     winner: accounts.alice,
                     debt: 0
                 })
-            )
+            );
+            assert!(auction.get_invalid_bids().is_empty());
         }
 
         #[ink::test]
         fn complete_auction_success_many_participants_all_valid() {
-            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            let mut auction = VickreyAuction::new(accounts.alice, 1u32);
-            let _ = auction.bid(accounts.alice);
-            let _ = auction.bid(accounts.bob);
-            let _ = auction.bid(accounts.charlie);
-            let revealed_bids = vec![
-                RevealedBid {
-                    bidder: accounts.alice,
-                    bid: 1,
-                },
-                RevealedBid {
-                    bidder: accounts.bob,
-                    bid: 3,
-                },
-                RevealedBid {
-                    bidder: accounts.charlie,
-                    bid: 2,
-                },
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 0);
+            let bids = [
+                seal(accounts.alice, 1),
+                seal(accounts.bob, 3),
+                seal(accounts.charlie, 2),
             ];
-            revealed_bids.iter().for_each(|bid| {
-                let res = auction.save_revealed_bid(bid.clone());
+            bids.iter().for_each(|(sealed_bid, revealed_bid)| {
+                let _ = auction.bid(revealed_bid.bidder, BidPayload::Sealed(sealed_bid.clone()));
+            });
+            bids.iter().for_each(|(_, revealed_bid)| {
+                let res = auction.reveal_bid(revealed_bid.clone());
                 assert!(res.is_ok());
             });
-            let res = auction.complete();
+            let res = auction.complete([0; 32]);
             assert!(res.is_ok());
-            assert_eq!(auction.revealed_bids[0], revealed_bids[0].clone());
-            assert_eq!(auction.revealed_bids[1], revealed_bids[1].clone());
-            assert_eq!(auction.revealed_bids[2], revealed_bids[2]);
-            // bob placed the highest bid and pays alice's bid
+            assert_eq!(auction.get_revealed_bids()[0], bids[0].1.clone());
+            assert_eq!(auction.get_revealed_bids()[1], bids[1].1.clone());
+            assert_eq!(auction.get_revealed_bids()[2], bids[2].1);
+            // bob placed the highest bid and pays charlie's bid
             assert_eq!(
-                auction.winner,
+                auction.get_winner(),
                 Some(AuctionResult {
                     winner: accounts.bob,
                     debt: 2
                 })
-            )
+            );
+            assert!(auction.get_invalid_bids().is_empty());
+        }
+
+        #[ink::test]
+        fn complete_auction_marks_unrevealed_bids_invalid() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 0);
+            let bids = [
+                seal(accounts.alice, 1),
+                seal(accounts.bob, 3),
+                seal(accounts.charlie, 2),
+            ];
+            bids.iter().for_each(|(sealed_bid, revealed_bid)| {
+                let _ = auction.bid(revealed_bid.bidder, BidPayload::Sealed(sealed_bid.clone()));
+            });
+            // bob's bid is never revealed
+            let _ = auction.reveal_bid(bids[0].1.clone());
+            let _ = auction.reveal_bid(bids[2].1.clone());
+            let res = auction.complete([0; 32]);
+            assert!(res.is_ok());
+            assert_eq!(auction.get_invalid_bids(), vec![accounts.bob]);
+            // charlie placed the highest valid bid and pays alice's bid
+            assert_eq!(
+                auction.get_winner(),
+                Some(AuctionResult {
+                    winner: accounts.charlie,
+                    debt: 1
+                })
+            );
+        }
+
+        #[ink::test]
+        fn complete_auction_clamps_debt_at_reserve_price() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 5);
+            let bids = [seal(accounts.bob, 10), seal(accounts.charlie, 2)];
+            bids.iter().for_each(|(sealed_bid, revealed_bid)| {
+                let _ = auction.bid(revealed_bid.bidder, BidPayload::Sealed(sealed_bid.clone()));
+            });
+            bids.iter().for_each(|(_, revealed_bid)| {
+                let _ = auction.reveal_bid(revealed_bid.clone());
+            });
+            assert!(auction.complete([0; 32]).is_ok());
+            // charlie's bid is below the reserve, so bob pays the reserve price
+            assert_eq!(
+                auction.get_outcome(),
+                Some(AuctionOutcome::Sale(vec![AuctionResult {
+                    winner: accounts.bob,
+                    debt: 5
+                }]))
+            );
+        }
+
+        #[ink::test]
+        fn complete_auction_without_bids_is_no_sale() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 0);
+            assert_eq!(auction.get_outcome(), None);
+            assert!(auction.complete([0; 32]).is_ok());
+            assert_eq!(auction.get_outcome(), Some(AuctionOutcome::NoSale));
+            assert_eq!(auction.get_winner(), None);
+        }
+
+        #[ink::test]
+        fn complete_auction_below_reserve_price_is_no_sale() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 100);
+            let bids = [seal(accounts.bob, 10), seal(accounts.charlie, 99)];
+            bids.iter().for_each(|(sealed_bid, revealed_bid)| {
+                let _ = auction.bid(revealed_bid.bidder, BidPayload::Sealed(sealed_bid.clone()));
+            });
+            bids.iter().for_each(|(_, revealed_bid)| {
+                let _ = auction.reveal_bid(revealed_bid.clone());
+            });
+            assert!(auction.complete([0; 32]).is_ok());
+            assert_eq!(auction.get_outcome(), Some(AuctionOutcome::NoSale));
+        }
+
+        #[ink::test]
+        fn complete_auction_breaks_ties_with_pulse() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 0);
+            let bids = [
+                seal(accounts.alice, 1),
+                seal(accounts.bob, 3),
+                seal(accounts.charlie, 3),
+            ];
+            bids.iter().for_each(|(sealed_bid, revealed_bid)| {
+                let _ = auction.bid(revealed_bid.bidder, BidPayload::Sealed(sealed_bid.clone()));
+            });
+            bids.iter().for_each(|(_, revealed_bid)| {
+                let _ = auction.reveal_bid(revealed_bid.clone());
+            });
+            // the pulse selects the second of the tied bidders
+            assert!(auction.complete(pulse(1)).is_ok());
+            // charlie wins the tie and pays the tied bid
+            assert_eq!(
+                auction.get_winner(),
+                Some(AuctionResult {
+                    winner: accounts.charlie,
+                    debt: 3
+                })
+            );
+        }
+
+        #[ink::test]
+        fn complete_auction_tie_depends_on_pulse() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            for (first_byte, winner) in [(2, accounts.bob), (3, accounts.charlie)] {
+                let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 0);
+                let bids = [seal(accounts.bob, 3), seal(accounts.charlie, 3)];
+                bids.iter().for_each(|(sealed_bid, revealed_bid)| {
+                    let bid = BidPayload::Sealed(sealed_bid.clone());
+                    let _ = auction.bid(revealed_bid.bidder, bid);
+                });
+                bids.iter().for_each(|(_, revealed_bid)| {
+                    let _ = auction.reveal_bid(revealed_bid.clone());
+                });
+                assert!(auction.complete(pulse(first_byte)).is_ok());
+                assert_eq!(auction.get_winner().map(|r| r.winner), Some(winner));
+            }
+        }
+
+        #[ink::test]
+        fn lifecycle_moves_from_open_to_settled() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 0);
+            assert_eq!(auction.get_status(), AuctionStatus::Open);
+            let (sealed_bid, revealed_bid) = seal(accounts.bob, 4);
+            let _ = auction.bid(accounts.bob, BidPayload::Sealed(sealed_bid));
+            assert_eq!(auction.get_status(), AuctionStatus::Open);
+            // a failed reveal does not close bidding
+            assert!(auction.reveal_bid(seal(accounts.bob, 5).1).is_err());
+            assert_eq!(auction.get_status(), AuctionStatus::Open);
+            assert_eq!(auction.reveal_bid(revealed_bid), Ok(()));
+            assert_eq!(auction.get_status(), AuctionStatus::RevealPhase);
+            assert_eq!(auction.complete([0; 32]), Ok(()));
+            assert_eq!(auction.get_status(), AuctionStatus::Settled);
+        }
+
+        #[ink::test]
+        fn bid_fails_once_reveals_start() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 0);
+            let (sealed_bid, revealed_bid) = seal(accounts.bob, 4);
+            let _ = auction.bid(accounts.bob, BidPayload::Sealed(sealed_bid));
+            let _ = auction.reveal_bid(revealed_bid);
+            let res = auction.bid(accounts.charlie, BidPayload::Sealed(seal(accounts.charlie, 5).0));
+            assert_eq!(res, Err(Error::AuctionClosed));
+        }
+
+        #[ink::test]
+        fn complete_fails_twice_and_closes_reveals() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 0);
+            let (sealed_bid, revealed_bid) = seal(accounts.bob, 4);
+            let _ = auction.bid(accounts.bob, BidPayload::Sealed(sealed_bid));
+            assert_eq!(auction.complete([0; 32]), Ok(()));
+            assert_eq!(auction.complete([0; 32]), Err(Error::AlreadyComplete));
+            assert_eq!(auction.reveal_bid(revealed_bid), Err(Error::RevealPhaseClosed));
+            assert_eq!(
+                auction.bid(accounts.bob, BidPayload::Sealed(seal(accounts.bob, 5).0)),
+                Err(Error::AuctionClosed)
+            );
+            // bob's bid was never revealed
+            assert_eq!(auction.get_invalid_bids(), vec![accounts.bob]);
+        }
+
+        #[ink::test]
+        fn cancel_closes_the_auction() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut auction = VickreyAuction::new(accounts.alice, Id::U32(1), 0);
+            let (sealed_bid, revealed_bid) = seal(accounts.bob, 4);
+            let _ = auction.bid(accounts.bob, BidPayload::Sealed(sealed_bid));
+            assert_eq!(auction.cancel(), Ok(()));
+            assert_eq!(auction.get_status(), AuctionStatus::Cancelled);
+            assert_eq!(auction.cancel(), Err(Error::AuctionClosed));
+            assert_eq!(auction.reveal_bid(revealed_bid), Err(Error::RevealPhaseClosed));
+            assert_eq!(auction.complete([0; 32]), Err(Error::AlreadyComplete));
+            assert_eq!(auction.get_outcome(), None);
+        }
+
+        /// a pulse starting with `first_byte`
+        fn pulse(first_byte: u8) -> [u8; 32] {
+            let mut pulse = [0u8; 32];
+            pulse[0] = first_byte;
+            pulse
+        }
+
+        /// seal a bid with a dummy ciphertext and a real commitment
+        fn seal(bidder: AccountId, bid: u128) -> (TlockMessage, RevealedBid<AccountId>) {
+            let salt = [bid as u8; 32];
+            let mut sealed_bid = TlockMessage {
+                ciphertext: vec![1; 32],
+                nonce: vec![2; 12],
+                capsule: vec![3; 32],
+                commitment: Vec::new(),
+            };
+            sealed_bid.commitment = bid_commitment(&bidder, bid, &salt, &sealed_bid).to_vec();
+            (sealed_bid, RevealedBid { bidder, bid, salt })
         }
     }
 }
@@ -1,22 +1,43 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 
-use etf_contract_utils::ext::EtfEnvironment;
-
-#[ink::contract(env = EtfEnvironment)]
+use idl_contract_extension::ext::DrandEnvironment;
+pub use self::transmutation::{
+    Asset,
+    Error,
+    Swap,
+    SwapOutcome,
+    Transmutation,
+    TransmutationRef,
+};
+
+#[ink::contract(env = DrandEnvironment)]
 mod transmutation {
 
-    use crate::EtfEnvironment;
+    use crate::DrandEnvironment;
 
+    use idl_contract_extension::psp34::{Id, PSP34TransferFrom, PSP34};
+    use ink::env::hash::{HashOutput, Sha2x256};
     use ink::prelude::vec::Vec;
     use ink::storage::Mapping;
-    use rs_merkle::{
-        algorithms::Sha256,
-        Hasher,
-        MerkleTree,
-    };
 
-    /// a dummy type to represent an asset
-    pub type OpaqueAssetId = Vec<u8>;
+    /// the PSP34 (e.g. ERC721) collection of a swapped token
+    type CollectionRef = ink::contract_ref!(PSP34, DrandEnvironment);
+
+    /// the same collection, to move a token from a named owner
+    type TransferFromRef = ink::contract_ref!(PSP34TransferFrom, DrandEnvironment);
+
+    /// a token of a PSP34 collection
+    #[derive(Clone, PartialEq, Debug, scale::Decode, scale::Encode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Asset {
+        /// the address of the collection
+        pub collection: AccountId,
+        /// the id of the token in the collection
+        pub id: Id,
+    }
 
     /// represents a swap between two participants
     #[derive(PartialEq, Debug, scale::Decode, scale::Encode)]
@@ -25,10 +46,30 @@ mod transmutation {
         derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
     )]
     pub struct Swap {
-        asset_id_one: OpaqueAssetId,
-        asset_id_two: OpaqueAssetId,
+        /// the participant who proposed the swap
+        pub owner_one: AccountId,
+        /// the token the proposer gives
+        pub asset_id_one: Asset,
+        /// the participant the swap was proposed to
+        pub owner_two: AccountId,
+        /// the token the proposer receives
+        pub asset_id_two: Asset,
         /// the deadline when the swap must complete
-        deadline: BlockNumber,
+        pub deadline: BlockNumber,
+        /// whether the contract holds the token of the first participant
+        pub escrowed_one: bool,
+        /// whether the contract holds the token of the second participant
+        pub escrowed_two: bool,
+    }
+
+    /// how a swap ended
+    #[derive(PartialEq, Debug, scale::Decode, scale::Encode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum SwapOutcome {
+        /// both tokens were escrowed, each participant received the other's token
+        Swapped,
+        /// a participant did not transmute, escrowed tokens went back to their owners
+        Refunded,
     }
 
     #[derive(PartialEq, Debug, scale::Decode, scale::Encode)]
@@ -38,26 +79,28 @@ mod transmutation {
     )]
     pub enum Error {
         InvalidBlockNumber,
-        InvalidMerkleTree,
         SwapDNE,
-        DuplicateSeed,
         InvalidSwap,
-        NoOwnedAsset,
-        NoSuchAsset,
+        /// a participant already has a pending swap
+        SwapExists,
+        /// the caller is not a participant of a pending swap
+        NotParticipant,
+        /// the caller's token is already held by the contract
+        AlreadyEscrowed,
+        /// the caller does not own the token they would escrow
+        NotOwner,
+        /// the token could not be transferred (is the contract approved to move it?)
+        TransferFailed,
+    }
+
+    impl Default for Transmutation {
+        fn default() -> Self {
+            Self::new()
+        }
     }
 
     #[ink(storage)]
     pub struct Transmutation {
-        /// mock to track ownership of assets
-        /// in real life this like would be 
-        /// a reference to some NFT contract
-        asset_registry: Mapping<OpaqueAssetId, AccountId>,
-        /// a temp registry to hold 'in transit' assets
-        asset_status: Mapping<OpaqueAssetId, Hash>,
-        /// a collection of all claimed assets
-        claimed_assets: Vec<OpaqueAssetId>,
-        /// only one asset per account
-        // mock_asset_ownership: Mapping<AccountId, OpaqueAssetId>,
         /// a mapping of all swaps
         /// any pair of accounts can only have one active swap
         swaps: Mapping<Hash, Swap>,
@@ -67,85 +110,13 @@ mod transmutation {
 
 
     impl Transmutation {
-        /// Constructor that initializes the `bool` value to the given `init_value`.
+        /// Constructor that initializes an empty swap registry
         #[ink(constructor, payable)]
         pub fn new() -> Self {
             Self {
-                asset_registry: Mapping::new(),
-                asset_status: Mapping::new(),
-                claimed_assets: Vec::new(),
-                swaps: Mapping::new(),
-                pending_swaps: Mapping::new(),
-            }
-        }
-
-        #[ink(constructor, payable)]
-        pub fn default() -> Self {
-            Self::new()
-        }
-
-        /// generates a random seed
-        #[ink(message)]
-        pub fn random_seed(
-            &mut self,
-            input_seed: [u8;48],
-        ) -> Result<(), Error> {
-            let caller = self.env().caller();
-            let mut seed = self.env().extension().secret();
-            
-            seed.clone().iter().enumerate().for_each(|(i, bit)| {
-                seed[i] = *bit ^ input_seed[i];
-            });
-
-            if self.claimed_assets.contains(&seed.to_vec()) {
-                return Err(Error::DuplicateSeed);
-            }
-
-            self.asset_registry.insert(seed.to_vec(), &caller);
-            self.claimed_assets.push(seed.to_vec());
-
-            Ok(())
-        }
-
-        #[ink(message)]
-        pub fn destroy_seed(&mut self) -> Result<(), Error> {
-            let caller = self.env().caller();
-
-            if let Some(asset) = self.registry_lookup(caller) {
-                self.asset_registry.remove(asset.clone());
-                self.asset_status.remove(asset.clone());
-                self.pending_swaps.remove(caller.clone());
-                return Ok(());
-            }
-            
-            Err(Error::NoOwnedAsset)
-        }
-
-        #[ink(message)]
-        pub fn get_asset_swap(&self, asset_id: OpaqueAssetId) -> Option<Hash> {
-            self.asset_status.get(asset_id)
-        }
-
-        #[ink(message)]
-        pub fn get_owner(&self, asset_id: OpaqueAssetId) -> Option<AccountId> {
-            self.asset_registry.get(asset_id)
-        }
-
-        #[ink(message)]
-        pub fn registry_lookup(&self, who: AccountId) -> Option<OpaqueAssetId> {
-            if let Some(found_seed) = self.claimed_assets.iter().find(|seed| {
-                self.asset_registry
-                    .get(seed)
-                    .map_or(false, |registry_entry| registry_entry.eq(&who))
-            }) {
-                return Some(found_seed.clone());
+                swaps: Mapping::default(),
+                pending_swaps: Mapping::default(),
             }
-            None
-        }
-        
-        #[ink(message)]
-        pub fn get_claimed_assets(&self) -> Vec<OpaqueAssetId> {
-            self.claimed_assets.clone()
         }
 
         #[ink(message)]
@@ -156,280 +127,347 @@ mod transmutation {
             None
         }
 
+        #[ink(message)]
+        pub fn get_swap(&self, swap_id: Hash) -> Option<Swap> {
+            self.swaps.get(swap_id)
+        }
+
         /// get all opens swaps the participant is associated with
         #[ink(message)]
         pub fn swap_lookup(
-            &self, 
-            left: AccountId, 
+            &self,
+            left: AccountId,
             right: AccountId
         ) -> Result<(Hash, Swap), Error> {
-            let merkle_root = Self::calculate_merkle_root(left, right)?;
+            let merkle_root = Self::calculate_merkle_root(left, right);
             if let Some(swap) = self.swaps.get(merkle_root)  {
                 return Ok((merkle_root, swap));
             }
             Err(Error::SwapDNE)
         }
 
-        /// create a new swap 
+        /// propose to swap a token of the caller for a token of `who`
+        /// Nothing moves until the deadline, when each participant calls `transmute`
+        /// to escrow their token, so both must approve the contract to move it first.
+        /// returns the id of the swap
+        ///
+        /// * `who`: the other participant
+        /// * `give`: the token of the caller
+        /// * `take`: the token of `who`
+        /// * `deadline`: the block when both tokens must be escrowed, in the future
+        ///
         #[ink(message)]
-        pub fn try_new_swap( 
+        pub fn try_new_swap(
             &mut self,
             who: AccountId,
+            give: Asset,
+            take: Asset,
             deadline: BlockNumber,
-        ) -> Result<(), Error> {
+        ) -> Result<Hash, Error> {
             let caller = self.env().caller();
-            // make sure caller has an asset
-            if let Some(source_asset_id) = self.registry_lookup(caller.clone()) {
-                // and neither asset is part of a pending swap
-                if let None = self.pending_swaps.get(caller.clone()) {
-                    if let None = self.pending_swaps.get(who.clone()) {
-                        // get the owner of the target asset id
-                        if let Some(target_asset_id) = self.registry_lookup(who.clone()) {
-                            let merkle_root = Self::calculate_merkle_root(caller, who.clone())?;
-                            let swap = Swap {
-                                asset_id_one: source_asset_id,
-                                asset_id_two: target_asset_id,
-                                deadline,
-                            };
-                            let hash = Hash::from(merkle_root);
-                            self.swaps.insert(hash, &swap);
-                            self.pending_swaps.insert(caller, &hash);
-                            self.pending_swaps.insert(who, &hash);
-                        } else {
-                            return Err(Error::NoSuchAsset);
-                        }
-                    }
-                }
-            } else {
-                return Err(Error::NoOwnedAsset);
+            if caller.eq(&who) || give.eq(&take) {
+                return Err(Error::InvalidSwap);
             }
-            
-            Ok(())
+            if deadline <= self.env().block_number() {
+                return Err(Error::InvalidBlockNumber);
+            }
+            // neither participant can be part of another pending swap
+            if self.pending_swaps.contains(caller) || self.pending_swaps.contains(who) {
+                return Err(Error::SwapExists);
+            }
+            let merkle_root = Self::calculate_merkle_root(caller, who);
+            let swap = Swap {
+                owner_one: caller,
+                asset_id_one: give,
+                owner_two: who,
+                asset_id_two: take,
+                deadline,
+                escrowed_one: false,
+                escrowed_two: false,
+            };
+            self.swaps.insert(merkle_root, &swap);
+            self.pending_swaps.insert(caller, &merkle_root);
+            self.pending_swaps.insert(who, &merkle_root);
+            Ok(merkle_root)
         }
 
-        /// if part of a pending swap, reject it 
+        /// if part of a pending swap, reject it
         /// this is needed since each participant can have only one pending swap at a time
+        /// Swaps can only be rejected before the deadline, when no token is escrowed
         #[ink(message)]
         pub fn reject_swap(&mut self) -> Result<(), Error> {
-            
-            if let Some(_root) = self.pending_swaps.take(self.env().caller()) {
-                return Ok(());
+            let merkle_root = self.pending_swaps
+                .get(self.env().caller())
+                .ok_or(Error::InvalidSwap)?;
+            let swap = self.swaps.get(merkle_root).ok_or(Error::SwapDNE)?;
+            if self.env().block_number() >= swap.deadline {
+                return Err(Error::InvalidBlockNumber);
             }
-
-            Err(Error::InvalidSwap)
-            
+            self.close(merkle_root, &swap);
+            Ok(())
         }
 
-        /// transfers ownership of the asset to the contract at the swap deadline only
+        /// transfers the caller's token to the contract at the swap deadline only
+        /// the caller must own the token, and the contract must be approved to move it
+        /// (the collection must implement `PSP34TransferFrom`)
         #[ink(message)]
         pub fn transmute(&mut self) -> Result<(), Error> {
             let caller = self.env().caller();
+            let merkle_root = self.pending_swaps.get(caller).ok_or(Error::NotParticipant)?;
+            let mut swap = self.swaps.get(merkle_root).ok_or(Error::SwapDNE)?;
+            // transmutation must occur simultaneously
+            let current_block = self.env().block_number();
+            if !swap.deadline.eq(&current_block) {
+                return Err(Error::InvalidBlockNumber);
+            }
 
-            if let Some(merkle_root) = self.pending_swaps.get(caller) {
-                if let Some(swap) = self.swaps.get(merkle_root)  {
-                    // transmutation must occur simultaneously
-                    let current_block = self.env().block_number();
-                    if !swap.deadline.eq(&current_block) {
-                        return Err(Error::InvalidBlockNumber);
-                    }
-
-                    if let Some(asset_owner_one) = 
-                        self.asset_registry.get(swap.asset_id_one.clone()) {
-                        if asset_owner_one.eq(&caller) {
-                            self.asset_status.insert(swap.asset_id_one, &merkle_root);
-                        } else {
-                            self.asset_status.insert(swap.asset_id_two, &merkle_root);
-                        }
-                    }
+            let asset = if swap.owner_one.eq(&caller) {
+                if swap.escrowed_one {
+                    return Err(Error::AlreadyEscrowed);
                 }
-            }
+                swap.escrowed_one = true;
+                swap.asset_id_one.clone()
+            } else {
+                if swap.escrowed_two {
+                    return Err(Error::AlreadyEscrowed);
+                }
+                swap.escrowed_two = true;
+                swap.asset_id_two.clone()
+            };
+            self.escrow_asset(asset, caller)?;
+            self.swaps.insert(merkle_root, &swap);
             Ok(())
         }
 
+        /// end the swap, callable by anyone
+        /// If both tokens are escrowed, each participant receives the other's token,
+        /// from the deadline on. Otherwise, once the deadline has passed, escrowed tokens
+        /// go back to their owners. Either every transfer succeeds or none does
+        ///
+        /// * `swap_id`: the id of the swap, the merkle root of its participants
+        ///
         #[ink(message)]
-        pub fn complete(&mut self, swap_id: Hash) -> Result<(), Error> {
-            // let caller = self.env().caller();
-            // let merkle_root = Self::calculate_merkle_root(caller, from)?;
-            if let Some(swap) = self.swaps.take(swap_id)  {
-                let current_block = self.env().block_number();
-                if swap.deadline > current_block {
+        pub fn complete(&mut self, swap_id: Hash) -> Result<SwapOutcome, Error> {
+            let swap = self.swaps.get(swap_id).ok_or(Error::SwapDNE)?;
+            let current_block = self.env().block_number();
+            if swap.deadline > current_block {
+                return Err(Error::InvalidBlockNumber);
+            }
+
+            let outcome = if swap.escrowed_one && swap.escrowed_two {
+                // execute the swap
+                self.transfer_asset(swap.asset_id_one.clone(), swap.owner_two)?;
+                self.transfer_asset(swap.asset_id_two.clone(), swap.owner_one)?;
+                SwapOutcome::Swapped
+            } else {
+                // the other participant can still transmute until the deadline has passed
+                if swap.deadline == current_block {
                     return Err(Error::InvalidBlockNumber);
                 }
-                // both assets  must be locked (r1 and r2 are merkle roots)
-                if let Some(r1) = self.asset_status.get(swap.asset_id_one.clone()) {
-                    if let Some(r2) = self.asset_status.get(swap.asset_id_two.clone()) {
-                        if !r1.eq(&swap_id) || !r2.eq(&swap_id) {
-                            return Err(Error::InvalidSwap);
-                        }
-                    }   
+                if swap.escrowed_one {
+                    self.transfer_asset(swap.asset_id_one.clone(), swap.owner_one)?;
                 }
-                // execute the swap
-                if let Some(asset_owner_one) = self.asset_registry.get(swap.asset_id_one.clone()) {
-                    if let Some(asset_owner_two) = self.asset_registry.get(swap.asset_id_two.clone()) {
-                        self.asset_registry.insert(swap.asset_id_one.clone(), &asset_owner_two);
-                        self.asset_registry.insert(swap.asset_id_two.clone(), &asset_owner_one);
-                        self.pending_swaps.remove(asset_owner_one);
-                        self.pending_swaps.remove(asset_owner_two);
-                        self.asset_status.remove(swap.asset_id_one);
-                        self.asset_status.remove(swap.asset_id_two);
-                    }
+                if swap.escrowed_two {
+                    self.transfer_asset(swap.asset_id_two.clone(), swap.owner_two)?;
                 }
-            }
-
-            Ok(())
+                SwapOutcome::Refunded
+            };
+            self.close(swap_id, &swap);
+            Ok(outcome)
         }
 
         /// a helper function to calculate a merkle root
+        /// the root of the tree whose leaves are the sha256 hashes of the accounts
         pub fn calculate_merkle_root(
-            left: AccountId, 
+            left: AccountId,
             right: AccountId
-        ) -> Result<Hash, Error> {
-            let mut leaf_values = [left, right];
-            let leaves: Vec<[u8;32]> = 
-                leaf_values
-                    .iter_mut()
-                    .map(|x| Sha256::hash(x.as_mut()))
-                    .collect();
-            let merkle_tree = MerkleTree::<Sha256>::from_leaves(&leaves);
-            // this should never happen
-            if let Some(merkle_root) = merkle_tree.root() {
-                return Ok(Hash::from(merkle_root));
+        ) -> Hash {
+            let leaves: Vec<u8> = [left, right]
+                .iter()
+                .flat_map(|x| sha256(x.as_ref()))
+                .collect();
+            Hash::from(sha256(&leaves))
+        }
+
+        /// remove the swap, freeing both participants
+        fn close(&mut self, swap_id: Hash, swap: &Swap) {
+            self.swaps.remove(swap_id);
+            self.pending_swaps.remove(swap.owner_one);
+            self.pending_swaps.remove(swap.owner_two);
+        }
+
+        /// move a token of `owner` to the contract, if they own it
+        fn escrow_asset(&self, asset: Asset, owner: AccountId) -> Result<(), Error> {
+            let collection: CollectionRef = asset.collection.into();
+            if collection.owner_of(asset.id.clone()) != Some(owner) {
+                return Err(Error::NotOwner);
             }
-            Err(Error::InvalidMerkleTree)
+            let mut collection: TransferFromRef = asset.collection.into();
+            collection
+                .transfer_from(owner, self.env().account_id(), asset.id, Vec::new())
+                .map_err(|_| Error::TransferFailed)
+        }
+
+        /// send a token held by the contract
+        fn transfer_asset(&self, asset: Asset, to: AccountId) -> Result<(), Error> {
+            let mut collection: CollectionRef = asset.collection.into();
+            collection
+                .transfer(to, asset.id, Vec::new())
+                .map_err(|_| Error::TransferFailed)
         }
     }
 
+    fn sha256(input: &[u8]) -> [u8; 32] {
+        let mut output = <Sha2x256 as HashOutput>::Type::default();
+        ink::env::hash_bytes::<Sha2x256>(input, &mut output);
+        output
+    }
+
     /// Unit tests in Rust are normally defined within such a `#[cfg(test)]`
     /// module and test functions are marked with a `#[test]` attribute.
     /// The below code is technically just normal Rust code.
+    /// Escrowing and releasing tokens calls the collection, which is covered by
+    /// the sandbox tests.
     #[cfg(test)]
     mod tests {
         /// Imports all the definitions from the outer scope so we can use them here.
         use super::*;
 
-        /// We test if the default constructor does its job.
-        #[ink::test]
-        fn can_register_seed() {
-            let accounts = 
-                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            setup_ext_even_parity();
-            let mut transmutation = Transmutation::default();
-            assert_eq!(transmutation.swap_lookup(accounts.alice, accounts.bob), Err(Error::SwapDNE));
-            assert_eq!(transmutation.claimed_assets.len(), 0);
-            if let Err(_) = transmutation.random_seed([5;48]) {
-                panic!("{:?}", "The test should pass");
-            }
+        fn asset(id: u32) -> Asset {
+            Asset { collection: AccountId::from([0x42; 32]), id: Id::U32(id) }
+        }
 
-            assert_eq!(transmutation.claimed_assets.len(), 1);
-            assert_eq!(
-                transmutation.asset_registry.get(transmutation.claimed_assets[0].clone()).unwrap(),
-                accounts.alice
-            );
+        /// alice proposes to swap her token 1 for bob's token 2 at the deadline
+        fn setup(deadline: BlockNumber) -> (Transmutation, Hash) {
+            let accounts =
+                ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let mut transmutation = Transmutation::default();
+            let swap_id = transmutation
+                .try_new_swap(accounts.bob, asset(1), asset(2), deadline)
+                .expect("the swap should be created");
+            (transmutation, swap_id)
         }
 
-        
         #[ink::test]
         fn test_can_create_new_swap() {
-            let accounts = 
-                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            setup_ext_even_parity();
-            let mut transmutation = Transmutation::default();
-
+            let accounts =
+                ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
             let deadline = 1;
-            
-            if let Err(_) = transmutation.random_seed([5;48]) {
-                panic!("{:?}", "The test should pass");
-            }
-
-            let alice_asset = transmutation.registry_lookup(accounts.alice).unwrap();
+            let (transmutation, swap_id) = setup(deadline);
 
-            // then bob creates one
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
-            if let Err(_) = transmutation.random_seed([2;48]) {
-                panic!("{:?}", "The test should pass");
-            }
-
-            let bob_asset = transmutation.registry_lookup(accounts.bob).unwrap();
-
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
-            if let Err(_) = transmutation.try_new_swap(
-                accounts.bob,
-                deadline
-            ) {
-                panic!("{:?}", "The test should pass");
-            }
             let expected_swap = Swap {
-                asset_id_one: alice_asset,
-                asset_id_two: bob_asset,
+                owner_one: accounts.alice,
+                asset_id_one: asset(1),
+                owner_two: accounts.bob,
+                asset_id_two: asset(2),
                 deadline,
+                escrowed_one: false,
+                escrowed_two: false,
             };
 
-            let merkle_root = Transmutation::calculate_merkle_root(accounts.alice, accounts.bob).unwrap();
+            let merkle_root = Transmutation::calculate_merkle_root(accounts.alice, accounts.bob);
+            assert_eq!(swap_id, merkle_root);
             assert_eq!(transmutation.swaps.get(merkle_root).unwrap(), expected_swap);
-            assert_eq!(transmutation.swap_lookup(accounts.alice, accounts.bob).unwrap(), (merkle_root, expected_swap));
+            assert_eq!(
+                transmutation.swap_lookup(accounts.alice, accounts.bob).unwrap(),
+                (merkle_root, expected_swap)
+            );
+            assert_eq!(
+                transmutation.swap_lookup(accounts.bob, accounts.alice),
+                Err(Error::SwapDNE)
+            );
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(transmutation.get_pending_swap().unwrap().owner_one, accounts.alice);
         }
 
         #[ink::test]
-        fn test_can_trasmute() {
-            let accounts = 
-                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            setup_ext_even_parity();
+        fn swaps_must_be_valid() {
+            let accounts =
+                ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
             let mut transmutation = Transmutation::default();
+            assert_eq!(
+                transmutation.try_new_swap(accounts.alice, asset(1), asset(2), 1),
+                Err(Error::InvalidSwap)
+            );
+            assert_eq!(
+                transmutation.try_new_swap(accounts.bob, asset(1), asset(1), 1),
+                Err(Error::InvalidSwap)
+            );
+            assert_eq!(
+                transmutation.try_new_swap(accounts.bob, asset(1), asset(2), 0),
+                Err(Error::InvalidBlockNumber)
+            );
+        }
 
-            let deadline = 1;
-            
-            if let Err(_) = transmutation.random_seed([5;48]) {
-                panic!("{:?}", "The test should pass");
-            }
+        #[ink::test]
+        fn participants_have_a_single_pending_swap() {
+            let accounts =
+                ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let (mut transmutation, _) = setup(1);
+            assert_eq!(
+                transmutation.try_new_swap(accounts.charlie, asset(1), asset(3), 1),
+                Err(Error::SwapExists)
+            );
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(
+                transmutation.try_new_swap(accounts.bob, asset(3), asset(2), 1),
+                Err(Error::SwapExists)
+            );
+        }
 
-            // then bob creates one
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
-            if let Err(_) = transmutation.random_seed([2;48]) {
-                panic!("{:?}", "The test should pass");
-            }
-            
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
-            if let Err(_) = transmutation.try_new_swap(
-                accounts.bob,
-                deadline
-            ) {
-                panic!("{:?}", "The test should pass");
-            }
-            // let expected_swap = Swap {
-            //     asset_id_one: alice_asset,
-            //     asset_id_two: bob_asset,
-            //     deadline,
-            // };
-
-            ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
-            if let Err(_) = transmutation.transmute() {
-                panic!("{:?}", "The test should pass");
-            }
+        #[ink::test]
+        fn swaps_can_be_rejected_before_the_deadline() {
+            let accounts =
+                ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let (mut transmutation, swap_id) = setup(2);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(transmutation.reject_swap(), Err(Error::InvalidSwap));
 
             ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
-            if let Err(_) = transmutation.transmute() {
-                panic!("{:?}", "The test should pass");
-            }
-            // let merkle_root = Transmutation::calculate_merkle_root(accounts.alice, accounts.bob).unwrap();
-            // assert_eq!(transmutation.swaps.get(merkle_root).unwrap(), expected_swap);
-
+            assert_eq!(transmutation.reject_swap(), Ok(()));
+            assert_eq!(transmutation.get_swap(swap_id), None);
+            // both participants are free to swap again
+            assert_eq!(
+                transmutation.try_new_swap(accounts.alice, asset(2), asset(1), 2).map(|_| ()),
+                Ok(())
+            );
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            assert_eq!(transmutation.reject_swap(), Err(Error::InvalidBlockNumber));
         }
 
-        fn setup_ext_even_parity() {
-            struct MockETFExtension;
-            impl ink_env::test::ChainExtension for MockETFExtension {
-                fn func_id(&self) -> u32 {
-                    1101
-                }
-
-                fn call(&mut self, _input: &[u8], output: &mut Vec<u8>) -> u32 {
-                    let ret = [0;48];
-                    scale::Encode::encode_to(&ret, output);
-                    0
-                }
-            }
+        #[ink::test]
+        fn test_can_only_transmute_at_the_deadline() {
+            let accounts =
+                ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let (mut transmutation, _) = setup(2);
+            assert_eq!(transmutation.transmute(), Err(Error::InvalidBlockNumber));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(transmutation.transmute(), Err(Error::NotParticipant));
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(transmutation.transmute(), Err(Error::InvalidBlockNumber));
+        }
 
-            ink_env::test::register_chain_extension(MockETFExtension);
+        #[ink::test]
+        fn swaps_without_transmutations_are_refunded_after_the_deadline() {
+            let accounts =
+                ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let (mut transmutation, swap_id) = setup(1);
+            assert_eq!(transmutation.complete(swap_id), Err(Error::InvalidBlockNumber));
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            // bob could still transmute
+            assert_eq!(transmutation.complete(swap_id), Err(Error::InvalidBlockNumber));
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            // nothing was escrowed, so nothing is transferred
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(transmutation.complete(swap_id), Ok(SwapOutcome::Refunded));
+            assert_eq!(transmutation.complete(swap_id), Err(Error::SwapDNE));
+            assert_eq!(transmutation.pending_swaps.get(accounts.alice), None);
+            assert_eq!(transmutation.pending_swaps.get(accounts.bob), None);
         }
     }
 
@@ -0,0 +1,86 @@
+//! A drink! sandbox for testing contracts that use the drand chain extension.
+//!
+//! The sandbox runtime registers a mock of the drand bridge chain extension
+//! which serves randomness from a configurable [`PulseStore`]. This lets contracts
+//! built on the `DrandEnvironment` be deployed and called in-process, across many blocks
+//! and many contracts, without running a node.
+//!
+//! ``` ignore
+//! use idl_contract_extension_sandbox::{DrandSandbox, PulseStore};
+//!
+//! #[drink::test(sandbox = DrandSandbox)]
+//! fn it_works(mut session: Session) -> Result<(), Box<dyn std::error::Error>> {
+//!     PulseStore::insert(1, [1; 32]);
+//!     ...
+//! }
+//! ```
+
+/// the pulses served by the chain extension
+pub mod pulses;
+
+pub use pulses::{PulseStore, Randomness};
+
+use drink::{
+    create_sandbox,
+    errors::MessageResult,
+    frame_support::{pallet_prelude::DispatchError, sp_runtime::traits::UniqueSaturatedInto},
+    pallet_contracts::chain_extension::{
+        ChainExtension, Config as ContractsConfig, Environment, Ext, InitState, RetVal,
+    },
+    session::error::SessionError,
+};
+use scale::{Decode, Encode};
+
+/// the id of the drand chain extension
+pub const DRAND_EXTENSION_ID: u16 = 12;
+
+/// the id of the function that reads the latest randomness
+pub const RANDOM_FUNC_ID: u16 = 1101;
+
+/// the status code returned when there is no pulse for the current block
+/// (decoded as `DrandErrorCode::InvalidBlockNumber` by contracts)
+pub const INVALID_BLOCK_NUMBER: u32 = 1101;
+
+/// a mock of the drand chain extension, backed by the [`PulseStore`]
+#[derive(Default)]
+pub struct DrandExtension;
+
+impl<Runtime: ContractsConfig> ChainExtension<Runtime> for DrandExtension {
+    fn call<E: Ext<T = Runtime>>(
+        &mut self,
+        env: Environment<E, InitState>,
+    ) -> Result<RetVal, DispatchError> {
+        if env.ext_id() != DRAND_EXTENSION_ID || env.func_id() != RANDOM_FUNC_ID {
+            return Err(DispatchError::Other("unknown drand chain extension function"));
+        }
+
+        let mut env = env.buf_in_buf_out();
+        let block: u32 = env.ext().block_number().unique_saturated_into();
+        // when no pulse was ingested in this block, the randomness is zeroed
+        let (randomness, status) = match PulseStore::get(block) {
+            Some(randomness) => (randomness, 0),
+            None => ([0u8; 32], INVALID_BLOCK_NUMBER),
+        };
+        env.write(&randomness.encode(), false, None)?;
+        Ok(RetVal::Converging(status))
+    }
+}
+
+create_sandbox!(DrandSandbox, crate::DrandExtension, ());
+
+/// ink! reverts the calls to messages that return an error, which drink! reports as
+/// [`SessionError::CallReverted`] with the encoded result of the message
+pub trait Reverted<T> {
+    /// decode the result of a reverted call, as if it had succeeded
+    fn or_reverted(self) -> Result<MessageResult<T>, SessionError>;
+}
+
+impl<T: Decode> Reverted<T> for Result<MessageResult<T>, SessionError> {
+    fn or_reverted(self) -> Result<MessageResult<T>, SessionError> {
+        match self {
+            Err(SessionError::CallReverted(data)) => MessageResult::<T>::decode(&mut &data[..])
+                .map_err(|err| SessionError::Decoding(err.to_string())),
+            res => res,
+        }
+    }
+}
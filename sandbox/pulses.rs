@@ -0,0 +1,47 @@
+use std::{cell::RefCell, collections::BTreeMap};
+
+/// a type to represent the randomness of a drand pulse (32 bytes)
+pub type Randomness = [u8; 32];
+
+thread_local! {
+    /// pulses keyed by the block in which they were ingested
+    static PULSES: RefCell<BTreeMap<u32, Randomness>> = const { RefCell::new(BTreeMap::new()) };
+    /// randomness returned for blocks without an explicit pulse
+    static FALLBACK: RefCell<Option<Randomness>> = const { RefCell::new(None) };
+}
+
+/// The pulses served by the sandboxed drand chain extension.
+///
+/// drink! sessions run in the test thread, so the store is thread local
+/// and tests running in parallel never observe each other's pulses.
+pub struct PulseStore;
+
+impl PulseStore {
+    /// make `randomness` the pulse available at `block`
+    pub fn insert(block: u32, randomness: Randomness) {
+        PULSES.with(|pulses| pulses.borrow_mut().insert(block, randomness));
+    }
+
+    /// insert a pulse for each (block, randomness) pair
+    pub fn extend(pulses: impl IntoIterator<Item = (u32, Randomness)>) {
+        PULSES.with(|store| store.borrow_mut().extend(pulses));
+    }
+
+    /// set (or unset) the randomness served for blocks that have no pulse
+    pub fn set_fallback(randomness: Option<Randomness>) {
+        FALLBACK.with(|fallback| *fallback.borrow_mut() = randomness);
+    }
+
+    /// get the pulse available at `block`, if any
+    pub fn get(block: u32) -> Option<Randomness> {
+        PULSES
+            .with(|pulses| pulses.borrow().get(&block).copied())
+            .or_else(|| FALLBACK.with(|fallback| *fallback.borrow()))
+    }
+
+    /// remove all pulses and the fallback
+    pub fn clear() {
+        PULSES.with(|pulses| pulses.borrow_mut().clear());
+        Self::set_fallback(None);
+    }
+}
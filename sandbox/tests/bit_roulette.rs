@@ -0,0 +1,110 @@
+use drink::{
+    sandbox_api::prelude::*,
+    session::{Session, NO_ENDOWMENT, NO_SALT},
+    AccountId32,
+};
+use bit_roulette::Error as TableError;
+use bit_roulette_factory::{Error, TableDetails};
+use idl_contract_extension_sandbox::{DrandSandbox, Reverted};
+use ink::primitives::AccountId;
+
+#[drink::contract_bundle_provider]
+enum BundleProvider {}
+
+type TestResult<T = ()> = Result<T, Box<dyn std::error::Error>>;
+
+/// upload the bit roulette code and deploy a factory owned by the session's actor
+fn deploy_factory(session: &mut Session<DrandSandbox>) -> TestResult<AccountId32> {
+    let table_code_hash = session.upload_bundle(BundleProvider::BitRoulette.bundle()?)?;
+    let factory = session.deploy_bundle(
+        BundleProvider::BitRouletteFactory.bundle()?,
+        "new",
+        &[format!("{table_code_hash:?}")],
+        NO_SALT,
+        NO_ENDOWMENT,
+    )?;
+    Ok(factory)
+}
+
+/// a funded account, other than the default actor which owns the factory
+fn player(session: &mut Session<DrandSandbox>, seed: u8) -> AccountId32 {
+    let player = AccountId32::new([seed.wrapping_add(0x10); 32]);
+    session.sandbox().mint_into(&player, 1_000_000_000_000).expect("minting should work");
+    player
+}
+
+/// the address of a contract as a message argument
+fn arg(account_id: AccountId) -> String {
+    AccountId32::new(*account_id.as_ref()).to_string()
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn tables_are_closed_by_their_current_game_master(mut session: Session) -> TestResult {
+    let factory = deploy_factory(&mut session)?;
+    let bob = player(&mut session, 2);
+    let charlie = player(&mut session, 3);
+    let dave = player(&mut session, 4);
+
+    session.set_actor(bob.clone());
+    let res: Result<AccountId, Error> = session.call_with_address(
+        factory.clone(),
+        "create_table",
+        &[
+            "EventConfig { initial_slot: 0, interval: 1 }",
+            "0",
+            "HouseRules { house_edge: 200, max_exposure: 1000 }",
+        ],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    let table = res.expect("the table should be created");
+    // the table was instantiated by the factory, so the session has no transcoder for it
+    let table_address = AccountId32::new(*table.as_ref());
+    session.set_transcoder(
+        table_address.clone(),
+        &BundleProvider::BitRoulette.bundle()?.transcoder,
+    );
+
+    // neither the factory owner nor the table's game master
+    session.set_actor(charlie.clone());
+    let res: Result<(), Error> = session.call_with_address(
+        factory.clone(),
+        "close_table",
+        &[arg(table)],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Err(Error::NotGameMaster));
+
+    // the creator hands the table over, and can no longer close it
+    session.set_actor(bob.clone());
+    let res: Result<(), TableError> = session.call_with_address(
+        table_address,
+        "transfer_game_master",
+        &[dave.to_string()],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    let res: Result<(), Error> = session.call_with_address(
+        factory.clone(),
+        "close_table",
+        &[arg(table)],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Err(Error::NotGameMaster));
+
+    session.set_actor(dave.clone());
+    let res: Result<(), Error> = session.call_with_address(
+        factory.clone(),
+        "close_table",
+        &[arg(table)],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    let details: Option<TableDetails> = session.call_with_address(
+        factory.clone(),
+        "get_table",
+        &[arg(table)],
+        NO_ENDOWMENT,
+    )??;
+    assert!(!details.expect("the table should be listed").active);
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use drink::{
+    sandbox_api::prelude::*,
+    session::{Session, NO_ARGS, NO_ENDOWMENT, NO_SALT},
+};
+use idl_contract_extension_sandbox::{DrandSandbox, PulseStore};
+
+#[drink::contract_bundle_provider]
+enum BundleProvider {}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn template_reads_the_latest_pulse(mut session: Session) -> Result<(), Box<dyn std::error::Error>> {
+    PulseStore::clear();
+    let block = session.sandbox().block_number();
+    PulseStore::insert(block, [1; 32]);
+
+    session.deploy_bundle(BundleProvider::Template.bundle()?, "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+    let random: [u8; 32] = session.call("get_random", NO_ARGS, NO_ENDOWMENT)??;
+    assert_eq!(random, [0; 32]);
+
+    session = session.call_and("mutate_random", NO_ARGS, NO_ENDOWMENT)?;
+    let random: [u8; 32] = session.call("get_random", NO_ARGS, NO_ENDOWMENT)??;
+    assert_eq!(random, [1; 32]);
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn template_reads_a_new_pulse_each_block(
+    mut session: Session,
+) -> Result<(), Box<dyn std::error::Error>> {
+    PulseStore::clear();
+    let start = session.sandbox().block_number();
+    PulseStore::extend((0..3).map(|i| (start + i, [i as u8 + 1; 32])));
+
+    session.deploy_bundle(BundleProvider::Template.bundle()?, "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+    for i in 0..3u8 {
+        session = session.call_and("mutate_random", NO_ARGS, NO_ENDOWMENT)?;
+        let random: [u8; 32] = session.call("get_random", NO_ARGS, NO_ENDOWMENT)??;
+        assert_eq!(random, [i + 1; 32]);
+        session.sandbox().build_block();
+    }
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn template_falls_back_without_a_pulse(
+    mut session: Session,
+) -> Result<(), Box<dyn std::error::Error>> {
+    PulseStore::clear();
+    PulseStore::set_fallback(Some([7; 32]));
+
+    session.deploy_bundle(BundleProvider::Template.bundle()?, "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+    session = session.call_and("mutate_random", NO_ARGS, NO_ENDOWMENT)?;
+    let random: [u8; 32] = session.call("get_random", NO_ARGS, NO_ENDOWMENT)??;
+    assert_eq!(random, [7; 32]);
+
+    PulseStore::set_fallback(None);
+    session = session.call_and("mutate_random", NO_ARGS, NO_ENDOWMENT)?;
+    let random: [u8; 32] = session.call("get_random", NO_ARGS, NO_ENDOWMENT)??;
+    assert_eq!(random, [0; 32]);
+    Ok(())
+}
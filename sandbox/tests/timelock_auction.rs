@@ -0,0 +1,1802 @@
+use drink::{
+    pallet_contracts::Event as ContractsEvent,
+    sandbox_api::prelude::*,
+    session::{Session, NO_ARGS, NO_ENDOWMENT, NO_SALT},
+    AccountId32, Sandbox,
+};
+use idl_contract_extension_sandbox::{DrandSandbox, PulseStore, Reverted, RuntimeEvent, System};
+use ink::primitives::{AccountId, Hash};
+use auction_traits::{
+    bid_commitment, AuctionKind, AuctionOutcome, AuctionResult, TlockMessage,
+};
+use erc721::{Id, PSP34Error};
+use scale::{DecodeAll, Encode};
+use tlock_proxy::{
+    AuctionCreated, AuctionDetails, AuctionSettled, BidPlaced, BidRevealed, Claimed, Error,
+    Forfeited, Refunded, Slashed, CLAIM_PERIOD, MAX_PAGE_SIZE,
+};
+
+#[drink::contract_bundle_provider]
+enum BundleProvider {}
+
+type TestResult<T = ()> = Result<T, Box<dyn std::error::Error>>;
+
+/// the number of blocks between the deadline and the reveal deadline of every auction
+const REVEAL_PERIOD: u32 = 2;
+
+/// upload the child contracts and deploy a proxy owned by the session's actor
+fn deploy_proxy(session: &mut Session<DrandSandbox>) -> TestResult<AccountId32> {
+    PulseStore::clear();
+    PulseStore::set_fallback(Some([2; 32]));
+
+    let erc721_code_hash = session.upload_bundle(BundleProvider::Erc721.bundle()?)?;
+    let auction_code_hash = session.upload_bundle(BundleProvider::VickreyAuction.bundle()?)?;
+    let owner = session.get_actor();
+    let proxy = session.deploy_bundle(
+        BundleProvider::TlockProxy.bundle()?,
+        "new",
+        &[
+            owner.to_string(),
+            format!("{auction_code_hash:?}"),
+            format!("{erc721_code_hash:?}"),
+        ],
+        NO_SALT,
+        NO_ENDOWMENT,
+    )?;
+    Ok(proxy)
+}
+
+/// create a new auction through the proxy
+fn new_auction(
+    session: &mut Session<DrandSandbox>,
+    name: [u8; 32],
+    deadline: u32,
+    deposit: u128,
+) -> TestResult<AccountId> {
+    new_auction_with_reserve(session, name, deadline, deposit, 0)
+}
+
+/// create a new vickrey auction with a reserve price through the proxy
+fn new_auction_with_reserve(
+    session: &mut Session<DrandSandbox>,
+    name: [u8; 32],
+    deadline: u32,
+    deposit: u128,
+    reserve_price: u128,
+) -> TestResult<AccountId> {
+    new_auction_of_kind(session, name, "Vickrey", deadline, deposit, reserve_price)
+}
+
+/// create a new auction of any kind through the proxy
+/// bids can be revealed for `REVEAL_PERIOD` blocks after the deadline
+///
+/// * `config`: the `AuctionConfig`, e.g. `English { min_increment: 1 }`
+fn new_auction_of_kind(
+    session: &mut Session<DrandSandbox>,
+    name: [u8; 32],
+    config: &str,
+    deadline: u32,
+    deposit: u128,
+    reserve_price: u128,
+) -> TestResult<AccountId> {
+    let res: Result<AccountId, Error> = session.call(
+        "new_auction",
+        &[
+            format!("{name:?}"),
+            config.to_string(),
+            terms(deadline, deadline + REVEAL_PERIOD, deposit, reserve_price),
+        ],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    Ok(res.expect("the auction should be created"))
+}
+
+/// encode the `AuctionTerms` of a new auction as a message argument
+fn terms(deadline: u32, reveal_deadline: u32, deposit: u128, reserve_price: u128) -> String {
+    format!(
+        "AuctionTerms {{ deadline: {deadline}, reveal_deadline: {reveal_deadline}, \
+         deposit: {deposit}, reserve_price: {reserve_price}, extension: None }}"
+    )
+}
+
+/// the ink! account id of a sandbox account
+fn account(account_id: &AccountId32) -> AccountId {
+    AccountId::from(*AsRef::<[u8; 32]>::as_ref(account_id))
+}
+
+/// the data of every contract event emitted during the last call, in order
+fn last_events(session: &mut Session<DrandSandbox>) -> Vec<Vec<u8>> {
+    session
+        .record()
+        .last_event_batch()
+        .all_events()
+        .iter()
+        .filter_map(|record| match &record.event {
+            RuntimeEvent::Contracts(ContractsEvent::ContractEmitted { data, .. }) => {
+                Some(data.clone())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// the data of the PSP34 `Transfer` event
+fn nft_transfer(from: AccountId, to: AccountId, id: u32) -> Vec<u8> {
+    (Some(from), Some(to), Id::U32(id)).encode()
+}
+
+/// the ids of the tokens minted during the last call, read from the PSP34 `Transfer` events
+fn minted_ids(session: &mut Session<DrandSandbox>) -> Vec<Id> {
+    last_events(session)
+        .into_iter()
+        .filter_map(|data| {
+            <(Option<AccountId>, Option<AccountId>, Id)>::decode_all(&mut &data[..]).ok()
+        })
+        .filter(|(from, to, _)| from.is_none() && to.is_some())
+        .map(|(_, _, id)| id)
+        .collect()
+}
+
+/// encode an ink! account id as a message argument
+fn arg(account_id: AccountId) -> String {
+    AccountId32::new(*account_id.as_ref()).to_string()
+}
+
+/// encode a sealed bid payload as a message argument, with a dummy ciphertext and a real commitment
+fn sealed_bid(bidder: &AccountId32, bid: u128, salt: [u8; 32]) -> String {
+    let mut message = TlockMessage {
+        ciphertext: vec![1; 32],
+        nonce: vec![2; 12],
+        capsule: vec![3; 32],
+        commitment: Vec::new(),
+    };
+    message.commitment = bid_commitment(&account(bidder), bid, &salt, &message).to_vec();
+    let TlockMessage { ciphertext, nonce, capsule, commitment } = message;
+    format!(
+        "Sealed(TlockMessage {{ ciphertext: {ciphertext:?}, nonce: {nonce:?}, \
+         capsule: {capsule:?}, commitment: {commitment:?} }})"
+    )
+}
+
+/// encode a revealed bid as a message argument
+fn revealed_bid(bidder: &AccountId32, bid: u128, salt: [u8; 32]) -> String {
+    format!("RevealedBid {{ bidder: {bidder}, bid: {bid}, salt: {salt:?} }}")
+}
+
+/// a funded account to bid with
+fn bidder(session: &mut Session<DrandSandbox>, seed: u8) -> AccountId32 {
+    // offset from the default actor, which owns the proxy
+    let bidder = AccountId32::new([seed.wrapping_add(0x10); 32]);
+    session.sandbox().mint_into(&bidder, 1_000_000_000_000).expect("minting should work");
+    bidder
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn default_works(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    let auctions: Result<Vec<AuctionDetails>, Error> =
+        session.call("get_auctions", &["0", "10"], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(auctions, Ok(Vec::new()));
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn new_auction_works(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    let auction_id = new_auction(&mut session, [1; 32], 10, 1)?;
+
+    let auctions: Result<Vec<AuctionDetails>, Error> =
+        session.call("get_auctions", &["0", "10"], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(auctions.map(|a| a.len()), Ok(1));
+
+    let latest: Result<AccountId, Error> =
+        session.call("get_latest_auction", NO_ARGS, NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(latest, Ok(auction_id));
+
+    let details: Result<AuctionDetails, Error> =
+        session.call("get_auction_details", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert!(details.is_ok());
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn bid_works(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    // some block waaaay in the future
+    let auction_id = new_auction(&mut session, [1; 32], 1_000_000_000, 1)?;
+
+    let bidder = session.get_actor();
+    let res: Result<(), Error> = session.call(
+        "bid",
+        &[arg(auction_id), sealed_bid(&bidder, 10, [4; 32])],
+        Some(1),
+    ).or_reverted()??;
+    assert_eq!(res, Ok(()));
+
+    let bidder = bidder.to_string();
+    let auctions: Result<Vec<AuctionDetails>, Error> = session.call(
+        "get_auctions_by_bidder",
+        &[bidder, "0".to_string(), "10".to_string()],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(auctions.map(|a| a.len()), Ok(1));
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn bid_fails_after_deadline(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    let deadline = session.sandbox().block_number() + 1;
+    let auction_id = new_auction(&mut session, [1; 32], deadline, 1)?;
+
+    session.sandbox().build_blocks(2);
+    let bidder = session.get_actor();
+    let res: Result<(), Error> = session.call(
+        "bid",
+        &[arg(auction_id), sealed_bid(&bidder, 10, [4; 32])],
+        Some(1),
+    ).or_reverted()??;
+    assert_eq!(res, Err(Error::BiddingClosed));
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn sealed_bid_auction_works(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    let deadline = session.sandbox().block_number() + 2;
+    let auction_id = new_auction(&mut session, [1; 32], deadline, 1)?;
+
+    let alice = bidder(&mut session, 1);
+    let bob = bidder(&mut session, 2);
+    for (who, bid) in [(&alice, 10), (&bob, 20)] {
+        session.set_actor(who.clone());
+        let res: Result<(), Error> = session.call(
+            "bid",
+            &[arg(auction_id), sealed_bid(who, bid, [bid as u8; 32])],
+            Some(1),
+        ).or_reverted()??;
+        assert_eq!(res, Ok(()));
+    }
+
+    // the deadline has passed, so the bids can be revealed
+    session.sandbox().build_blocks(2);
+    // a reveal that does not open the commitment is rejected
+    let res: Result<(), Error> = session.call(
+        "reveal_bid",
+        &[arg(auction_id), revealed_bid(&bob, 30, [20; 32])],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Err(Error::CommitmentMismatch));
+
+    for (who, bid) in [(&alice, 10), (&bob, 20)] {
+        let res: Result<(), Error> = session.call(
+            "reveal_bid",
+            &[arg(auction_id), revealed_bid(who, bid, [bid as u8; 32])],
+            NO_ENDOWMENT,
+        ).or_reverted()??;
+        assert_eq!(res, Ok(()));
+    }
+
+    session.sandbox().build_blocks(REVEAL_PERIOD);
+    let res: Result<(), Error> =
+        session.call("complete", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+
+    let winner: Result<AuctionResult<AccountId, u128>, Error> =
+        session.call("get_winner", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(
+        winner,
+        Ok(AuctionResult {
+            winner: account(&bob),
+            debt: 10,
+        })
+    );
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn ties_are_broken_with_the_pulse_of_the_last_reveal(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    let deadline = session.sandbox().block_number() + 2;
+    let auction_id = new_auction(&mut session, [1; 32], deadline, 1)?;
+
+    let alice = bidder(&mut session, 1);
+    let bob = bidder(&mut session, 2);
+    for who in [&alice, &bob] {
+        session.set_actor(who.clone());
+        let res: Result<(), Error> = session.call(
+            "bid",
+            &[arg(auction_id), sealed_bid(who, 10, [10; 32])],
+            Some(1),
+        ).or_reverted()??;
+        assert_eq!(res, Ok(()));
+    }
+    session.sandbox().build_blocks(2);
+    // the pulse of the block of the reveals selects the second of the tied bidders,
+    // any later pulse (the fallback) selects the first
+    PulseStore::insert(session.sandbox().block_number(), [1; 32]);
+    for who in [&alice, &bob] {
+        let res: Result<(), Error> = session.call(
+            "reveal_bid",
+            &[arg(auction_id), revealed_bid(who, 10, [10; 32])],
+            NO_ENDOWMENT,
+        ).or_reverted()??;
+        assert_eq!(res, Ok(()));
+    }
+
+    // completing well after the reveal deadline does not change the winner
+    session.sandbox().build_blocks(REVEAL_PERIOD + 3);
+    let res: Result<(), Error> =
+        session.call("complete", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    let winner: Result<AuctionResult<AccountId, u128>, Error> =
+        session.call("get_winner", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(winner.map(|r| r.winner), Ok(account(&bob)));
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn settlement_balances_are_conserved(mut session: Session) -> TestResult {
+    let proxy = deploy_proxy(&mut session)?;
+    let seller = session.get_actor();
+    let deadline = session.sandbox().block_number() + 2;
+    let auction_id = new_auction(&mut session, [1; 32], deadline, 5)?;
+
+    let alice = bidder(&mut session, 1);
+    let bob = bidder(&mut session, 2);
+    let charlie = bidder(&mut session, 3);
+    let proxy_balance = session.sandbox().free_balance(&proxy);
+    let seller_balance = session.sandbox().free_balance(&seller);
+
+    for (who, bid) in [(&alice, 10), (&bob, 20), (&charlie, 30)] {
+        session.set_actor(who.clone());
+        let res: Result<(), Error> = session.call(
+            "bid",
+            &[arg(auction_id), sealed_bid(who, bid, [bid as u8; 32])],
+            Some(5),
+        ).or_reverted()??;
+        assert_eq!(res, Ok(()));
+    }
+    assert_eq!(session.sandbox().free_balance(&proxy), proxy_balance + 15);
+
+    // charlie never reveals, so their deposit is slashed
+    session.sandbox().build_blocks(2);
+    for (who, bid) in [(&alice, 10), (&bob, 20)] {
+        let res: Result<(), Error> = session.call(
+            "reveal_bid",
+            &[arg(auction_id), revealed_bid(who, bid, [bid as u8; 32])],
+            NO_ENDOWMENT,
+        ).or_reverted()??;
+        assert_eq!(res, Ok(()));
+    }
+    session.sandbox().build_blocks(REVEAL_PERIOD);
+    let res: Result<(), Error> =
+        session.call("complete", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    assert_eq!(session.sandbox().free_balance(&proxy), proxy_balance + 10);
+    assert_eq!(session.sandbox().free_balance(&seller), seller_balance + 5);
+    let deposit: u128 =
+        session
+            .call("get_deposit", &[arg(auction_id), charlie.to_string()], NO_ENDOWMENT)
+            .or_reverted()??;
+    assert_eq!(deposit, 0);
+
+    // bob wins, owes alice's bid and has their deposit credited against it
+    session.set_actor(bob.clone());
+    let res: Result<(), Error> =
+        session.call("claim", &[arg(auction_id)], Some(10)).or_reverted()??;
+    assert_eq!(res, Err(Error::InvalidCurrencyAmountTransferred));
+    let res: Result<(), Error> = session.call("claim", &[arg(auction_id)], Some(5)).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    assert_eq!(session.sandbox().free_balance(&seller), seller_balance + 15);
+
+    // alice lost, so they are refunded exactly once
+    session.set_actor(alice.clone());
+    let res: Result<(), Error> =
+        session.call("claim", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    let res: Result<(), Error> =
+        session.call("claim", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::NothingToClaim));
+
+    // nothing is left in escrow
+    assert_eq!(session.sandbox().free_balance(&proxy), proxy_balance);
+    for who in [&alice, &bob, &charlie] {
+        let deposit: u128 =
+            session
+                .call("get_deposit", &[arg(auction_id), who.to_string()], NO_ENDOWMENT)
+                .or_reverted()??;
+        assert_eq!(deposit, 0);
+    }
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn winner_surplus_deposit_is_refunded(mut session: Session) -> TestResult {
+    let proxy = deploy_proxy(&mut session)?;
+    let seller = session.get_actor();
+    let deadline = session.sandbox().block_number() + 2;
+    let auction_id = new_auction(&mut session, [1; 32], deadline, 50)?;
+
+    let alice = bidder(&mut session, 1);
+    let bob = bidder(&mut session, 2);
+    let proxy_balance = session.sandbox().free_balance(&proxy);
+    let seller_balance = session.sandbox().free_balance(&seller);
+    for (who, bid) in [(&alice, 10), (&bob, 20)] {
+        session.set_actor(who.clone());
+        let _: Result<(), Error> = session.call(
+            "bid",
+            &[arg(auction_id), sealed_bid(who, bid, [bid as u8; 32])],
+            Some(50),
+        ).or_reverted()??;
+    }
+
+    session.sandbox().build_blocks(2);
+    for (who, bid) in [(&alice, 10), (&bob, 20)] {
+        let _: Result<(), Error> = session.call(
+            "reveal_bid",
+            &[arg(auction_id), revealed_bid(who, bid, [bid as u8; 32])],
+            NO_ENDOWMENT,
+        ).or_reverted()??;
+    }
+    session.sandbox().build_blocks(REVEAL_PERIOD);
+    let _: Result<(), Error> =
+        session.call("complete", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+
+    // bob's deposit covers their debt, so they transfer nothing and the surplus is refunded
+    session.set_actor(bob.clone());
+    let res: Result<(), Error> =
+        session.call("claim", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    session.set_actor(alice.clone());
+    let res: Result<(), Error> =
+        session.call("claim", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+
+    assert_eq!(session.sandbox().free_balance(&seller), seller_balance + 10);
+    assert_eq!(session.sandbox().free_balance(&proxy), proxy_balance);
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn unclaimed_prizes_are_forfeited(mut session: Session) -> TestResult {
+    let collection = session.deploy_bundle(
+        BundleProvider::Erc721.bundle()?,
+        "new",
+        NO_ARGS,
+        NO_SALT,
+        NO_ENDOWMENT,
+    )?;
+    let _: Result<(), PSP34Error> = session.call_with_address(
+        collection.clone(),
+        "PSP34Mintable::mint",
+        &["U32(7)"],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    let proxy = deploy_proxy(&mut session)?;
+    let seller = session.get_actor();
+    let _: Result<(), PSP34Error> = session.call_with_address(
+        collection.clone(),
+        "PSP34::approve",
+        &[proxy.to_string(), "Some(U32(7))".to_string(), "true".to_string()],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    let deadline = session.sandbox().block_number() + 2;
+    let res: Result<AccountId, Error> = session.call(
+        "new_auction_for",
+        &[
+            format!("{:?}", [1u8; 32]),
+            collection.to_string(),
+            "U32(7)".to_string(),
+            "Vickrey".to_string(),
+            terms(deadline, deadline + REVEAL_PERIOD, 5, 0),
+        ],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    let auction_id = res.expect("the auction should be created");
+
+    let alice = bidder(&mut session, 1);
+    session.set_actor(alice.clone());
+    let _: Result<(), Error> = session.call(
+        "bid",
+        &[arg(auction_id), sealed_bid(&alice, 10, [10; 32])],
+        Some(5),
+    ).or_reverted()??;
+    session.sandbox().build_blocks(2);
+    let _: Result<(), Error> = session.call(
+        "reveal_bid",
+        &[arg(auction_id), revealed_bid(&alice, 10, [10; 32])],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    session.sandbox().build_blocks(REVEAL_PERIOD);
+    let _: Result<(), Error> =
+        session.call("complete", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+
+    // alice won but does not claim
+    let bob = bidder(&mut session, 2);
+    session.set_actor(bob);
+    let reclaim = [arg(auction_id), alice.to_string()];
+    let res: Result<(), Error> =
+        session.call("reclaim", &reclaim, NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::ClaimPeriodInProgress));
+    // building every block of the claim period is slow, so skip to its last one
+    session.sandbox().execute_with(|| {
+        System::set_block_number(System::block_number() + CLAIM_PERIOD - 1)
+    });
+    session.sandbox().build_block();
+    session.set_actor(alice.clone());
+    let res: Result<(), Error> =
+        session.call("claim", &[arg(auction_id)], Some(5)).or_reverted()??;
+    assert_eq!(res, Err(Error::ClaimPeriodOver));
+
+    // anyone forfeits the prize, for the seller
+    let seller_balance = session.sandbox().free_balance(&seller);
+    let res: Result<(), Error> =
+        session.call("reclaim", &reclaim, NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    assert_eq!(
+        last_events(&mut session),
+        vec![
+            nft_transfer(account(&proxy), account(&seller), 7),
+            Forfeited {
+                auction_id,
+                winner: account(&alice),
+                asset_id: Id::U32(7),
+                deposit: 5,
+            }
+            .encode(),
+        ]
+    );
+    assert_eq!(session.sandbox().free_balance(&seller), seller_balance + 5);
+    let owner: Option<AccountId> = session.call_with_address(
+        collection.clone(),
+        "PSP34::owner_of",
+        &["U32(7)"],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(owner, Some(account(&seller)));
+    let res: Result<(), Error> =
+        session.call("reclaim", &reclaim, NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::NothingToClaim));
+    let res: Result<(), Error> =
+        session.call("claim", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::NothingToClaim));
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn claim_fails_before_complete(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    let deadline = session.sandbox().block_number() + 1;
+    let auction_id = new_auction(&mut session, [1; 32], deadline, 5)?;
+    let alice = bidder(&mut session, 1);
+    session.set_actor(alice.clone());
+    let _: Result<(), Error> = session.call(
+        "bid",
+        &[arg(auction_id), sealed_bid(&alice, 10, [10; 32])],
+        Some(5),
+    ).or_reverted()??;
+
+    session.sandbox().build_blocks(2);
+    let res: Result<(), Error> =
+        session.call("claim", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::AuctionNotComplete));
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn no_sale_refunds_every_bidder(mut session: Session) -> TestResult {
+    let proxy = deploy_proxy(&mut session)?;
+    let deadline = session.sandbox().block_number() + 2;
+    let auction_id = new_auction_with_reserve(&mut session, [1; 32], deadline, 5, 100)?;
+
+    let alice = bidder(&mut session, 1);
+    let proxy_balance = session.sandbox().free_balance(&proxy);
+    session.set_actor(alice.clone());
+    let _: Result<(), Error> = session.call(
+        "bid",
+        &[arg(auction_id), sealed_bid(&alice, 10, [10; 32])],
+        Some(5),
+    ).or_reverted()??;
+
+    session.sandbox().build_blocks(2);
+    let _: Result<(), Error> = session.call(
+        "reveal_bid",
+        &[arg(auction_id), revealed_bid(&alice, 10, [10; 32])],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    // the only bid is below the reserve price, so the asset returns to the seller
+    session.sandbox().build_blocks(REVEAL_PERIOD);
+    let res: Result<(), Error> =
+        session.call("complete", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    let winner: Result<AuctionResult<AccountId, u128>, Error> =
+        session.call("get_winner", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(winner, Err(Error::NoWinnerDetermined));
+
+    let res: Result<(), Error> =
+        session.call("claim", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    assert_eq!(session.sandbox().free_balance(&proxy), proxy_balance);
+    Ok(())
+}
+
+/// upload the other auction formats and register them with the proxy
+fn register_auction_kinds(session: &mut Session<DrandSandbox>) -> TestResult {
+    let kinds = [
+        ("FirstPrice", BundleProvider::FirstPriceAuction.bundle()?),
+        ("English", BundleProvider::EnglishAuction.bundle()?),
+        ("Dutch", BundleProvider::DutchAuction.bundle()?),
+        ("UniformPrice", BundleProvider::UniformPriceAuction.bundle()?),
+    ];
+    for (kind, bundle) in kinds {
+        let code_hash = session.upload_bundle(bundle)?;
+        let res: Result<(), Error> = session.call(
+            "register_auction_kind",
+            &[kind.to_string(), format!("{code_hash:?}")],
+            NO_ENDOWMENT,
+        ).or_reverted()??;
+        assert_eq!(res, Ok(()));
+    }
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn unregistered_kind_fails(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    let res: Result<AccountId, Error> = session.call(
+        "new_auction",
+        &[
+            format!("{:?}", [1u8; 32]),
+            "English { min_increment: 1 }".to_string(),
+            terms(10, 10, 1, 0),
+        ],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Err(Error::UnknownAuctionKind));
+
+    // only the owner can register auction kinds
+    let alice = bidder(&mut session, 1);
+    session.set_actor(alice);
+    let res: Result<(), Error> = session.call(
+        "register_auction_kind",
+        &["English".to_string(), format!("0x{}", "00".repeat(32))],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Err(Error::NotOwner));
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn ownership_can_be_transferred(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    let alice = bidder(&mut session, 1);
+    let res: Result<(), Error> =
+        session.call("transfer_ownership", &[arg(account(&alice))], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+
+    // the previous owner lost their rights
+    let res: Result<(), Error> = session.call(
+        "register_auction_kind",
+        &["English".to_string(), format!("0x{}", "00".repeat(32))],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Err(Error::NotOwner));
+    session.set_actor(alice);
+    let res: Result<(), Error> = session.call(
+        "register_auction_kind",
+        &["English".to_string(), format!("0x{}", "00".repeat(32))],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn proxy_is_upgraded_in_place(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    let owner = session.get_actor();
+    let deadline = session.sandbox().block_number() + 2;
+    let auction_id = new_auction(&mut session, [1; 32], deadline, 1)?;
+    let before: Result<AuctionDetails, Error> =
+        session.call("get_auction_details", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    let proxy_code_hash = session.upload_bundle(BundleProvider::TlockProxy.bundle()?)?;
+
+    // only the owner can upgrade
+    let alice = bidder(&mut session, 1);
+    session.set_actor(alice);
+    let res: Result<(), Error> =
+        session.call("upgrade", &[format!("{proxy_code_hash:?}")], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::NotOwner));
+    session.set_actor(owner);
+    let res: Result<(), Error> =
+        session.call("upgrade", &[format!("0x{}", "00".repeat(32))], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::UpgradeFailed));
+    let res: Result<(), Error> =
+        session.call("upgrade", &[format!("{proxy_code_hash:?}")], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+
+    // the storage survives the upgrade
+    let details: Result<AuctionDetails, Error> =
+        session.call("get_auction_details", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert!(before.is_ok());
+    assert_eq!(details, before);
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn auction_code_hash_is_updated(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    let deadline = session.sandbox().block_number() + 2;
+    let auction_id = new_auction(&mut session, [1; 32], deadline, 1)?;
+    // stands in for a fixed version of the vickrey auction contract
+    let code_hash = session.upload_bundle(BundleProvider::FirstPriceAuction.bundle()?)?;
+
+    // only registered kinds can be updated
+    let res: Result<(), Error> = session.call(
+        "update_auction_code_hash",
+        &["English".to_string(), format!("{code_hash:?}")],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Err(Error::UnknownAuctionKind));
+    let res: Result<(), Error> = session.call(
+        "update_auction_code_hash",
+        &["Vickrey".to_string(), format!("{code_hash:?}")],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    let registered: Option<Hash> =
+        session.call("get_auction_code_hash", &["Vickrey"], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(registered, Some(Hash::from(code_hash.0)));
+
+    // the auction deployed before keeps running its code
+    let alice = bidder(&mut session, 1);
+    session.set_actor(alice.clone());
+    let res: Result<(), Error> = session.call(
+        "bid",
+        &[arg(auction_id), sealed_bid(&alice, 10, [10; 32])],
+        Some(1),
+    ).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn bids_are_revealed_once(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    let deadline = session.sandbox().block_number() + 2;
+    let auction_id = new_auction(&mut session, [1; 32], deadline, 1)?;
+    let alice = bidder(&mut session, 1);
+    session.set_actor(alice.clone());
+    let res: Result<(), Error> = session.call(
+        "bid",
+        &[arg(auction_id), sealed_bid(&alice, 10, [10; 32])],
+        Some(1),
+    ).or_reverted()??;
+    assert_eq!(res, Ok(()));
+
+    session.sandbox().build_blocks(2);
+    for expected in [Ok(()), Err(Error::AlreadyRevealed)] {
+        let res: Result<(), Error> = session.call(
+            "reveal_bid",
+            &[arg(auction_id), revealed_bid(&alice, 10, [10; 32])],
+            NO_ENDOWMENT,
+        ).or_reverted()??;
+        assert_eq!(res, expected);
+    }
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn first_price_auction_works(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    register_auction_kinds(&mut session)?;
+    let deadline = session.sandbox().block_number() + 2;
+    let auction_id = new_auction_of_kind(&mut session, [1; 32], "FirstPrice", deadline, 1, 0)?;
+
+    let alice = bidder(&mut session, 1);
+    let bob = bidder(&mut session, 2);
+    for (who, bid) in [(&alice, 10), (&bob, 20)] {
+        session.set_actor(who.clone());
+        let res: Result<(), Error> = session.call(
+            "bid",
+            &[arg(auction_id), sealed_bid(who, bid, [bid as u8; 32])],
+            Some(1),
+        ).or_reverted()??;
+        assert_eq!(res, Ok(()));
+    }
+
+    session.sandbox().build_blocks(2);
+    for (who, bid) in [(&alice, 10), (&bob, 20)] {
+        let res: Result<(), Error> = session.call(
+            "reveal_bid",
+            &[arg(auction_id), revealed_bid(who, bid, [bid as u8; 32])],
+            NO_ENDOWMENT,
+        ).or_reverted()??;
+        assert_eq!(res, Ok(()));
+    }
+    session.sandbox().build_blocks(REVEAL_PERIOD);
+    let res: Result<(), Error> =
+        session.call("complete", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+
+    // bob pays their own bid
+    let winner: Result<AuctionResult<AccountId, u128>, Error> =
+        session.call("get_winner", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(
+        winner,
+        Ok(AuctionResult {
+            winner: account(&bob),
+            debt: 20,
+        })
+    );
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn english_auction_works(mut session: Session) -> TestResult {
+    let proxy = deploy_proxy(&mut session)?;
+    register_auction_kinds(&mut session)?;
+    let seller = session.get_actor();
+    let deadline = session.sandbox().block_number() + 2;
+    let auction_id = new_auction_of_kind(
+        &mut session,
+        [1; 32],
+        "English { min_increment: 5 }",
+        deadline,
+        1,
+        10,
+    )?;
+
+    let alice = bidder(&mut session, 1);
+    let bob = bidder(&mut session, 2);
+    let proxy_balance = session.sandbox().free_balance(&proxy);
+    let seller_balance = session.sandbox().free_balance(&seller);
+    // bids are open and must beat the highest bid by the minimum increment
+    for (who, bid, expected) in [
+        (&alice, 10, Ok(())),
+        (&bob, 12, Err(Error::BidTooLow)),
+        (&bob, 15, Ok(())),
+    ] {
+        session.set_actor(who.clone());
+        let res: Result<(), Error> =
+            session
+                .call("bid", &[arg(auction_id), format!("Open({bid})")], Some(1))
+                .or_reverted()??;
+        assert_eq!(res, expected);
+    }
+    // a sealed bid does not suit an english auction
+    let res: Result<(), Error> = session.call(
+        "bid",
+        &[arg(auction_id), sealed_bid(&alice, 30, [30; 32])],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Err(Error::UnsupportedBid));
+
+    session.sandbox().build_blocks(2);
+    session.sandbox().build_blocks(REVEAL_PERIOD);
+    let res: Result<(), Error> =
+        session.call("complete", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+
+    session.set_actor(bob.clone());
+    let res: Result<(), Error> =
+        session.call("claim", &[arg(auction_id)], Some(14)).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    session.set_actor(alice.clone());
+    let res: Result<(), Error> =
+        session.call("claim", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    assert_eq!(session.sandbox().free_balance(&seller), seller_balance + 15);
+    assert_eq!(session.sandbox().free_balance(&proxy), proxy_balance);
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn dutch_auction_works(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    register_auction_kinds(&mut session)?;
+    let deadline = session.sandbox().block_number() + 10;
+    let auction_id = new_auction_of_kind(
+        &mut session,
+        [1; 32],
+        "Dutch { start_price: 100, decrement: 10 }",
+        deadline,
+        1,
+        50,
+    )?;
+
+    // the price drops for three blocks before alice accepts it
+    session.sandbox().build_blocks(3);
+    let alice = bidder(&mut session, 1);
+    let bob = bidder(&mut session, 2);
+    for (who, expected) in [(&alice, Ok(())), (&bob, Err(Error::BiddingClosed))] {
+        session.set_actor(who.clone());
+        let res: Result<(), Error> =
+            session.call("bid", &[arg(auction_id), "Accept".to_string()], Some(1)).or_reverted()??;
+        assert_eq!(res, expected);
+    }
+
+    session.sandbox().build_blocks(10);
+    session.sandbox().build_blocks(REVEAL_PERIOD);
+    let res: Result<(), Error> =
+        session.call("complete", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    let winner: Result<AuctionResult<AccountId, u128>, Error> =
+        session.call("get_winner", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(
+        winner,
+        Ok(AuctionResult {
+            winner: account(&alice),
+            debt: 70,
+        })
+    );
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn uniform_price_auction_sells_every_unit(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    register_auction_kinds(&mut session)?;
+    let seller = session.get_actor();
+    let deadline = session.sandbox().block_number() + 2;
+    let auction_id = new_auction_of_kind(
+        &mut session,
+        [1; 32],
+        "UniformPrice { units: 2 }",
+        deadline,
+        1,
+        0,
+    )?;
+    let details: Result<AuctionDetails, Error> =
+        session.call("get_auction_details", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert!(details.is_ok());
+
+    let alice = bidder(&mut session, 1);
+    let bob = bidder(&mut session, 2);
+    let charlie = bidder(&mut session, 3);
+    let seller_balance = session.sandbox().free_balance(&seller);
+    let bids = [(&alice, 10), (&bob, 30), (&charlie, 20)];
+    for (who, bid) in bids {
+        session.set_actor(who.clone());
+        let _: Result<(), Error> = session.call(
+            "bid",
+            &[arg(auction_id), sealed_bid(who, bid, [bid as u8; 32])],
+            Some(1),
+        ).or_reverted()??;
+    }
+    session.sandbox().build_blocks(2);
+    for (who, bid) in bids {
+        let _: Result<(), Error> = session.call(
+            "reveal_bid",
+            &[arg(auction_id), revealed_bid(who, bid, [bid as u8; 32])],
+            NO_ENDOWMENT,
+        ).or_reverted()??;
+    }
+    session.sandbox().build_blocks(REVEAL_PERIOD);
+    let res: Result<(), Error> =
+        session.call("complete", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+
+    // bob and charlie each win a unit and pay alice's bid
+    let outcome: Result<Option<AuctionOutcome<AccountId, u128>>, Error> =
+        session.call("get_outcome", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(
+        outcome,
+        Ok(Some(AuctionOutcome::Sale(vec![
+            AuctionResult {
+                winner: account(&bob),
+                debt: 10,
+            },
+            AuctionResult {
+                winner: account(&charlie),
+                debt: 10,
+            },
+        ])))
+    );
+    for who in [&bob, &charlie] {
+        session.set_actor(who.clone());
+        let res: Result<(), Error> =
+            session.call("claim", &[arg(auction_id)], Some(9)).or_reverted()??;
+        assert_eq!(res, Ok(()));
+    }
+    assert_eq!(session.sandbox().free_balance(&seller), seller_balance + 20);
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn auctions_are_indexed_and_paginated(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    let seller = session.get_actor();
+    let deadline = session.sandbox().block_number() + 2;
+    let mut auction_ids = Vec::new();
+    for i in 1..=5u8 {
+        auction_ids.push(new_auction(&mut session, [i; 32], deadline, 1)?);
+    }
+    let count: u32 = session.call("get_auction_count", &["All"], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(count, 5);
+
+    // pages keep the order the auctions were created in
+    let page: Result<Vec<AuctionDetails>, Error> =
+        session.call("get_auctions", &["1", "3"], NO_ENDOWMENT).or_reverted()??;
+    let page = page.expect("the page should be read");
+    assert_eq!(page.len(), 3);
+    for (details, auction_id) in page.iter().zip(&auction_ids[1..4]) {
+        let expected: Result<AuctionDetails, Error> =
+            session.call("get_auction_details", &[arg(*auction_id)], NO_ENDOWMENT).or_reverted()??;
+        assert_eq!(Ok(details.clone()), expected);
+    }
+    let page: Result<Vec<AuctionDetails>, Error> = session.call(
+        "get_auctions",
+        &["4".to_string(), MAX_PAGE_SIZE.to_string()],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(page.map(|p| p.len()), Ok(1));
+
+    let owned: Result<Vec<AuctionDetails>, Error> = session.call(
+        "get_auctions_by_owner",
+        &[seller.to_string(), "0".to_string(), "10".to_string()],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(owned.map(|p| p.len()), Ok(5));
+
+    // bidding lists the auction under the bidder, once
+    let alice = bidder(&mut session, 1);
+    session.set_actor(alice.clone());
+    for bid in [10, 11] {
+        let res: Result<(), Error> = session.call(
+            "bid",
+            &[arg(auction_ids[2]), sealed_bid(&alice, bid, [bid as u8; 32])],
+            Some(1),
+        ).or_reverted()??;
+        assert_eq!(res, Ok(()));
+    }
+    let count: u32 =
+        session
+            .call("get_auction_count", &[format!("Bidder({alice})")], NO_ENDOWMENT)
+            .or_reverted()??;
+    assert_eq!(count, 1);
+
+    // completing an auction moves it between the status lists
+    // (with no bids there is nothing to claim, so it is claimed straight away)
+    session.sandbox().build_blocks(2 + REVEAL_PERIOD);
+    let res: Result<(), Error> =
+        session.call("complete", &[arg(auction_ids[0])], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    let open: Result<Vec<AuctionDetails>, Error> =
+        session.call("get_auctions_by_status", &["Open", "0", "10"], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(open.map(|p| p.len()), Ok(4));
+    let claimed: Result<Vec<AuctionDetails>, Error> =
+        session
+            .call("get_auctions_by_status", &["Claimed", "0", "10"], NO_ENDOWMENT)
+            .or_reverted()??;
+    assert_eq!(claimed.map(|p| p.len()), Ok(1));
+    Ok(())
+}
+
+/// the number of auctions with a status
+fn count_with_status(session: &mut Session<DrandSandbox>, status: &str) -> TestResult<u32> {
+    Ok(session.call("get_auction_count", &[format!("Status({status})")], NO_ENDOWMENT)??)
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn lifecycle_transitions_are_enforced(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    // the reveal deadline cannot be before the deadline
+    let res: Result<AccountId, Error> = session.call(
+        "new_auction",
+        &[
+            format!("{:?}", [2u8; 32]),
+            "Vickrey".to_string(),
+            terms(10, 9, 1, 0),
+        ],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Err(Error::InvalidDeadline));
+
+    let deadline = session.sandbox().block_number() + 2;
+    let auction_id = new_auction(&mut session, [1; 32], deadline, 5)?;
+    let alice = bidder(&mut session, 1);
+    session.set_actor(alice.clone());
+    let res: Result<(), Error> = session.call(
+        "bid",
+        &[arg(auction_id), sealed_bid(&alice, 10, [10; 32])],
+        Some(5),
+    ).or_reverted()??;
+    assert_eq!(res, Ok(()));
+
+    // bids are sealed until the deadline
+    let res: Result<(), Error> = session.call(
+        "reveal_bid",
+        &[arg(auction_id), revealed_bid(&alice, 10, [10; 32])],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Err(Error::AuctionInProgress));
+    let res: Result<(), Error> =
+        session.call("complete", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::AuctionInProgress));
+
+    // between the deadline and the reveal deadline, bids are only revealed
+    session.sandbox().build_blocks(2);
+    let res: Result<(), Error> = session.call(
+        "bid",
+        &[arg(auction_id), sealed_bid(&alice, 11, [11; 32])],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Err(Error::BiddingClosed));
+    let res: Result<(), Error> =
+        session.call("complete", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::RevealPhaseInProgress));
+    let res: Result<(), Error> = session.call(
+        "reveal_bid",
+        &[arg(auction_id), revealed_bid(&alice, 10, [10; 32])],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    assert_eq!(count_with_status(&mut session, "RevealPhase")?, 1);
+    let res: Result<(), Error> =
+        session.call("claim", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::AuctionNotComplete));
+
+    // after the reveal deadline, the auction is completed exactly once
+    session.sandbox().build_blocks(REVEAL_PERIOD);
+    let res: Result<(), Error> = session.call(
+        "reveal_bid",
+        &[arg(auction_id), revealed_bid(&alice, 10, [10; 32])],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Err(Error::RevealPhaseClosed));
+    let res: Result<(), Error> =
+        session.call("complete", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    let res: Result<(), Error> =
+        session.call("complete", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::AuctionAlreadyComplete));
+    assert_eq!(count_with_status(&mut session, "Settled")?, 1);
+
+    // alice wins at the reserve price, their deposit covers it, and nothing is left to claim
+    let res: Result<(), Error> =
+        session.call("claim", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    assert_eq!(count_with_status(&mut session, "Claimed")?, 1);
+    let res: Result<(), Error> =
+        session.call("claim", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::NothingToClaim));
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn cancel_before_bids_is_free(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    let seller = session.get_actor();
+    let deadline = session.sandbox().block_number() + 2;
+    let auction_id = new_auction(&mut session, [1; 32], deadline, 5)?;
+
+    // only the seller can cancel
+    let alice = bidder(&mut session, 1);
+    session.set_actor(alice.clone());
+    let res: Result<(), Error> =
+        session.call("cancel_auction", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::NotAuctionOwner));
+
+    session.set_actor(seller);
+    let res: Result<(), Error> =
+        session.call("cancel_auction", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    assert_eq!(count_with_status(&mut session, "Cancelled")?, 1);
+    let res: Result<(), Error> =
+        session.call("cancel_auction", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::AuctionCancelled));
+
+    // a cancelled auction takes no bids and is never completed
+    session.set_actor(alice.clone());
+    let res: Result<(), Error> = session.call(
+        "bid",
+        &[arg(auction_id), sealed_bid(&alice, 10, [10; 32])],
+        Some(5),
+    ).or_reverted()??;
+    assert_eq!(res, Err(Error::AuctionCancelled));
+    session.sandbox().build_blocks(2 + REVEAL_PERIOD);
+    let res: Result<(), Error> =
+        session.call("complete", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::AuctionCancelled));
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn cancel_after_bids_pays_a_penalty(mut session: Session) -> TestResult {
+    let proxy = deploy_proxy(&mut session)?;
+    let seller = session.get_actor();
+    let deadline = session.sandbox().block_number() + 2;
+    let auction_id = new_auction(&mut session, [1; 32], deadline, 5)?;
+
+    let alice = bidder(&mut session, 1);
+    let bob = bidder(&mut session, 2);
+    let proxy_balance = session.sandbox().free_balance(&proxy);
+    for (who, bid) in [(&alice, 10), (&bob, 20), (&alice, 11)] {
+        session.set_actor(who.clone());
+        let res: Result<(), Error> = session.call(
+            "bid",
+            &[arg(auction_id), sealed_bid(who, bid, [bid as u8; 32])],
+            Some(5),
+        ).or_reverted()??;
+        assert_eq!(res, Ok(()));
+    }
+
+    // the seller matches the deposit of each of the two bidders
+    session.set_actor(seller.clone());
+    let res: Result<(), Error> =
+        session.call("cancel_auction", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::InvalidCurrencyAmountTransferred));
+    let res: Result<(), Error> =
+        session.call("cancel_auction", &[arg(auction_id)], Some(10)).or_reverted()??;
+    assert_eq!(res, Ok(()));
+
+    // each bidder reclaims their deposit and the penalty, once
+    for (who, amount) in [(&alice, 15), (&bob, 10)] {
+        session.set_actor(who.clone());
+        let res: Result<(), Error> =
+            session.call("claim", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+        assert_eq!(res, Ok(()));
+        let refunded = Refunded {
+            auction_id,
+            bidder: account(who),
+            amount,
+        };
+        assert_eq!(last_events(&mut session), vec![refunded.encode()]);
+        let res: Result<(), Error> =
+            session.call("claim", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+        assert_eq!(res, Err(Error::NothingToClaim));
+    }
+    assert_eq!(session.sandbox().free_balance(&proxy), proxy_balance);
+
+    // the seller cannot claim anything
+    session.set_actor(seller);
+    let res: Result<(), Error> =
+        session.call("claim", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::NothingToClaim));
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn cancel_fails_after_deadline(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    let deadline = session.sandbox().block_number() + 1;
+    let auction_id = new_auction(&mut session, [1; 32], deadline, 5)?;
+
+    session.sandbox().build_blocks(2);
+    let res: Result<(), Error> =
+        session.call("cancel_auction", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::BiddingClosed));
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn auction_for_an_existing_token_works(mut session: Session) -> TestResult {
+    // the seller owns a token of a collection the proxy did not deploy
+    let collection = session.deploy_bundle(
+        BundleProvider::Erc721.bundle()?,
+        "new",
+        NO_ARGS,
+        NO_SALT,
+        NO_ENDOWMENT,
+    )?;
+    let res: Result<(), PSP34Error> = session.call_with_address(
+        collection.clone(),
+        "PSP34Mintable::mint",
+        &["U32(7)"],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    let proxy = deploy_proxy(&mut session)?;
+    let seller = session.get_actor();
+    let deadline = session.sandbox().block_number() + 2;
+    let args = [
+        format!("{:?}", [1u8; 32]),
+        collection.to_string(),
+        "U32(7)".to_string(),
+        "Vickrey".to_string(),
+        terms(deadline, deadline + REVEAL_PERIOD, 1, 0),
+    ];
+
+    // the proxy can only take custody once the seller approves it
+    let res: Result<AccountId, Error> =
+        session.call("new_auction_for", &args, NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::AssetTransferFailed));
+    let res: Result<(), PSP34Error> = session.call_with_address(
+        collection.clone(),
+        "PSP34::approve",
+        &[proxy.to_string(), "Some(U32(7))".to_string(), "true".to_string()],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    let res: Result<AccountId, Error> =
+        session.call("new_auction_for", &args, NO_ENDOWMENT).or_reverted()??;
+    let auction_id = res.expect("the auction should be created");
+    let owner: Option<AccountId> = session.call_with_address(
+        collection.clone(),
+        "PSP34::owner_of",
+        &["U32(7)"],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(owner, Some(account(&proxy)));
+    let details: Result<AuctionDetails, Error> = session.call(
+        "get_auction_details_by_token",
+        &[collection.to_string(), "U32(7)".to_string()],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert!(details.is_ok());
+
+    let alice = bidder(&mut session, 1);
+    let seller_balance = session.sandbox().free_balance(&seller);
+    session.set_actor(alice.clone());
+    let res: Result<(), Error> = session.call(
+        "bid",
+        &[arg(auction_id), sealed_bid(&alice, 10, [10; 32])],
+        Some(1),
+    ).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    session.sandbox().build_blocks(2);
+    let res: Result<(), Error> = session.call(
+        "reveal_bid",
+        &[arg(auction_id), revealed_bid(&alice, 10, [10; 32])],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    session.sandbox().build_blocks(REVEAL_PERIOD);
+    let res: Result<(), Error> =
+        session.call("complete", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+
+    // alice is the only bidder, so they pay the reserve price of zero and receive the token
+    let res: Result<(), Error> =
+        session.call("claim", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    let owner: Option<AccountId> =
+        session
+            .call_with_address(collection, "PSP34::owner_of", &["U32(7)"], NO_ENDOWMENT)
+            .or_reverted()??;
+    assert_eq!(owner, Some(account(&alice)));
+    assert_eq!(session.sandbox().free_balance(&seller), seller_balance);
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn late_bids_extend_the_deadline(mut session: Session) -> TestResult {
+    deploy_proxy(&mut session)?;
+    register_auction_kinds(&mut session)?;
+    let start = session.sandbox().block_number();
+    let deadline = start + 5;
+    let extended_terms = |config: &str| {
+        [
+            format!("{:?}", [1u8; 32]),
+            config.to_string(),
+            format!(
+                "AuctionTerms {{ deadline: {deadline}, reveal_deadline: {}, deposit: 1, \
+                 reserve_price: 0, extension: Some(DeadlineExtension {{ window: 2, \
+                 extension: 3, max_extension: 4 }}) }}",
+                deadline + REVEAL_PERIOD,
+            ),
+        ]
+    };
+
+    // sealed bids are revealed after a fixed deadline, so it cannot be extended
+    let res: Result<AccountId, Error> =
+        session.call("new_auction", &extended_terms("Vickrey"), NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::InvalidAuctionConfig));
+    let res: Result<AccountId, Error> = session.call(
+        "new_auction",
+        &extended_terms("English { min_increment: 1 }"),
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    let auction_id = res.expect("the auction should be created");
+
+    let alice = bidder(&mut session, 1);
+    session.set_actor(alice);
+    // blocks to build before each bid, and whether it is accepted:
+    // - an early bid does not extend the deadline
+    // - a bid one block before the deadline extends it by 3 blocks, to start + 8
+    // - the next late bid is capped at the one block of extension left, to start + 9
+    // - the cap is reached, so the deadline stays at start + 9
+    for (blocks, amount, expected) in [
+        (0, 1, Ok(())),
+        (4, 2, Ok(())),
+        (3, 3, Ok(())),
+        (1, 4, Ok(())),
+        (1, 5, Err(Error::BiddingClosed)),
+    ] {
+        session.sandbox().build_blocks(blocks);
+        let res: Result<(), Error> =
+            session
+                .call("bid", &[arg(auction_id), format!("Open({amount})")], Some(1))
+                .or_reverted()??;
+        assert_eq!(res, expected, "bid of {amount}");
+    }
+
+    // the reveal deadline moved with the deadline
+    let res: Result<(), Error> =
+        session.call("complete", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::RevealPhaseInProgress));
+    session.sandbox().build_blocks(REVEAL_PERIOD);
+    let res: Result<(), Error> =
+        session.call("complete", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn only_the_owner_can_list_an_approved_token(mut session: Session) -> TestResult {
+    let collection = session.deploy_bundle(
+        BundleProvider::Erc721.bundle()?,
+        "new",
+        NO_ARGS,
+        NO_SALT,
+        NO_ENDOWMENT,
+    )?;
+    let _: Result<(), PSP34Error> = session.call_with_address(
+        collection.clone(),
+        "PSP34Mintable::mint",
+        &["U32(7)"],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    let proxy = deploy_proxy(&mut session)?;
+    let seller = account(&session.get_actor());
+    let _: Result<(), PSP34Error> = session.call_with_address(
+        collection.clone(),
+        "PSP34::approve",
+        &[proxy.to_string(), "Some(U32(7))".to_string(), "true".to_string()],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+
+    // alice lists the seller's token, which the proxy may move
+    let alice = bidder(&mut session, 1);
+    session.set_actor(alice);
+    let deadline = session.sandbox().block_number() + 2;
+    let res: Result<AccountId, Error> = session.call(
+        "new_auction_for",
+        &[
+            format!("{:?}", [1u8; 32]),
+            collection.to_string(),
+            "U32(7)".to_string(),
+            "Vickrey".to_string(),
+            terms(deadline, deadline + REVEAL_PERIOD, 1, 0),
+        ],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Err(Error::AssetTransferFailed));
+    let owner: Option<AccountId> = session.call_with_address(
+        collection.clone(),
+        "PSP34::owner_of",
+        &["U32(7)"],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(owner, Some(seller));
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn events_describe_a_whole_auction(mut session: Session) -> TestResult {
+    let collection = session.deploy_bundle(
+        BundleProvider::Erc721.bundle()?,
+        "new",
+        NO_ARGS,
+        NO_SALT,
+        NO_ENDOWMENT,
+    )?;
+    let _: Result<(), PSP34Error> = session.call_with_address(
+        collection.clone(),
+        "PSP34Mintable::mint",
+        &["U32(7)"],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    let proxy = deploy_proxy(&mut session)?;
+    let seller = account(&session.get_actor());
+    let _: Result<(), PSP34Error> = session.call_with_address(
+        collection.clone(),
+        "PSP34::approve",
+        &[proxy.to_string(), "Some(U32(7))".to_string(), "true".to_string()],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    let deadline = session.sandbox().block_number() + 2;
+    let res: Result<AccountId, Error> = session.call(
+        "new_auction_for",
+        &[
+            format!("{:?}", [1u8; 32]),
+            collection.to_string(),
+            "U32(7)".to_string(),
+            "Vickrey".to_string(),
+            terms(deadline, deadline + REVEAL_PERIOD, 5, 0),
+        ],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    let auction_id = res.expect("the auction should be created");
+    let proxy = account(&proxy);
+    assert_eq!(
+        last_events(&mut session),
+        vec![
+            nft_transfer(seller, proxy, 7),
+            AuctionCreated {
+                auction_id,
+                owner: seller,
+                collection: account(&collection),
+                asset_ids: vec![Id::U32(7)],
+                kind: AuctionKind::Vickrey,
+                deadline,
+                reveal_deadline: deadline + REVEAL_PERIOD,
+            }
+            .encode(),
+        ]
+    );
+
+    // charlie never reveals their bid
+    let alice = bidder(&mut session, 1);
+    let bob = bidder(&mut session, 2);
+    let charlie = bidder(&mut session, 3);
+    let bids = [(&alice, 10), (&bob, 20), (&charlie, 30)];
+    for (who, bid) in bids {
+        session.set_actor(who.clone());
+        let _: Result<(), Error> = session.call(
+            "bid",
+            &[arg(auction_id), sealed_bid(who, bid, [bid as u8; 32])],
+            Some(5),
+        ).or_reverted()??;
+        let bid_placed = BidPlaced {
+            auction_id,
+            bidder: account(who),
+            deposit: 5,
+        };
+        assert_eq!(last_events(&mut session), vec![bid_placed.encode()]);
+    }
+    session.sandbox().build_blocks(2);
+    for (who, bid) in &bids[..2] {
+        let _: Result<(), Error> = session.call(
+            "reveal_bid",
+            &[arg(auction_id), revealed_bid(who, *bid, [*bid as u8; 32])],
+            NO_ENDOWMENT,
+        ).or_reverted()??;
+        let bid_revealed = BidRevealed {
+            auction_id,
+            bidder: account(who),
+            bid: *bid,
+        };
+        assert_eq!(last_events(&mut session), vec![bid_revealed.encode()]);
+    }
+
+    session.sandbox().build_blocks(REVEAL_PERIOD);
+    let _: Result<(), Error> =
+        session.call("complete", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    let settled = AuctionSettled {
+        auction_id,
+        outcome: AuctionOutcome::Sale(vec![AuctionResult {
+            winner: account(&bob),
+            debt: 10,
+        }]),
+    };
+    let slashed = Slashed {
+        auction_id,
+        bidder: account(&charlie),
+        amount: 5,
+    };
+    assert_eq!(last_events(&mut session), vec![slashed.encode(), settled.encode()]);
+
+    // bob tops up their deposit to the second price, alice is refunded
+    session.set_actor(bob.clone());
+    let _: Result<(), Error> = session.call("claim", &[arg(auction_id)], Some(5)).or_reverted()??;
+    let claimed = Claimed {
+        auction_id,
+        winner: account(&bob),
+        asset_id: Id::U32(7),
+        price: 10,
+        protocol_fee: 0,
+        royalty: 0,
+    };
+    assert_eq!(
+        last_events(&mut session),
+        vec![nft_transfer(proxy, account(&bob), 7), claimed.encode()]
+    );
+    session.set_actor(alice.clone());
+    let _: Result<(), Error> =
+        session.call("claim", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+    let refunded = Refunded {
+        auction_id,
+        bidder: account(&alice),
+        amount: 5,
+    };
+    assert_eq!(last_events(&mut session), vec![refunded.encode()]);
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn sales_pay_the_protocol_fee_and_royalty(mut session: Session) -> TestResult {
+    let collection = session.deploy_bundle(
+        BundleProvider::Erc721.bundle()?,
+        "new",
+        NO_ARGS,
+        NO_SALT,
+        NO_ENDOWMENT,
+    )?;
+    let creator = bidder(&mut session, 8);
+    // token 7 pays a 10% royalty, token 8 a royalty which leaves no room for the fee
+    for (id, royalty) in [("U32(7)", "1000"), ("U32(8)", "9600")] {
+        let res: Result<(), PSP34Error> = session.call_with_address(
+            collection.clone(),
+            "mint_with_royalty",
+            &[id, &creator.to_string(), royalty],
+            NO_ENDOWMENT,
+        ).or_reverted()??;
+        assert_eq!(res, Ok(()));
+    }
+    let proxy = deploy_proxy(&mut session)?;
+    let seller = session.get_actor();
+    let treasury = bidder(&mut session, 9);
+    let res: Result<(), Error> =
+        session.call("set_protocol_fee", &["10001"], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::InvalidFee));
+    let res: Result<(), Error> =
+        session.call("set_protocol_fee", &["500"], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    let res: Result<(), Error> =
+        session
+            .call("transfer_ownership", &[arg(account(&treasury))], NO_ENDOWMENT)
+            .or_reverted()??;
+    assert_eq!(res, Ok(()));
+
+    let deadline = session.sandbox().block_number() + 2;
+    let mut auction_ids = vec![];
+    for (name, id) in [(7u8, "U32(7)"), (8, "U32(8)")] {
+        let _: Result<(), PSP34Error> = session.call_with_address(
+            collection.clone(),
+            "PSP34::approve",
+            &[proxy.to_string(), format!("Some({id})"), "true".to_string()],
+            NO_ENDOWMENT,
+        ).or_reverted()??;
+        let res: Result<AccountId, Error> = session.call(
+            "new_auction_for",
+            &[
+                format!("{:?}", [name; 32]),
+                collection.to_string(),
+                id.to_string(),
+                "Vickrey".to_string(),
+                terms(deadline, deadline + REVEAL_PERIOD, 1, 100),
+            ],
+            NO_ENDOWMENT,
+        ).or_reverted()??;
+        auction_ids.push(res.expect("the auction should be created"));
+    }
+
+    // alice is the only bidder in both auctions, so they pay the reserve price
+    let alice = bidder(&mut session, 1);
+    session.set_actor(alice.clone());
+    for auction_id in &auction_ids {
+        let _: Result<(), Error> = session.call(
+            "bid",
+            &[arg(*auction_id), sealed_bid(&alice, 100, [100; 32])],
+            Some(1),
+        ).or_reverted()??;
+    }
+    session.sandbox().build_blocks(2);
+    for auction_id in &auction_ids {
+        let _: Result<(), Error> = session.call(
+            "reveal_bid",
+            &[arg(*auction_id), revealed_bid(&alice, 100, [100; 32])],
+            NO_ENDOWMENT,
+        ).or_reverted()??;
+    }
+    session.sandbox().build_blocks(REVEAL_PERIOD);
+    for auction_id in &auction_ids {
+        let res: Result<(), Error> =
+            session.call("complete", &[arg(*auction_id)], NO_ENDOWMENT).or_reverted()??;
+        assert_eq!(res, Ok(()));
+    }
+
+    let balances = |session: &mut Session<DrandSandbox>| {
+        [&seller, &creator, &treasury].map(|who| session.sandbox().free_balance(who))
+    };
+    let before = balances(&mut session);
+    let res: Result<(), Error> =
+        session.call("claim", &[arg(auction_ids[0])], Some(99)).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    let claimed = Claimed {
+        auction_id: auction_ids[0],
+        winner: account(&alice),
+        asset_id: Id::U32(7),
+        price: 100,
+        protocol_fee: 5,
+        royalty: 10,
+    };
+    assert_eq!(last_events(&mut session).last(), Some(&claimed.encode()));
+    let after = balances(&mut session);
+    assert_eq!(after, [before[0] + 85, before[1] + 10, before[2] + 5]);
+
+    // a fee and royalty above the price are never paid
+    let res: Result<(), Error> =
+        session.call("claim", &[arg(auction_ids[1])], Some(99)).or_reverted()??;
+    assert_eq!(res, Err(Error::PaymentExceedsPrice));
+    assert_eq!(balances(&mut session), after);
+    let owner: Option<AccountId> =
+        session
+            .call_with_address(collection, "PSP34::owner_of", &["U32(8)"], NO_ENDOWMENT)
+            .or_reverted()??;
+    assert_eq!(owner, Some(account(&proxy)));
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn minting_is_restricted_and_ids_are_derived(mut session: Session) -> TestResult {
+    let collection = session.deploy_bundle(
+        BundleProvider::Erc721.bundle()?,
+        "new",
+        NO_ARGS,
+        NO_SALT,
+        NO_ENDOWMENT,
+    )?;
+    let proxy = deploy_proxy(&mut session)?;
+    let seller = session.get_actor();
+
+    // the proxy mints the assets of its auctions with ids its collection derives,
+    // which never repeat even though every auction has the same name and pulse
+    let deadline = session.sandbox().block_number() + 2;
+    let mut asset_ids = vec![];
+    for _ in 0..2 {
+        let auction_id = new_auction(&mut session, [1; 32], deadline, 1)?;
+        let minted = minted_ids(&mut session);
+        let [Id::U128(asset_id)] = minted[..] else {
+            panic!("one U128 asset should be minted, got {minted:?}");
+        };
+        asset_ids.push(asset_id);
+        let details: Result<AuctionDetails, Error> = session.call(
+            "get_auction_details_by_asset_id",
+            &[format!("U128({asset_id})")],
+            NO_ENDOWMENT,
+        ).or_reverted()??;
+        let details = details.expect("the asset should be on sale");
+        let by_id: Result<AuctionDetails, Error> =
+            session.call("get_auction_details", &[arg(auction_id)], NO_ENDOWMENT).or_reverted()??;
+        assert_eq!(by_id, Ok(details));
+    }
+    assert_eq!(asset_ids.iter().map(|id| *id as u64).collect::<Vec<_>>(), vec![0, 1]);
+
+    // only the account which instantiated a collection can mint
+    let alice = bidder(&mut session, 1);
+    session.set_actor(alice.clone());
+    let res: Result<(), PSP34Error> = session.call_with_address(
+        collection.clone(),
+        "PSP34Mintable::mint",
+        &["U32(7)"],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Err(PSP34Error::Custom(String::from("MissingRole"))));
+    session.set_actor(seller.clone());
+    let res: Result<Id, PSP34Error> =
+        session
+            .call_with_address(collection.clone(), "mint_next", NO_ARGS, NO_ENDOWMENT)
+            .or_reverted()??;
+    let id = res.expect("the seller should mint");
+    let arg_id = match id {
+        Id::U128(id) => format!("U128({id})"),
+        other => panic!("unexpected id {other:?}"),
+    };
+
+    // the proxy does not implement `Erc721Receiver`, so it cannot be sent tokens safely
+    let res: Result<(), PSP34Error> = session.call_with_address(
+        collection.clone(),
+        "safe_transfer_from",
+        &[seller.to_string(), proxy.to_string(), arg_id.clone(), "[]".to_string()],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    let rejected = String::from("the recipient did not accept the token");
+    assert_eq!(res, Err(PSP34Error::SafeTransferCheckFailed(rejected)));
+    let res: Result<(), PSP34Error> = session.call_with_address(
+        collection.clone(),
+        "safe_transfer_from",
+        &[seller.to_string(), alice.to_string(), arg_id.clone(), "[]".to_string()],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    let owner: Option<AccountId> =
+        session
+            .call_with_address(collection, "PSP34::owner_of", &[arg_id], NO_ENDOWMENT)
+            .or_reverted()??;
+    assert_eq!(owner, Some(account(&alice)));
+    Ok(())
+}
@@ -0,0 +1,239 @@
+use drink::{
+    sandbox_api::prelude::*,
+    session::{Session, NO_ARGS, NO_ENDOWMENT, NO_SALT},
+    AccountId32,
+};
+use erc721::PSP34Error;
+use idl_contract_extension_sandbox::{DrandSandbox, Reverted};
+use ink::primitives::{AccountId, Hash};
+use transmutation::{Error, SwapOutcome};
+
+#[drink::contract_bundle_provider]
+enum BundleProvider {}
+
+type TestResult<T = ()> = Result<T, Box<dyn std::error::Error>>;
+
+/// the participants, the collection and the transmutation contract of a swap
+struct Setup {
+    alice: AccountId32,
+    bob: AccountId32,
+    collection: AccountId32,
+    transmutation: AccountId32,
+}
+
+/// mint token 1 to alice (the session's actor) and token 2 to bob,
+/// then deploy a transmutation contract
+fn setup(session: &mut Session<DrandSandbox>) -> TestResult<Setup> {
+    let alice = session.get_actor();
+    let bob = AccountId32::new([2; 32]);
+    session.sandbox().mint_into(&bob, 1_000_000_000_000).expect("minting should work");
+    let collection = session.deploy_bundle(
+        BundleProvider::Erc721.bundle()?,
+        "new",
+        NO_ARGS,
+        NO_SALT,
+        NO_ENDOWMENT,
+    )?;
+    for id in ["U32(1)", "U32(2)"] {
+        let res: Result<(), PSP34Error> = session.call_with_address(
+            collection.clone(),
+            "PSP34Mintable::mint",
+            &[id],
+            NO_ENDOWMENT,
+        ).or_reverted()??;
+        assert_eq!(res, Ok(()));
+    }
+    let res: Result<(), PSP34Error> = session.call_with_address(
+        collection.clone(),
+        "PSP34::transfer",
+        &[bob.to_string(), "U32(2)".to_string(), "[]".to_string()],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    let transmutation = session.deploy_bundle(
+        BundleProvider::Transmutation.bundle()?,
+        "new",
+        NO_ARGS,
+        NO_SALT,
+        NO_ENDOWMENT,
+    )?;
+    Ok(Setup { alice, bob, collection, transmutation })
+}
+
+/// encode a token of the collection as a message argument
+fn asset(collection: &AccountId32, id: u32) -> String {
+    format!("Asset {{ collection: {collection}, id: U32({id}) }}")
+}
+
+/// alice proposes to swap token 1 for bob's token 2 at the deadline
+fn new_swap(session: &mut Session<DrandSandbox>, s: &Setup, deadline: u32) -> TestResult<Hash> {
+    session.set_actor(s.alice.clone());
+    let res: Result<Hash, Error> = session.call(
+        "try_new_swap",
+        &[
+            s.bob.to_string(),
+            asset(&s.collection, 1),
+            asset(&s.collection, 2),
+            deadline.to_string(),
+        ],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    Ok(res.expect("the swap should be created"))
+}
+
+/// approve the transmutation contract to move the token of the actor
+fn approve(session: &mut Session<DrandSandbox>, s: &Setup, id: u32) -> TestResult {
+    let res: Result<(), PSP34Error> = session.call_with_address(
+        s.collection.clone(),
+        "PSP34::approve",
+        &[s.transmutation.to_string(), format!("Some(U32({id}))"), "true".to_string()],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    Ok(())
+}
+
+fn owner_of(session: &mut Session<DrandSandbox>, s: &Setup, id: u32) -> TestResult<AccountId> {
+    let owner: Option<AccountId> = session.call_with_address(
+        s.collection.clone(),
+        "PSP34::owner_of",
+        &[format!("U32({id})")],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    Ok(owner.expect("the token should exist"))
+}
+
+fn account(account_id: &AccountId32) -> AccountId {
+    AccountId::from(*AsRef::<[u8; 32]>::as_ref(account_id))
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn transmuted_tokens_are_swapped(mut session: Session) -> TestResult {
+    let s = setup(&mut session)?;
+    let deadline = session.sandbox().block_number() + 2;
+    let swap_id = new_swap(&mut session, &s, deadline)?;
+    approve(&mut session, &s, 1)?;
+    session.set_actor(s.bob.clone());
+    approve(&mut session, &s, 2)?;
+
+    session.sandbox().build_blocks(2);
+    for who in [&s.alice, &s.bob] {
+        session.set_actor(who.clone());
+        let res: Result<(), Error> =
+            session.call("transmute", NO_ARGS, NO_ENDOWMENT).or_reverted()??;
+        assert_eq!(res, Ok(()));
+        let res: Result<(), Error> =
+            session.call("transmute", NO_ARGS, NO_ENDOWMENT).or_reverted()??;
+        assert_eq!(res, Err(Error::AlreadyEscrowed));
+    }
+    // the contract holds both tokens until the swap completes
+    assert_eq!(owner_of(&mut session, &s, 1)?, account(&s.transmutation));
+    assert_eq!(owner_of(&mut session, &s, 2)?, account(&s.transmutation));
+
+    let res: Result<SwapOutcome, Error> =
+        session.call("complete", &[format!("{swap_id:?}")], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(SwapOutcome::Swapped));
+    assert_eq!(owner_of(&mut session, &s, 1)?, account(&s.bob));
+    assert_eq!(owner_of(&mut session, &s, 2)?, account(&s.alice));
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn escrowed_tokens_are_refunded_when_a_participant_does_not_transmute(
+    mut session: Session,
+) -> TestResult {
+    let s = setup(&mut session)?;
+    let deadline = session.sandbox().block_number() + 2;
+    let swap_id = new_swap(&mut session, &s, deadline)?;
+    approve(&mut session, &s, 1)?;
+
+    session.sandbox().build_blocks(2);
+    let res: Result<(), Error> = session.call("transmute", NO_ARGS, NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    // bob can still transmute at the deadline
+    let res: Result<SwapOutcome, Error> =
+        session.call("complete", &[format!("{swap_id:?}")], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::InvalidBlockNumber));
+
+    session.sandbox().build_block();
+    let res: Result<SwapOutcome, Error> =
+        session.call("complete", &[format!("{swap_id:?}")], NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(SwapOutcome::Refunded));
+    assert_eq!(owner_of(&mut session, &s, 1)?, account(&s.alice));
+    assert_eq!(owner_of(&mut session, &s, 2)?, account(&s.bob));
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn tokens_must_be_approved_to_transmute(mut session: Session) -> TestResult {
+    let s = setup(&mut session)?;
+    let deadline = session.sandbox().block_number() + 1;
+    new_swap(&mut session, &s, deadline)?;
+
+    session.sandbox().build_block();
+    let res: Result<(), Error> = session.call("transmute", NO_ARGS, NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Err(Error::TransferFailed));
+    assert_eq!(owner_of(&mut session, &s, 1)?, account(&s.alice));
+    // the failed escrow is reverted, so alice can still transmute once approved
+    approve(&mut session, &s, 1)?;
+    let res: Result<(), Error> = session.call("transmute", NO_ARGS, NO_ENDOWMENT).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    Ok(())
+}
+
+#[drink::test(sandbox = DrandSandbox)]
+fn only_the_owner_can_escrow_a_token(mut session: Session) -> TestResult {
+    let s = setup(&mut session)?;
+    // carol owns token 3, and lets the contract move all of her tokens
+    let carol = AccountId32::new([3; 32]);
+    session.sandbox().mint_into(&carol, 1_000_000_000_000).expect("minting should work");
+    let res: Result<(), PSP34Error> = session.call_with_address(
+        s.collection.clone(),
+        "PSP34Mintable::mint",
+        &["U32(3)"],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    let res: Result<(), PSP34Error> = session.call_with_address(
+        s.collection.clone(),
+        "PSP34::transfer",
+        &[carol.to_string(), "U32(3)".to_string(), "[]".to_string()],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Ok(()));
+    session.set_actor(carol.clone());
+    let res: Result<(), PSP34Error> = session.call_with_address(
+        s.collection.clone(),
+        "PSP34::approve",
+        &[s.transmutation.to_string(), "None".to_string(), "true".to_string()],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Ok(()));
+
+    // alice offers carol's token to bob
+    session.set_actor(s.alice.clone());
+    let deadline = session.sandbox().block_number() + 1;
+    let res: Result<Hash, Error> = session.call_with_address(
+        s.transmutation.clone(),
+        "try_new_swap",
+        &[
+            s.bob.to_string(),
+            asset(&s.collection, 3),
+            asset(&s.collection, 2),
+            deadline.to_string(),
+        ],
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert!(res.is_ok());
+
+    session.sandbox().build_block();
+    let res: Result<(), Error> = session.call_with_address(
+        s.transmutation.clone(),
+        "transmute",
+        NO_ARGS,
+        NO_ENDOWMENT,
+    ).or_reverted()??;
+    assert_eq!(res, Err(Error::NotOwner));
+    assert_eq!(owner_of(&mut session, &s, 3)?, account(&carol));
+    Ok(())
+}
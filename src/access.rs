@@ -0,0 +1,123 @@
+use ink::{primitives::AccountId, storage::Mapping};
+
+/// identifies a role, each contract defines its own roles
+pub type RoleId = u32;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum AccessError {
+    /// the caller is not the owner
+    NotOwner,
+    /// the caller has not been granted the role
+    MissingRole,
+}
+
+/// A single owner, e.g. the admin of a contract or the proxy that deployed it
+///
+/// It is stored like a plain `AccountId`, so an `owner: AccountId` field can be
+/// replaced with an `Ownable` without migrating the contract's storage.
+#[ink::storage_item]
+#[derive(Debug)]
+pub struct Ownable {
+    owner: AccountId,
+}
+
+impl Ownable {
+    pub fn new(owner: AccountId) -> Self {
+        Self { owner }
+    }
+
+    /// the current owner
+    pub fn owner(&self) -> AccountId {
+        self.owner
+    }
+
+    /// fails unless the caller is the owner
+    pub fn ensure_owner(&self, caller: AccountId) -> Result<(), AccessError> {
+        if caller != self.owner {
+            return Err(AccessError::NotOwner);
+        }
+        Ok(())
+    }
+
+    /// hand ownership to `new_owner`, callable only by the owner
+    ///
+    /// * `caller`: the account making the call
+    /// * `new_owner`: the account which becomes the owner
+    ///
+    pub fn transfer_ownership(
+        &mut self,
+        caller: AccountId,
+        new_owner: AccountId,
+    ) -> Result<(), AccessError> {
+        self.ensure_owner(caller)?;
+        self.owner = new_owner;
+        Ok(())
+    }
+}
+
+/// Accounts granted roles, e.g. the minters of a token
+///
+/// Roles are granted and revoked by the contract, usually after checking
+/// the caller is the `Ownable` owner.
+#[ink::storage_item]
+#[derive(Debug, Default)]
+pub struct Roles {
+    members: Mapping<(RoleId, AccountId), ()>,
+}
+
+impl Roles {
+    /// grant the role to the account
+    pub fn grant(&mut self, role: RoleId, account: AccountId) {
+        self.members.insert((role, account), &());
+    }
+
+    /// revoke the role from the account
+    pub fn revoke(&mut self, role: RoleId, account: AccountId) {
+        self.members.remove((role, account));
+    }
+
+    /// whether the account has been granted the role
+    pub fn has_role(&self, role: RoleId, account: AccountId) -> bool {
+        self.members.contains((role, account))
+    }
+
+    /// fails unless the account has been granted the role
+    pub fn ensure_role(&self, role: RoleId, account: AccountId) -> Result<(), AccessError> {
+        if !self.has_role(role, account) {
+            return Err(AccessError::MissingRole);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINTER: RoleId = 1;
+
+    #[test]
+    fn only_the_owner_transfers_ownership() {
+        let (alice, bob) = (AccountId::from([1; 32]), AccountId::from([2; 32]));
+        let mut ownable = Ownable::new(alice);
+        assert_eq!(ownable.ensure_owner(bob), Err(AccessError::NotOwner));
+        assert_eq!(ownable.transfer_ownership(bob, bob), Err(AccessError::NotOwner));
+        assert_eq!(ownable.transfer_ownership(alice, bob), Ok(()));
+        assert_eq!(ownable.owner(), bob);
+        assert_eq!(ownable.ensure_owner(alice), Err(AccessError::NotOwner));
+        assert_eq!(ownable.ensure_owner(bob), Ok(()));
+    }
+
+    #[ink::test]
+    fn roles_are_granted_and_revoked() {
+        let alice = AccountId::from([1; 32]);
+        let mut roles = Roles::default();
+        assert_eq!(roles.ensure_role(MINTER, alice), Err(AccessError::MissingRole));
+        roles.grant(MINTER, alice);
+        assert!(roles.has_role(MINTER, alice));
+        assert!(!roles.has_role(MINTER + 1, alice));
+        roles.revoke(MINTER, alice);
+        assert_eq!(roles.ensure_role(MINTER, alice), Err(AccessError::MissingRole));
+    }
+}
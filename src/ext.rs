@@ -18,6 +18,8 @@ pub enum DrandErrorCode {
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+// the scale derive casts the index of variants with fields to u8
+#[allow(clippy::cast_possible_truncation)]
 pub enum DrandError {
   ErrorCode(DrandErrorCode), 
   BufferTooSmall { required_bytes: u32 },
@@ -0,0 +1,148 @@
+use ink::{prelude::vec::Vec, primitives::AccountId, storage::Mapping};
+
+/// the maximum number of entries returned by a single query
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// Lists of accounts (e.g. the contracts a factory deployed), one list per key,
+/// stored one entry per cell
+///
+/// Each list keeps the order accounts were added in, until one is removed
+/// (the last account of the list takes its position).
+#[ink::storage_item]
+#[derive(Debug)]
+pub struct Index<K: scale::Encode + ink::storage::traits::Packed> {
+    /// the account at each position of each list
+    entries: Mapping<(K, u32), AccountId>,
+    /// the position of each account in each list
+    positions: Mapping<(K, AccountId), u32>,
+    /// the length of each list
+    lens: Mapping<K, u32>,
+}
+
+// derived, `Default` would need the keys to have a default too
+impl<K: scale::Encode + ink::storage::traits::Packed> Default for Index<K> {
+    fn default() -> Self {
+        Self {
+            entries: Mapping::default(),
+            positions: Mapping::default(),
+            lens: Mapping::default(),
+        }
+    }
+}
+
+impl<K: scale::Encode + ink::storage::traits::Packed + Clone> Index<K> {
+    /// add the account to the end of the list, unless it is already listed
+    /// returns true if the account was added
+    pub fn insert(&mut self, key: K, account: AccountId) -> bool {
+        if self.contains(key.clone(), account) {
+            return false;
+        }
+        let len = self.len(key.clone());
+        self.entries.insert((&key, len), &account);
+        self.positions.insert((&key, account), &len);
+        self.lens.insert(&key, &len.saturating_add(1));
+        true
+    }
+
+    /// remove the account from the list
+    /// returns true if the account was listed
+    pub fn remove(&mut self, key: K, account: AccountId) -> bool {
+        let Some(position) = self.positions.take((&key, account)) else {
+            return false;
+        };
+        let last = self.len(key.clone()).saturating_sub(1);
+        if position != last {
+            let moved = self
+                .entries
+                .get((&key, last))
+                .expect("every position below the length has an entry");
+            self.entries.insert((&key, position), &moved);
+            self.positions.insert((&key, moved), &position);
+        }
+        self.entries.remove((&key, last));
+        if last == 0 {
+            self.lens.remove(&key);
+        } else {
+            self.lens.insert(&key, &last);
+        }
+        true
+    }
+
+    /// whether the account is in the list
+    pub fn contains(&self, key: K, account: AccountId) -> bool {
+        self.positions.contains((&key, account))
+    }
+
+    /// the number of accounts in the list
+    pub fn len(&self, key: K) -> u32 {
+        self.lens.get(&key).unwrap_or(0)
+    }
+
+    /// the last account added to the list
+    pub fn last(&self, key: K) -> Option<AccountId> {
+        self.len(key.clone())
+            .checked_sub(1)
+            .and_then(|last| self.entries.get((&key, last)))
+    }
+
+    /// up to `limit` accounts of the list, starting at `offset`
+    /// at most `MAX_PAGE_SIZE` accounts are read
+    pub fn page(&self, key: K, offset: u32, limit: u32) -> Vec<AccountId> {
+        let end = offset
+            .saturating_add(limit.min(MAX_PAGE_SIZE))
+            .min(self.len(key.clone()));
+        (offset..end)
+            .filter_map(|position| self.entries.get((&key, position)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[ink::test]
+    fn insert_appends_once() {
+        let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+        let mut index = Index::<u8>::default();
+        assert!(index.insert(0, accounts.alice));
+        assert!(index.insert(0, accounts.bob));
+        assert!(!index.insert(0, accounts.alice));
+        assert_eq!(index.len(0), 2);
+        assert_eq!(index.len(1), 0);
+        assert_eq!(index.last(0), Some(accounts.bob));
+        assert_eq!(index.last(1), None);
+        assert_eq!(index.page(0, 0, 10), vec![accounts.alice, accounts.bob]);
+    }
+
+    #[ink::test]
+    fn remove_moves_the_last_entry() {
+        let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+        let mut index = Index::<u8>::default();
+        for account in [accounts.alice, accounts.bob, accounts.charlie] {
+            index.insert(0, account);
+        }
+        assert!(index.remove(0, accounts.alice));
+        assert!(!index.remove(0, accounts.alice));
+        assert_eq!(index.page(0, 0, 10), vec![accounts.charlie, accounts.bob]);
+        assert_eq!(index.page(0, 1, 10), vec![accounts.bob]);
+        assert!(index.remove(0, accounts.bob));
+        assert!(index.remove(0, accounts.charlie));
+        assert_eq!(index.len(0), 0);
+        assert!(index.page(0, 0, 10).is_empty());
+    }
+
+    #[ink::test]
+    fn page_is_bounded() {
+        let mut index = Index::<u8>::default();
+        for i in 0..(MAX_PAGE_SIZE + 10) {
+            let mut bytes = [0u8; 32];
+            bytes[..4].copy_from_slice(&i.to_le_bytes());
+            index.insert(0, AccountId::from(bytes));
+        }
+        assert_eq!(index.page(0, 0, u32::MAX).len(), MAX_PAGE_SIZE as usize);
+        assert_eq!(index.page(0, 105, 10).len(), 5);
+        assert!(index.page(0, 200, 10).is_empty());
+        assert_eq!(index.page(0, u32::MAX, u32::MAX), vec![]);
+    }
+}
@@ -1,5 +1,13 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 
+/// ownership and roles, shared by the example contracts
+pub mod access;
 /// the drand network chain extension
 pub mod ext;
+/// paginated lists of accounts, kept in contract storage
+pub mod index;
+/// the PSP34 non-fungible token standard, implemented by the example collections
+pub mod psp34;
+/// win/loss statistics and leaderboards for round-based games
+pub mod stats;
 pub mod types;
@@ -0,0 +1,160 @@
+use crate::access::AccessError;
+use ink::{
+    prelude::{string::String, vec::Vec},
+    primitives::AccountId,
+};
+
+/// the id of a token, any of the integer widths or raw bytes
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+// the scale derive casts the index of variants with fields to u8
+#[allow(clippy::cast_possible_truncation)]
+pub enum Id {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    Bytes(Vec<u8>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+#[allow(clippy::cast_possible_truncation)]
+pub enum PSP34Error {
+    /// an error specific to the collection
+    Custom(String),
+    /// the caller tried to approve themselves
+    SelfApprove,
+    /// the caller is neither the owner of the token nor approved to move it
+    NotApproved,
+    /// the token has already been minted
+    TokenExists,
+    /// the token does not exist
+    TokenNotExists,
+    /// the recipient did not accept the token
+    SafeTransferCheckFailed(String),
+}
+
+impl From<AccessError> for PSP34Error {
+    fn from(error: AccessError) -> Self {
+        let name = match error {
+            AccessError::NotOwner => "NotOwner",
+            AccessError::MissingRole => "MissingRole",
+        };
+        PSP34Error::Custom(String::from(name))
+    }
+}
+
+/// The PSP34 non-fungible token standard
+///
+/// The trait names are part of the message selectors (e.g. `PSP34::transfer`),
+/// so they must not be renamed.
+#[ink::trait_definition]
+pub trait PSP34 {
+    /// the id of the collection, under which collection-wide attributes are set
+    #[ink(message)]
+    fn collection_id(&self) -> Id;
+
+    /// the number of tokens owned by `owner`
+    #[ink(message)]
+    fn balance_of(&self, owner: AccountId) -> u32;
+
+    /// the owner of the token, if it exists
+    #[ink(message)]
+    fn owner_of(&self, id: Id) -> Option<AccountId>;
+
+    /// whether `operator` can move the token `id` of `owner`,
+    /// or every token of `owner` when `id` is `None`
+    #[ink(message)]
+    fn allowance(&self, owner: AccountId, operator: AccountId, id: Option<Id>) -> bool;
+
+    /// approve (or disapprove) `operator` to move the token `id` of the caller,
+    /// or every token of the caller when `id` is `None`
+    #[ink(message)]
+    fn approve(
+        &mut self,
+        operator: AccountId,
+        id: Option<Id>,
+        approved: bool,
+    ) -> Result<(), PSP34Error>;
+
+    /// move the token `id` from its owner to `to`,
+    /// callable by the owner or an account approved by them
+    #[ink(message)]
+    fn transfer(&mut self, to: AccountId, id: Id, data: Vec<u8>) -> Result<(), PSP34Error>;
+
+    /// the number of tokens in existence
+    #[ink(message)]
+    fn total_supply(&self) -> u128;
+}
+
+/// The PSP34 metadata extension
+#[ink::trait_definition]
+pub trait PSP34Metadata {
+    /// the attribute `key` of the token `id` (or of the collection, for its `collection_id`)
+    #[ink(message)]
+    fn get_attribute(&self, id: Id, key: Vec<u8>) -> Option<Vec<u8>>;
+}
+
+/// The PSP34 enumerable extension
+#[ink::trait_definition]
+pub trait PSP34Enumerable {
+    /// the token at `index` of the tokens of `owner`
+    #[ink(message)]
+    fn owners_token_by_index(&self, owner: AccountId, index: u128) -> Result<Id, PSP34Error>;
+
+    /// the token at `index` of every token
+    #[ink(message)]
+    fn token_by_index(&self, index: u128) -> Result<Id, PSP34Error>;
+}
+
+/// The PSP34 mintable extension
+#[ink::trait_definition]
+pub trait PSP34Mintable {
+    /// create the token `id`, owned by the caller
+    #[ink(message)]
+    fn mint(&mut self, id: Id) -> Result<(), PSP34Error>;
+}
+
+/// The PSP34 burnable extension
+#[ink::trait_definition]
+pub trait PSP34Burnable {
+    /// destroy the token `id` of `account`
+    #[ink(message)]
+    fn burn(&mut self, account: AccountId, id: Id) -> Result<(), PSP34Error>;
+}
+
+/// EIP-2981 style royalties for PSP34 collections (not part of the standard)
+///
+/// Marketplaces call it with `try_invoke`, collections which do not implement it
+/// pay no royalties.
+#[ink::trait_definition]
+pub trait PSP34Royalty {
+    /// the receiver of the royalty and the royalty owed for a sale of the token
+    /// at `sale_price`, if the token pays royalties
+    #[ink(message)]
+    fn royalty_info(&self, id: Id, sale_price: u128) -> Option<(AccountId, u128)>;
+}
+
+/// Transfers from an explicit owner (not part of the standard)
+///
+/// `PSP34::transfer` moves a token from whoever owns it, so an operator approved by
+/// several owners cannot tell whose token it moves. This transfer fails unless `from`
+/// owns the token.
+#[ink::trait_definition]
+pub trait PSP34TransferFrom {
+    /// move the token `id` from `from` to `to`, fails unless `from` owns it
+    /// callable by `from` or an account approved by them
+    #[ink(message)]
+    fn transfer_from(
+        &mut self,
+        from: AccountId,
+        to: AccountId,
+        id: Id,
+        data: Vec<u8>,
+    ) -> Result<(), PSP34Error>;
+}
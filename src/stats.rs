@@ -0,0 +1,246 @@
+use crate::types::RoundNumber;
+use ink::{
+    prelude::vec::Vec,
+    primitives::AccountId,
+    storage::{Lazy, Mapping},
+};
+
+/// the number of players ranked on the leaderboard
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// the number of most recent rounds whose aggregates are kept
+pub const ROUND_HISTORY: RoundNumber = 256;
+
+/// A player's totals over every round they played
+///
+/// A round is won when the player was paid more than they staked in it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, scale::Decode, scale::Encode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct PlayerStats {
+    /// the rounds played
+    pub rounds: u32,
+    /// the rounds won
+    pub wins: u32,
+    /// the rounds lost
+    pub losses: u32,
+    /// the sum of the stakes
+    pub staked: u128,
+    /// the sum of the payouts
+    pub paid: u128,
+    /// the sum of the profits of the rounds won, which never decreases
+    pub winnings: u128,
+    /// the rounds won in a row, up to the last round played
+    pub win_streak: u32,
+    /// the rounds lost in a row, up to the last round played
+    pub loss_streak: u32,
+    /// the most rounds won in a row
+    pub longest_win_streak: u32,
+    /// the most rounds lost in a row
+    pub longest_loss_streak: u32,
+    /// the last round played
+    pub last_round: RoundNumber,
+}
+
+/// The totals of every player of a round
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, scale::Decode, scale::Encode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct RoundStats {
+    pub round: RoundNumber,
+    /// the players of the round
+    pub players: u32,
+    /// the players who won the round
+    pub winners: u32,
+    /// the sum of the stakes
+    pub staked: u128,
+    /// the sum of the payouts
+    pub paid: u128,
+}
+
+/// Statistics of a round-based game, in bounded storage
+///
+/// Each player has a single `PlayerStats` entry, only the last `ROUND_HISTORY`
+/// rounds are kept, and the leaderboard ranks the `LEADERBOARD_SIZE` players with
+/// the most `winnings`. As winnings never decrease, updating the leaderboard as
+/// rounds are recorded keeps it exact.
+#[ink::storage_item]
+#[derive(Debug, Default)]
+pub struct GameStats {
+    /// the totals of each player
+    players: Mapping<AccountId, PlayerStats>,
+    /// the totals of each recent round, at its position in the history
+    rounds: Mapping<RoundNumber, RoundStats>,
+    /// the top players and their winnings, most winnings first
+    leaderboard: Lazy<Vec<(AccountId, u128)>>,
+}
+
+impl GameStats {
+    /// record the outcome of a round for a player, once per player and round
+    ///
+    /// * `player`: the player
+    /// * `round`: the round played
+    /// * `staked`: the sum of the player's stakes in the round
+    /// * `paid`: the sum of the player's payouts in the round
+    ///
+    pub fn record(&mut self, player: AccountId, round: RoundNumber, staked: u128, paid: u128) {
+        let won = paid > staked;
+        let mut stats = self.player(player);
+        stats.rounds = stats.rounds.saturating_add(1);
+        stats.staked = stats.staked.saturating_add(staked);
+        stats.paid = stats.paid.saturating_add(paid);
+        stats.last_round = round;
+        if won {
+            stats.wins = stats.wins.saturating_add(1);
+            stats.winnings = stats.winnings.saturating_add(paid.saturating_sub(staked));
+            stats.win_streak = stats.win_streak.saturating_add(1);
+            stats.loss_streak = 0;
+            stats.longest_win_streak = stats.longest_win_streak.max(stats.win_streak);
+        } else {
+            stats.losses = stats.losses.saturating_add(1);
+            stats.loss_streak = stats.loss_streak.saturating_add(1);
+            stats.win_streak = 0;
+            stats.longest_loss_streak = stats.longest_loss_streak.max(stats.loss_streak);
+        }
+        self.players.insert(player, &stats);
+        if won {
+            self.rank(player, stats.winnings);
+        }
+
+        // an older round than the one kept at its position is no longer tracked
+        let position = round % ROUND_HISTORY;
+        let mut totals = match self.rounds.get(position) {
+            Some(totals) if totals.round == round => totals,
+            Some(totals) if totals.round > round => return,
+            _ => RoundStats { round, ..Default::default() },
+        };
+        totals.players = totals.players.saturating_add(1);
+        totals.winners = totals.winners.saturating_add(won as u32);
+        totals.staked = totals.staked.saturating_add(staked);
+        totals.paid = totals.paid.saturating_add(paid);
+        self.rounds.insert(position, &totals);
+    }
+
+    /// the totals of the player, zeroed if they have not played
+    pub fn player(&self, player: AccountId) -> PlayerStats {
+        self.players.get(player).unwrap_or_default()
+    }
+
+    /// the totals of the round, if any player was recorded in it
+    /// and it is one of the last `ROUND_HISTORY` rounds recorded
+    pub fn round(&self, round: RoundNumber) -> Option<RoundStats> {
+        self.rounds
+            .get(round % ROUND_HISTORY)
+            .filter(|totals| totals.round == round)
+    }
+
+    /// the top players and their winnings, most winnings first
+    pub fn leaderboard(&self) -> Vec<(AccountId, u128)> {
+        self.leaderboard.get().unwrap_or_default()
+    }
+
+    /// move the player to their place on the leaderboard,
+    /// if their winnings rank among the top players
+    fn rank(&mut self, player: AccountId, winnings: u128) {
+        let mut leaderboard = self.leaderboard();
+        leaderboard.retain(|(who, _)| *who != player);
+        // ties are ranked by who got there first
+        let position = leaderboard
+            .iter()
+            .position(|(_, score)| *score < winnings)
+            .unwrap_or(leaderboard.len());
+        if position >= LEADERBOARD_SIZE {
+            return;
+        }
+        leaderboard.insert(position, (player, winnings));
+        leaderboard.truncate(LEADERBOARD_SIZE);
+        self.leaderboard.set(&leaderboard);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(n: u8) -> AccountId {
+        AccountId::from([n; 32])
+    }
+
+    #[ink::test]
+    fn streaks_and_totals_are_tracked() {
+        let mut stats = GameStats::default();
+        let alice = account(1);
+        for (round, paid) in [(1, 200), (2, 150), (3, 0), (4, 0), (5, 0), (6, 300)] {
+            stats.record(alice, round, 100, paid);
+        }
+        assert_eq!(
+            stats.player(alice),
+            PlayerStats {
+                rounds: 6,
+                wins: 3,
+                losses: 3,
+                staked: 600,
+                paid: 650,
+                winnings: 100 + 50 + 200,
+                win_streak: 1,
+                loss_streak: 0,
+                longest_win_streak: 2,
+                longest_loss_streak: 3,
+                last_round: 6,
+            }
+        );
+        // breaking even is not a win
+        stats.record(alice, 7, 100, 100);
+        assert_eq!(stats.player(alice).loss_streak, 1);
+        assert_eq!(stats.player(account(2)), PlayerStats::default());
+    }
+
+    #[ink::test]
+    fn rounds_are_aggregated_within_the_history() {
+        let mut stats = GameStats::default();
+        stats.record(account(1), 3, 100, 0);
+        stats.record(account(2), 3, 50, 100);
+        assert_eq!(
+            stats.round(3),
+            Some(RoundStats { round: 3, players: 2, winners: 1, staked: 150, paid: 100 })
+        );
+        assert_eq!(stats.round(4), None);
+
+        // a round `ROUND_HISTORY` later takes the place of round 3
+        stats.record(account(1), 3 + ROUND_HISTORY, 10, 0);
+        assert_eq!(stats.round(3), None);
+        assert_eq!(stats.round(3 + ROUND_HISTORY).unwrap().players, 1);
+        // and round 3 is no longer tracked
+        stats.record(account(3), 3, 10, 0);
+        assert_eq!(stats.round(3), None);
+        assert_eq!(stats.player(account(3)).rounds, 1);
+    }
+
+    #[ink::test]
+    fn the_leaderboard_ranks_the_most_winnings() {
+        let mut stats = GameStats::default();
+        // players 1 to 12 each win their number in round 1
+        for n in 1..=12u8 {
+            stats.record(account(n), 1, 10, 10 + u128::from(n));
+        }
+        let ranked = |stats: &GameStats| {
+            let number = |who: &AccountId| (1..=12u8).find(|n| account(*n) == *who);
+            stats.leaderboard().iter().filter_map(|(who, _)| number(who)).collect::<Vec<_>>()
+        };
+        assert_eq!(ranked(&stats), vec![12, 11, 10, 9, 8, 7, 6, 5, 4, 3]);
+
+        // losses do not change the ranking
+        stats.record(account(12), 2, 1_000, 0);
+        assert_eq!(stats.leaderboard()[0], (account(12), 12));
+        // player 1 climbs from outside the leaderboard to the top
+        stats.record(account(1), 2, 10, 30);
+        assert_eq!(ranked(&stats), vec![1, 12, 11, 10, 9, 8, 7, 6, 5, 4]);
+        // player 5 ties with player 11, who got there first
+        stats.record(account(5), 2, 10, 16);
+        assert_eq!(ranked(&stats), vec![1, 12, 11, 5, 10, 9, 8, 7, 6, 4]);
+    }
+}
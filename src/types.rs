@@ -67,4 +67,66 @@ pub struct EventConfig {
     // pub initial_block: BlockNumber,
     /// the interval (in slots) between events
     pub interval: SlotNumber,
-}
\ No newline at end of file
+}
+/// a share expressed in basis points, where `MAX_BASIS_POINTS` is the whole
+pub type BasisPoints = u16;
+
+/// 100%, in basis points
+pub const MAX_BASIS_POINTS: BasisPoints = 10_000;
+
+/// the share of `amount` worth `bps` basis points, rounded down
+/// e.g. a 2.5% fee on a price is `share_of(price, 250)`
+pub fn share_of(amount: u128, bps: BasisPoints) -> u128 {
+    const MAX: u128 = MAX_BASIS_POINTS as u128;
+    let bps = u128::from(bps);
+    // split the product so that it cannot overflow
+    (amount / MAX).saturating_mul(bps).saturating_add((amount % MAX).saturating_mul(bps) / MAX)
+}
+
+/// a wide id derived from a drand pulse, which never repeats for a nonce
+///
+/// The high 64 bits hash the pulse, the nonce and the counter, so ids cannot be
+/// guessed before the pulse is known. The low 64 bits are the counter itself, so
+/// ids derived with the same nonce and an increasing counter never collide.
+///
+/// * `pulse`: the drand randomness, e.g. `self.env().extension().random()`
+/// * `nonce`: separates the ids of each contract, e.g. its account id
+/// * `counter`: the number of ids derived with the nonce so far
+///
+pub fn unique_id(pulse: &[u8; 32], nonce: &[u8], counter: u64) -> u128 {
+    let mut input = Vec::with_capacity(pulse.len().saturating_add(nonce.len()).saturating_add(8));
+    input.extend_from_slice(pulse);
+    input.extend_from_slice(nonce);
+    input.extend_from_slice(&counter.to_le_bytes());
+    let mut hash = [0u8; 32];
+    ink::env::hash_bytes::<ink::env::hash::Blake2x256>(&input, &mut hash);
+    let mut high = [0u8; 8];
+    high.copy_from_slice(&hash[..8]);
+    (u128::from(u64::from_le_bytes(high)) << 64) | u128::from(counter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique_ids_follow_the_counter() {
+        let (pulse, nonce) = ([7u8; 32], [1u8; 32]);
+        let ids = (0..1000)
+            .map(|counter| unique_id(&pulse, &nonce, counter))
+            .collect::<ink::prelude::collections::BTreeSet<_>>();
+        assert_eq!(ids.len(), 1000);
+        assert!(ids.iter().all(|id| (*id as u64) < 1000));
+        // the same inputs always derive the same id
+        assert_eq!(unique_id(&pulse, &nonce, 5), unique_id(&pulse, &nonce, 5));
+    }
+
+    #[test]
+    fn unique_ids_depend_on_the_pulse_and_nonce() {
+        let id = unique_id(&[7u8; 32], &[1u8; 32], 0);
+        assert_ne!(id, unique_id(&[8u8; 32], &[1u8; 32], 0));
+        assert_ne!(id, unique_id(&[7u8; 32], &[2u8; 32], 0));
+        assert_eq!(id as u64, 0);
+        assert_ne!(id >> 64, 0);
+    }
+}
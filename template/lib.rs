@@ -1,9 +1,9 @@
 //! This is a template that demonstrates how to fetch the latest randomness from the drand bridge pallet.
 //! This contract demonstrates:
-//! 
-//!     1) how to configure a contract to use the required chain extension
-//!     2) how to read/write the latest randomness
-//! 
+//!
+//! 1) how to configure a contract to use the required chain extension
+//! 2) how to read/write the latest randomness
+//!
 
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 use idl_contract_extension::ext::DrandEnvironment;